@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+/// Server software behind a Mastodon-compatible API.  Pleroma, GoToSocial and Firefish implement the same client
+/// API as Mastodon with differences in limits, OAuth scopes and endpoints, handled here.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum Software {
+    #[default]
+    Mastodon,
+    /// Pleroma and its fork Akkoma.
+    Pleroma,
+    GoToSocial,
+    /// Firefish (formerly Calckey), through its Mastodon API layer.
+    Firefish,
+}
+
+impl Software {
+    pub const ALL: [Software; 4] = [
+        Software::Mastodon,
+        Software::Pleroma,
+        Software::GoToSocial,
+        Software::Firefish,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Software::Mastodon => "Mastodon",
+            Software::Pleroma => "Pleroma / Akkoma",
+            Software::GoToSocial => "GoToSocial",
+            Software::Firefish => "Firefish",
+        }
+    }
+
+    /// Recognizes the software from the `version` of `/api/v1/instance`, which compatible servers report like
+    /// "2.7.2 (compatible; Pleroma 2.5.0)".
+    pub fn from_version(version: &str) -> Self {
+        let version = version.to_ascii_lowercase();
+        if version.contains("pleroma") || version.contains("akkoma") {
+            Software::Pleroma
+        } else if version.contains("gotosocial") {
+            Software::GoToSocial
+        } else if version.contains("firefish") || version.contains("calckey") {
+            Software::Firefish
+        } else {
+            Software::Mastodon
+        }
+    }
+
+    /// OAuth scopes to request for posting and for streaming notifications into the inbox.  GoToSocial and Firefish
+    /// only understand the coarse scopes.
+    pub fn scopes(self) -> &'static str {
+        match self {
+            Software::Mastodon | Software::Pleroma => "write:statuses read:notifications",
+            Software::GoToSocial | Software::Firefish => "write read",
+        }
+    }
+
+    /// Path of the instance information with the status limits.  Pleroma and Firefish only have the v1 endpoint,
+    /// which reports the length as `max_toot_chars`.
+    pub fn instance_path(self) -> &'static str {
+        match self {
+            Software::Mastodon | Software::GoToSocial => "/api/v2/instance",
+            Software::Pleroma | Software::Firefish => "/api/v1/instance",
+        }
+    }
+
+    /// Status length when the instance doesn't report one.
+    pub fn default_max_chars(self) -> usize {
+        match self {
+            Software::Mastodon => 500,
+            Software::Pleroma | Software::GoToSocial => 5000,
+            Software::Firefish => 3000,
+        }
+    }
+
+    /// Whether the server has `/api/v1/trends/tags`.
+    pub fn has_trends(self) -> bool {
+        matches!(self, Software::Mastodon | Software::Firefish)
+    }
+}
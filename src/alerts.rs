@@ -0,0 +1,48 @@
+use tracing::{error, info};
+
+use crate::history::PlatformResult;
+use crate::posts::PostError;
+use crate::{mail, settings, timezone};
+
+/// Results of a scheduled post worth an alert: failures the app won't retry on its own.  Rate-limited platforms are
+/// left out, since the post is held and sent once the limit resets.
+pub fn failures(results: &[PlatformResult]) -> Vec<&PlatformResult> {
+    results
+        .iter()
+        .filter(|result| !result.success && result.error != Some(PostError::RateLimited))
+        .collect()
+}
+
+/// Emails `settings.alert_email_to` that a scheduled post failed on some platforms.  Does nothing when no address or
+/// SMTP server is configured; a failure to send is logged.
+pub async fn post_failed(text: &str, scheduled_at: chrono::DateTime<chrono::Utc>, failures: &[&PlatformResult]) {
+    let settings = settings::get();
+    if settings.alert_email_to.trim().is_empty() || settings.smtp_host.trim().is_empty() || failures.is_empty() {
+        return;
+    }
+
+    let platforms: Vec<&str> = failures.iter().map(|result| result.platform.as_str()).collect();
+    let mut body = format!(
+        "A post scheduled for {} failed on {}.\n\n",
+        timezone::format(scheduled_at),
+        platforms.join(", ")
+    );
+    for result in failures {
+        let reason = result.error.map_or("unknown".to_string(), |err| format!("{:?}", err));
+        body.push_str(&format!("- {}: {}\n", result.platform, reason));
+    }
+    body.push_str(&format!("\nThe post:\n\n{}\n", text));
+
+    match mail::send(
+        &settings,
+        &settings.alert_email_from,
+        &settings.alert_email_to,
+        &format!("multique: post failed on {}", platforms.join(", ")),
+        body,
+    )
+    .await
+    {
+        Ok(_) => info!(to = %settings.alert_email_to, "Sent a failure alert"),
+        Err(err) => error!(error = %err, "Failed to send a failure alert"),
+    }
+}
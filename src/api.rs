@@ -0,0 +1,394 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use tracing::{debug, error, info, warn};
+
+use crate::posts::PostError;
+use crate::{cli, history, posts, publish, scheduler, settings, shared, timezone};
+
+/// The browser UI served at `/`: a composer, platform checkboxes and the schedule, using the API below.
+const REMOTE_UI: &str = include_str!("remote.html");
+
+/// Requests larger than this are refused.
+const MAX_REQUEST_BYTES: usize = 1 << 20;
+
+/// What a token may do.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum Scope {
+    /// Read the schedule and history.
+    #[default]
+    Read,
+    /// Also post and schedule.
+    Write,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 2] = [Scope::Read, Scope::Write];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Scope::Read => "Read only",
+            Scope::Write => "Read and write",
+        }
+    }
+
+    fn allows(self, needed: Scope) -> bool {
+        self == Scope::Write || needed == Scope::Read
+    }
+}
+
+/// A named bearer token for the API, kept in settings until revoked.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ApiToken {
+    pub name: String,
+    pub token: String,
+    #[serde(default)]
+    pub scope: Scope,
+    #[serde(default)]
+    pub created_at: DateTime<Utc>,
+}
+
+impl ApiToken {
+    /// A new token with a random secret.
+    pub fn new(name: &str, scope: Scope) -> Self {
+        let bytes: [u8; 32] = rand::random();
+        ApiToken {
+            name: name.to_string(),
+            token: bytes.iter().map(|b| format!("{:02x}", b)).collect(),
+            scope,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// Body of `POST /posts`.
+#[derive(Deserialize)]
+struct NewPost {
+    text: String,
+    /// Platform names as on the command line; every known platform when missing.
+    #[serde(default)]
+    platforms: Option<Vec<String>>,
+    /// "YYYY-MM-DD HH:MM" in the configured time zone to schedule the post instead of sending it now.
+    #[serde(default)]
+    at: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ApiError {
+    error: String,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    authorization: Option<String>,
+    content_type: Option<String>,
+    body: Vec<u8>,
+}
+
+/// Serves a small JSON API on `address` for scripts and other tools on the machine:
+///
+/// - `GET /health`: whether the daemon is up and online
+/// - `GET /healthz`, `GET /readyz`: liveness and readiness for orchestrators, answering 503 when failing
+/// - `GET /`: a page for posting from a browser, e.g. a phone
+/// - `GET /schedule`, `GET /history`, `GET /platforms`: the scheduled posts, post history and platforms
+/// - `POST /posts`: `{"text", "platforms"?, "at"?}` posts now (answering with the history entry) or schedules
+///
+/// Posting always needs a write token as `Authorization: Bearer <token>` and a JSON body.  Once
+/// `settings.api_tokens` has tokens, every other request but the health checks needs one too.  Runs until the
+/// listener fails.
+pub async fn serve(state: Arc<Mutex<posts::AppState>>, address: &str) -> std::io::Result<()> {
+    let listener = TcpListener::bind(address).await?;
+    info!(address, "Local API listening");
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let state = Arc::clone(&state);
+        tokio::spawn(async move {
+            if let Err(err) = handle(stream, &state).await {
+                debug!(%peer, error = ?err, "API connection failed");
+            }
+        });
+    }
+}
+
+async fn handle(mut stream: TcpStream, state: &Arc<Mutex<posts::AppState>>) -> std::io::Result<()> {
+    let (status, content_type, body) = match read_request(&mut stream).await? {
+        // The page itself holds nothing private; its requests carry the token
+        Some(request) if request.method == "GET" && request.path == "/" => {
+            (200, "text/html; charset=utf-8", REMOTE_UI.to_string())
+        }
+        Some(request) => {
+            let (status, body) = respond(&request, state).await;
+            (status, "application/json", body)
+        }
+        None => (400, "application/json", error("Malformed request")),
+    };
+    let reason = match status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        413 => "Payload Too Large",
+        415 => "Unsupported Media Type",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    };
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+async fn respond(request: &Request, state: &Arc<Mutex<posts::AppState>>) -> (u16, String) {
+    // Probes from orchestrators come without a token
+    let open = matches!(request.path.as_str(), "/health" | "/healthz" | "/readyz");
+    let tokens = settings::get().api_tokens;
+    // Any web page open in a browser on this machine can send a simple POST to localhost, so posting always needs a
+    // token, and a JSON body, which browsers only send cross-origin after a preflight this server never allows
+    if request.method != "GET" {
+        if tokens.is_empty() {
+            warn!(path = %request.path, "API write request while no tokens are set");
+            return (
+                401,
+                error("Posting through the API needs a read and write token; create one under Settings"),
+            );
+        }
+        let is_json = request
+            .content_type
+            .as_deref()
+            .is_some_and(|content_type| content_type.starts_with("application/json"));
+        if !is_json {
+            return (415, error("Send the body as application/json"));
+        }
+    }
+    if !open && !tokens.is_empty() {
+        let needed = if request.method == "GET" {
+            Scope::Read
+        } else {
+            Scope::Write
+        };
+        let presented = request
+            .authorization
+            .as_deref()
+            .and_then(|value| value.strip_prefix("Bearer "));
+        let Some(token) = tokens.iter().find(|token| Some(token.token.as_str()) == presented) else {
+            warn!(path = %request.path, "API request without a valid token");
+            return (401, error("Missing or wrong API token"));
+        };
+        if !token.scope.allows(needed) {
+            warn!(path = %request.path, token = %token.name, "API token is read-only");
+            return (403, error("This token is read-only"));
+        }
+        debug!(method = %request.method, path = %request.path, token = %token.name, "API request");
+    }
+    if request.body.len() > MAX_REQUEST_BYTES {
+        return (413, error("Request too large"));
+    }
+
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/health") => {
+            let offline = state.lock().await.offline;
+            (
+                200,
+                serde_json::json!({ "status": "ok", "offline": offline }).to_string(),
+            )
+        }
+        ("GET", "/healthz") => healthz(state).await,
+        ("GET", "/readyz") => readyz(state).await,
+        ("GET", "/schedule") => (200, json(&scheduler::load_schedule())),
+        ("GET", "/history") => (200, json(&state.lock().await.history)),
+        ("GET", "/platforms") => {
+            let state = state.lock().await;
+            let platforms: Vec<_> = cli::known_platforms()
+                .into_iter()
+                .map(|name| serde_json::json!({ "name": name, "authorized": cli::is_authorized(&state, name) }))
+                .collect();
+            (200, json(&platforms))
+        }
+        ("POST", "/posts") => match serde_json::from_slice::<NewPost>(&request.body) {
+            Ok(post) => new_post(post, state).await,
+            Err(err) => (
+                400,
+                error(&format!("Expected {{\"text\", \"platforms\", \"at\"}}: {}", err)),
+            ),
+        },
+        _ => (404, error("No such endpoint")),
+    }
+}
+
+/// Liveness: fails once the scheduler is stuck, so an orchestrator restarts the daemon.
+async fn healthz(state: &Arc<Mutex<posts::AppState>>) -> (u16, String) {
+    let checked_at = state.lock().await.scheduler_checked_at;
+    let stalled = scheduler::is_stalled(checked_at);
+    let body = serde_json::json!({
+        "status": if stalled { "stalled" } else { "ok" },
+        "scheduler_checked_at": checked_at,
+    });
+    (if stalled { 503 } else { 200 }, body.to_string())
+}
+
+/// Readiness: the scheduler is running, the network is up, and at least one platform has a usable token.  A token is
+/// reported unusable while the platform's latest post was refused for authorization.
+async fn readyz(state: &Arc<Mutex<posts::AppState>>) -> (u16, String) {
+    let state = state.lock().await;
+    let tokens: BTreeMap<&str, bool> = cli::known_platforms()
+        .into_iter()
+        .map(|platform| {
+            let refused = state
+                .history
+                .iter()
+                .rev()
+                .find_map(|entry| entry.results.iter().find(|result| result.platform == platform))
+                .is_some_and(|result| result.error == Some(PostError::Unauthorized));
+            (platform, cli::is_authorized(&state, platform) && !refused)
+        })
+        .collect();
+    let scheduler_running = state.scheduler_checked_at.is_some() && !scheduler::is_stalled(state.scheduler_checked_at);
+    let ready = scheduler_running && !state.offline && tokens.values().any(|valid| *valid);
+    let body = serde_json::json!({
+        "ready": ready,
+        "offline": state.offline,
+        "scheduler_checked_at": state.scheduler_checked_at,
+        "tokens": tokens,
+    });
+    (if ready { 200 } else { 503 }, body.to_string())
+}
+
+async fn new_post(post: NewPost, state: &Arc<Mutex<posts::AppState>>) -> (u16, String) {
+    if post.text.trim().is_empty() {
+        return (400, error("Nothing to post"));
+    }
+    let requested = post.platforms.unwrap_or_else(|| vec!["all".to_string()]);
+    let platforms = match cli::resolve_platforms(&requested) {
+        Ok(platforms) => platforms,
+        Err(err) => return (400, error(&err)),
+    };
+    let options = posts::PostOptions::default();
+    let idempotency_key = history::new_idempotency_key();
+
+    match post.at {
+        Some(at) => match timezone::parse(&at) {
+            Ok(scheduled_at) => {
+                let id = scheduler::schedule(
+                    &post.text,
+                    &platforms,
+                    scheduled_at,
+                    &idempotency_key,
+                    None,
+                    None,
+                    &options,
+                );
+                info!(id, "Scheduled a post from the API");
+                (
+                    201,
+                    serde_json::json!({ "id": id, "scheduled_at": scheduled_at }).to_string(),
+                )
+            }
+            Err(err) => (400, error(&err)),
+        },
+        None => {
+            let entry = publish::publish(
+                state,
+                &post.text,
+                &platforms,
+                &idempotency_key,
+                &options,
+                &shared::current_user(),
+            )
+            .await;
+            match entry {
+                Some(entry) => (200, json(&entry)),
+                None => (400, error("None of the platforms could be posted to")),
+            }
+        }
+    }
+}
+
+/// Reads one request: the request line, the headers this API uses and a `Content-Length` body.  `None` when it
+/// isn't HTTP.
+async fn read_request(stream: &mut TcpStream) -> std::io::Result<Option<Request>> {
+    let mut data = Vec::new();
+    let mut buffer = [0; 8192];
+    let header_end = loop {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            return Ok(None);
+        }
+        data.extend_from_slice(&buffer[..read]);
+        if let Some(end) = data.windows(4).position(|window| window == b"\r\n\r\n") {
+            break end;
+        }
+        if data.len() > MAX_REQUEST_BYTES {
+            return Ok(None);
+        }
+    };
+
+    let head = String::from_utf8_lossy(&data[..header_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let (Some(method), Some(target)) = (request_line.next(), request_line.next()) else {
+        return Ok(None);
+    };
+    let mut content_length = 0;
+    let mut authorization = None;
+    let mut content_type = None;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => content_length = value.trim().parse().unwrap_or(0),
+            "authorization" => authorization = Some(value.trim().to_string()),
+            "content-type" => content_type = Some(value.trim().to_ascii_lowercase()),
+            _ => {}
+        }
+    }
+
+    let mut body = data[header_end + 4..].to_vec();
+    let wanted = content_length.min(MAX_REQUEST_BYTES + 1);
+    while body.len() < wanted {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        body.extend_from_slice(&buffer[..read]);
+    }
+    body.truncate(wanted);
+
+    Ok(Some(Request {
+        method: method.to_string(),
+        path: target.split('?').next().unwrap_or_default().to_string(),
+        authorization,
+        content_type,
+        body,
+    }))
+}
+
+fn json<T: Serialize>(value: &T) -> String {
+    match serde_json::to_string(value) {
+        Ok(json) => json,
+        Err(err) => {
+            error!(error = %err, "Failed to serialize an API response");
+            error("Internal error")
+        }
+    }
+}
+
+fn error(message: &str) -> String {
+    serde_json::to_string(&ApiError {
+        error: message.to_string(),
+    })
+    .expect("Failed to serialize an API error")
+}
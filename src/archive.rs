@@ -0,0 +1,123 @@
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use tracing::{info, warn};
+
+use crate::history::{self, HistoryEntry, PlatformResult};
+use crate::media::ImageAttachment;
+
+/// Folder of the archive the attachment copies go in.
+const MEDIA_DIR: &str = "media";
+
+/// A post as written to its JSON file: the history entry, with the images pointing at their copies.
+#[derive(Serialize)]
+struct ArchivedPost<'a> {
+    id: &'a str,
+    posted_at: String,
+    author: Option<&'a str>,
+    text: &'a str,
+    results: &'a [PlatformResult],
+    images: Vec<ImageAttachment>,
+}
+
+/// Writes every post in history to `dir`, as a Markdown file (front matter with the date, platforms and links, then
+/// the text) and a JSON file each, named after the post's date and id.  Attached images are copied into
+/// `media/` next to them, so the archive stands on its own; an image no longer on disk keeps its original path.
+/// Posts that failed everywhere are left out.  Returns the number of posts written.
+pub fn export(dir: &Path) -> Result<usize, String> {
+    let media_dir = dir.join(MEDIA_DIR);
+    fs::create_dir_all(&media_dir).map_err(|err| format!("Failed to create {}: {}", media_dir.display(), err))?;
+
+    let mut count = 0;
+    for entry in history::load_history() {
+        if !entry.results.iter().any(|result| result.success) {
+            continue;
+        }
+        let name = file_name(&entry);
+        let images: Vec<ImageAttachment> = entry
+            .images
+            .iter()
+            .enumerate()
+            .map(|(i, image)| copy_image(image, &media_dir, &format!("{}-{}", name, i + 1)))
+            .collect();
+
+        let post = ArchivedPost {
+            id: &entry.id,
+            posted_at: entry.posted_at.to_rfc3339(),
+            author: entry.author.as_deref(),
+            text: &entry.text,
+            results: &entry.results,
+            images,
+        };
+        let json = serde_json::to_string_pretty(&post).expect("Failed to serialize archived post");
+        write(&dir.join(format!("{}.json", name)), &json)?;
+        write(&dir.join(format!("{}.md", name)), &markdown(&post))?;
+        count += 1;
+    }
+    info!(dir = %dir.display(), posts = count, "Exported archive");
+    Ok(count)
+}
+
+/// "2025-03-30-0915-<id>": sorts by date, and stays unique for posts sent in the same minute.
+fn file_name(entry: &HistoryEntry) -> String {
+    let id: String = entry
+        .id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect();
+    format!("{}-{}", entry.posted_at.format("%Y-%m-%d-%H%M"), id)
+}
+
+/// Copies an image into the archive as `<name>.<extension>`, returning the attachment pointing at the copy (relative
+/// to the archive), or the original when it can't be copied.
+fn copy_image(image: &ImageAttachment, media_dir: &Path, name: &str) -> ImageAttachment {
+    let source = Path::new(&image.path);
+    let extension = source.extension().and_then(|ext| ext.to_str()).unwrap_or("img");
+    let file = format!("{}.{}", name, extension.to_ascii_lowercase());
+    match fs::copy(source, media_dir.join(&file)) {
+        Ok(_) => ImageAttachment {
+            path: format!("{}/{}", MEDIA_DIR, file),
+            alt: image.alt.clone(),
+        },
+        Err(err) => {
+            warn!(path = %image.path, error = ?err, "Failed to copy an image into the archive");
+            image.clone()
+        }
+    }
+}
+
+/// The post as Markdown with YAML front matter, as static site generators read it.  Values are written as JSON
+/// strings, which YAML reads as they are.
+fn markdown(post: &ArchivedPost) -> String {
+    let quote = |value: &str| serde_json::to_string(value).expect("Failed to quote a string");
+    let mut front = vec![
+        format!("date: {}", quote(&post.posted_at)),
+        format!("id: {}", quote(post.id)),
+    ];
+    if let Some(author) = post.author {
+        front.push(format!("author: {}", quote(author)));
+    }
+    let posted: Vec<&PlatformResult> = post.results.iter().filter(|result| result.success).collect();
+    front.push("platforms:".to_string());
+    front.extend(posted.iter().map(|result| format!("  - {}", quote(&result.platform))));
+    if posted.iter().any(|result| result.url.is_some()) {
+        front.push("links:".to_string());
+        front.extend(posted.iter().filter_map(|result| {
+            Some(format!(
+                "  {}: {}",
+                quote(&result.platform),
+                quote(result.url.as_deref()?)
+            ))
+        }));
+    }
+
+    let mut body = post.text.to_string();
+    for image in &post.images {
+        body.push_str(&format!("\n\n![{}]({})", image.alt.replace(['[', ']'], ""), image.path));
+    }
+    format!("---\n{}\n---\n\n{}\n", front.join("\n"), body)
+}
+
+fn write(path: &Path, contents: &str) -> Result<(), String> {
+    fs::write(path, contents).map_err(|err| format!("Failed to write {}: {}", path.display(), err))
+}
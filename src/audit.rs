@@ -0,0 +1,57 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::io::Write;
+use tracing::error;
+
+use crate::profiles;
+use crate::shared;
+
+/// One JSON object per line.  Only ever appended to, so it can't be edited from the app; it lives in the shared data
+/// folder when one is set, so a team gets a single trail.
+const AUDIT_FILE: &str = "audit.log";
+
+/// Kind of action recorded in the audit log.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Authorize,
+    Refresh,
+    Post,
+    Edit,
+    Delete,
+    Settings,
+    Import,
+}
+
+#[derive(Serialize)]
+struct Event<'a> {
+    at: DateTime<Utc>,
+    user: String,
+    profile: String,
+    action: Action,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    platform: Option<&'a str>,
+    detail: &'a str,
+}
+
+/// Appends an action to the audit log, with the time, user and profile.  Failing to write is logged, never fatal.
+pub fn record(action: Action, platform: Option<&str>, detail: &str) {
+    let event = Event {
+        at: Utc::now(),
+        user: shared::current_user(),
+        profile: profiles::active(),
+        action,
+        platform,
+        detail,
+    };
+    let line = serde_json::to_string(&event).expect("Failed to serialize audit event");
+    let written = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(shared::path(AUDIT_FILE))
+        .and_then(|mut file| writeln!(file, "{}", line));
+    if let Err(err) = written {
+        error!(error = ?err, ?action, "Failed to write to the audit log");
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::{HashMap, HashSet};
+
+#[cfg(feature = "bluesky")]
+use crate::bluesky;
+use crate::history::HistoryEntry;
+#[cfg(feature = "mastodon")]
+use crate::mastodon;
+#[cfg(feature = "twitter")]
+use crate::twitter;
+
+/// Most hashtags offered at once.
+const MAX_HASHTAGS: usize = 8;
+
+/// A handle offered while a mention is typed.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Suggestion {
+    pub platform: String,
+    /// As it's written in a post on that platform, `@` included.
+    pub handle: String,
+    pub name: String,
+}
+
+/// The word being typed at the end of `text` when it starts with `sigil`, without the sigil.  Nothing is being typed
+/// once the text ends in whitespace.
+pub fn typing(text: &str, sigil: char) -> Option<&str> {
+    if text.ends_with(char::is_whitespace) {
+        return None;
+    }
+    let word = text.rsplit(char::is_whitespace).next()?;
+    let query = word.strip_prefix(sigil)?;
+    (!query.is_empty()).then_some(query)
+}
+
+/// Replaces the `sigil` word being typed at the end of `text` with `completion`, followed by a space.
+pub fn complete(text: &mut String, sigil: char, completion: &str) {
+    if let Some(query) = typing(text, sigil) {
+        text.truncate(text.len() - query.len() - sigil.len_utf8());
+        text.push_str(completion);
+        text.push(' ');
+    }
+}
+
+/// Hashtags starting with `query` (ignoring case): the ones used in `history`, most used first, then `trending`
+/// ones not used yet.  Tags come without the `#`.
+pub fn hashtags(query: &str, history: &[HistoryEntry], trending: &[String]) -> Vec<String> {
+    let query = query.to_lowercase();
+    let matches = |tag: &str| tag.to_lowercase().starts_with(&query) && tag.len() > query.len();
+
+    let mut uses: HashMap<String, (usize, String)> = HashMap::new();
+    for tag in history.iter().flat_map(|entry| tags_in(&entry.text)) {
+        if matches(tag) {
+            uses.entry(tag.to_lowercase()).or_insert((0, tag.to_string())).0 += 1;
+        }
+    }
+    let mut used: Vec<(usize, String)> = uses.into_values().collect();
+    used.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+    let mut suggestions: Vec<String> = used.into_iter().map(|(_, tag)| tag).collect();
+    for tag in trending {
+        if matches(tag) && !suggestions.iter().any(|used| used.eq_ignore_ascii_case(tag)) {
+            suggestions.push(tag.clone());
+        }
+    }
+    suggestions.truncate(MAX_HASHTAGS);
+    suggestions
+}
+
+/// The hashtags in `text`, without the `#`.
+fn tags_in(text: &str) -> impl Iterator<Item = &str> {
+    text.split_whitespace().filter_map(|word| {
+        let tag = word.strip_prefix('#')?;
+        let end = tag
+            .char_indices()
+            .find(|(_, c)| !(c.is_alphanumeric() || *c == '_'))
+            .map_or(tag.len(), |(i, _)| i);
+        (end > 0).then(|| &tag[..end])
+    })
+}
+
+/// Handles starting with `query` on each of `platforms` that can look them up, in the platform's own format.
+pub async fn mentions(query: &str, platforms: &[String], bluesky_token: Option<&str>) -> Vec<Suggestion> {
+    let searches = platforms
+        .iter()
+        .map(|platform| search_handles(platform, query, bluesky_token));
+    let mut suggestions: Vec<Suggestion> = futures::future::join_all(searches).await.concat();
+    // Accounts on several Mastodon instances find the same people
+    let mut seen = HashSet::new();
+    suggestions.retain(|suggestion| seen.insert(suggestion.handle.clone()));
+    suggestions
+}
+
+#[cfg_attr(
+    not(all(feature = "twitter", feature = "bluesky", feature = "mastodon")),
+    allow(unused_variables)
+)]
+async fn search_handles(platform: &str, query: &str, bluesky_token: Option<&str>) -> Vec<Suggestion> {
+    let found: Vec<(String, String)> = match platform {
+        #[cfg(feature = "twitter")]
+        "Twitter" => match twitter::load_bearer_token() {
+            Some(token) => twitter::lookup_user(&token, query).await.into_iter().collect(),
+            None => Vec::new(),
+        },
+        #[cfg(feature = "bluesky")]
+        "Bluesky" => match bluesky_token {
+            Some(token) => bluesky::search_handles(token, query).await,
+            None => Vec::new(),
+        },
+        #[cfg(feature = "mastodon")]
+        "Mastodon" => mastodon::search_accounts(&mastodon::api_base_url(), query).await,
+        #[cfg(feature = "mastodon")]
+        name if name.starts_with(mastodon::ACCOUNT_PREFIX) => match mastodon::find_account(name) {
+            Some(account) => mastodon::search_accounts(&account.base_url(), query).await,
+            None => Vec::new(),
+        },
+        _ => Vec::new(),
+    };
+    found
+        .into_iter()
+        .map(|(handle, name)| Suggestion {
+            platform: platform.to_string(),
+            handle: format!("@{}", handle),
+            name,
+        })
+        .collect()
+}
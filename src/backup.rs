@@ -0,0 +1,116 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use tracing::{error, info, warn};
+
+use crate::{crypto, profiles};
+
+/// Contents of a credentials backup, before encryption.
+#[derive(Serialize, Deserialize)]
+struct CredentialBackup {
+    created_at: DateTime<Utc>,
+    /// Profile name -> token file name -> file contents.
+    profiles: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+fn collect() -> CredentialBackup {
+    let mut backup = CredentialBackup {
+        created_at: Utc::now(),
+        profiles: BTreeMap::new(),
+    };
+
+    for profile in profiles::list() {
+        let Ok(entries) = fs::read_dir(profiles::dir(&profile)) else {
+            continue;
+        };
+        for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+            let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            if !file_name.ends_with("_tokens.json") {
+                continue;
+            }
+            if let Ok(contents) = fs::read_to_string(&path) {
+                backup
+                    .profiles
+                    .entry(profile.clone())
+                    .or_default()
+                    .insert(file_name.to_string(), contents);
+            }
+        }
+    }
+
+    backup
+}
+
+/// Writes every profile's platform tokens to `path`, encrypted with `passphrase`.  Returns the number of token files
+/// backed up, or `None` on failure.
+pub fn backup_to(path: &Path, passphrase: &str) -> Option<usize> {
+    let backup = collect();
+    let count = backup.profiles.values().map(|files| files.len()).sum();
+    let sealed = crypto::seal(
+        passphrase,
+        &serde_json::to_vec(&backup).expect("Failed to serialize credentials backup"),
+    );
+    let json = serde_json::to_string_pretty(&sealed).expect("Failed to serialize sealed backup");
+
+    match fs::write(path, json) {
+        Ok(()) => {
+            info!(path = %path.display(), files = count, "Backed up credentials");
+            Some(count)
+        }
+        Err(err) => {
+            error!(path = %path.display(), error = ?err, "Failed to write credentials backup");
+            None
+        }
+    }
+}
+
+/// Restores the token files in a backup written by `backup_to`, recreating missing profiles.  Existing token files
+/// for the same platform are overwritten.  Returns the number of token files restored, or `None` on failure.
+pub fn restore_from(path: &Path, passphrase: &str) -> Option<usize> {
+    let sealed: crypto::Sealed = match fs::read_to_string(path).map(|json| serde_json::from_str(&json)) {
+        Ok(Ok(sealed)) => sealed,
+        Ok(Err(err)) => {
+            error!(path = %path.display(), error = ?err, "Not a credentials backup");
+            return None;
+        }
+        Err(err) => {
+            error!(path = %path.display(), error = ?err, "Failed to read credentials backup");
+            return None;
+        }
+    };
+    let Some(plaintext) = crypto::open(passphrase, &sealed) else {
+        error!(path = %path.display(), "Wrong passphrase or corrupted credentials backup");
+        return None;
+    };
+    let backup: CredentialBackup = match serde_json::from_slice(&plaintext) {
+        Ok(backup) => backup,
+        Err(err) => {
+            error!(path = %path.display(), error = ?err, "Credentials backup is malformed");
+            return None;
+        }
+    };
+
+    let mut restored = 0;
+    for (profile, files) in backup.profiles {
+        if !profiles::list().contains(&profile) && !profiles::create(&profile) {
+            warn!(profile = %profile, "Skipping profile with an unusable name");
+            continue;
+        }
+        for (file_name, contents) in files {
+            let target = profiles::dir(&profile).join(&file_name);
+            if !profiles::is_token_path(&target) {
+                warn!(profile = %profile, file = %file_name, "Skipping unexpected file in credentials backup");
+                continue;
+            }
+            fs::write(&target, contents).expect("Failed to write token file");
+            restored += 1;
+        }
+    }
+
+    info!(path = %path.display(), backed_up_at = %backup.created_at, files = restored, "Restored credentials");
+    Some(restored)
+}
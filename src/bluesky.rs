@@ -1,13 +1,32 @@
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::env;
-use std::fs;
-use std::path::Path;
+use std::ops::Range;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::Mutex;
+use tracing::{error, info, warn};
 
 const TOKEN_FILE: &str = "bluesky_tokens.json";
-use crate::posts;
+/// Upgrades of `TokenData` as stored by older versions; see `tokens::load`.
+const TOKEN_MIGRATIONS: &[tokens::Migration] = &[];
+const DEFAULT_API_BASE_URL: &str = "https://bsky.social";
+const MAX_TAG_CHARS: usize = 64; // Longest tag the app.bsky.richtext.facet lexicon accepts
+use crate::audit;
+use crate::engagement::Engagement;
+use crate::entities;
+use crate::http;
+use crate::limits;
+use crate::links;
+use crate::media;
+use crate::posts::{self, PostError, PostOptions};
+use crate::settings;
+use crate::tokens;
+
+/// The PDS/AppView to talk to, honoring the override in settings.
+fn api_base_url() -> String {
+    settings::api_base_url("bluesky", DEFAULT_API_BASE_URL)
+}
 
 #[derive(Serialize, Deserialize)]
 pub struct TokenData {
@@ -22,17 +41,11 @@ pub fn save_tokens(access_jwt: &str, refresh_jwt: &str, did: &str) {
         refresh_jwt: refresh_jwt.to_string(),
         did: did.to_string(),
     };
-    let json = serde_json::to_string(&token_data).expect("Failed to serialize token data");
-    fs::write(TOKEN_FILE, json).expect("Failed to write token file");
+    tokens::save(TOKEN_FILE, TOKEN_MIGRATIONS, &token_data);
 }
 
 pub fn load_tokens() -> Option<TokenData> {
-    if Path::new(TOKEN_FILE).exists() {
-        let json = fs::read_to_string(TOKEN_FILE).expect("Failed to read token file");
-        serde_json::from_str(&json).ok()
-    } else {
-        None
-    }
+    tokens::load(TOKEN_FILE, TOKEN_MIGRATIONS)
 }
 
 pub async fn refresh_access_token(refresh_jwt: &str) -> Option<TokenData> {
@@ -48,44 +61,64 @@ pub async fn refresh_access_token(refresh_jwt: &str) -> Option<TokenData> {
         did: String,
     }
 
-    let client = Client::new();
+    let client = http::client();
     let refresh_data = RefreshRequest {
         refreshJwt: refresh_jwt.to_string(),
     };
 
-    match client
-        .post("https://bsky.social/xrpc/com.atproto.server.refreshSession")
-        .json(&refresh_data)
-        .send()
-        .await
+    let started = Instant::now();
+    match http::send(
+        client
+            .post(format!("{}/xrpc/com.atproto.server.refreshSession", api_base_url()))
+            .json(&refresh_data),
+    )
+    .await
     {
         Ok(response) => {
-            if response.status().is_success() {
+            let status = response.status();
+            let duration_ms = started.elapsed().as_millis() as u64;
+            if status.is_success() {
                 let refresh_response: RefreshResponse = response.json().await.ok()?;
                 save_tokens(
                     &refresh_response.accessJwt,
                     &refresh_response.refreshJwt,
                     &refresh_response.did,
                 );
+                info!(
+                    platform = "bluesky",
+                    request = "refreshSession",
+                    status = status.as_u16(),
+                    duration_ms,
+                    "Refreshed access token"
+                );
+                audit::record(audit::Action::Refresh, Some("Bluesky"), "");
                 Some(TokenData {
                     access_jwt: refresh_response.accessJwt,
                     refresh_jwt: refresh_response.refreshJwt,
                     did: refresh_response.did,
                 })
             } else {
-                println!("Failed to refresh access token: {:?}", response.text().await);
+                let body = response.text().await.unwrap_or_default();
+                warn!(
+                    platform = "bluesky",
+                    request = "refreshSession",
+                    status = status.as_u16(),
+                    duration_ms,
+                    %body,
+                    "Failed to refresh access token"
+                );
                 None
             }
         }
         Err(err) => {
-            println!("Error refreshing token: {:?}", err);
+            error!(platform = "bluesky", request = "refreshSession", error = ?err, "Error refreshing token");
             None
         }
     }
 }
 
 pub async fn authorize_bluesky(state: Arc<Mutex<posts::AppState>>) -> Option<TokenData> {
-    let client = Client::new();
+    let client = http::client();
     let auth_data = create_auth_request();
 
     match send_auth_request(&client, &auth_data).await {
@@ -95,7 +128,7 @@ pub async fn authorize_bluesky(state: Arc<Mutex<posts::AppState>>) -> Option<Tok
             None
         }
         Err(err) => {
-            println!("Error during authorization: {:?}", err);
+            error!(platform = "bluesky", request = "createSession", error = ?err, "Error during authorization");
             None
         }
     }
@@ -110,6 +143,8 @@ async fn process_auth_success(response: reqwest::Response, state: Arc<Mutex<post
         );
 
         update_app_state(&state, &auth_response).await;
+        info!(platform = "bluesky", request = "createSession", "Authorized Bluesky");
+        audit::record(audit::Action::Authorize, Some("Bluesky"), "");
 
         Some(TokenData {
             access_jwt: auth_response.access_jwt,
@@ -117,7 +152,11 @@ async fn process_auth_success(response: reqwest::Response, state: Arc<Mutex<post
             did: auth_response.did,
         })
     } else {
-        println!("Failed to parse authorization response.");
+        error!(
+            platform = "bluesky",
+            request = "createSession",
+            "Failed to parse authorization response"
+        );
         None
     }
 }
@@ -135,11 +174,11 @@ async fn log_auth_error(context: &str, response: reqwest::Response) {
         .text()
         .await
         .unwrap_or_else(|_| "<Failed to read body>".to_string());
-    println!("{}: Status: {}, Body: {}", context, status, body);
+    warn!(platform = "bluesky", status = status.as_u16(), %body, "{}", context);
 }
 
 pub async fn reauthorize_bluesky() -> Option<TokenData> {
-    let client = Client::new();
+    let client = http::client();
     let auth_data = create_auth_request();
 
     match send_auth_request(&client, &auth_data).await {
@@ -151,6 +190,11 @@ pub async fn reauthorize_bluesky() -> Option<TokenData> {
                         &auth_response.refresh_jwt,
                         &auth_response.did,
                     );
+                    audit::record(
+                        audit::Action::Authorize,
+                        Some("Bluesky"),
+                        "Reauthorized with the stored password",
+                    );
 
                     Some(TokenData {
                         access_jwt: auth_response.access_jwt,
@@ -158,42 +202,308 @@ pub async fn reauthorize_bluesky() -> Option<TokenData> {
                         did: auth_response.did,
                     })
                 } else {
-                    println!("Failed to parse reauthorization response.");
+                    error!(
+                        platform = "bluesky",
+                        request = "createSession",
+                        "Failed to parse reauthorization response"
+                    );
                     None
                 }
             } else {
-                println!("Reauthorization failed: {:?}", response.text().await);
+                log_auth_error("Reauthorization failed", response).await;
                 None
             }
         }
         Err(err) => {
-            println!("Error during reauthorization: {:?}", err);
+            error!(platform = "bluesky", request = "createSession", error = ?err, "Error during reauthorization");
             None
         }
     }
 }
 
-pub async fn post_to_bluesky(token: &str, text: &str, user_did: &str) -> bool {
-    let client = Client::new();
+/// A record as another refers to it: its URI and the content hash of the version referred to.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct StrongRef {
+    pub uri: String,
+    pub cid: String,
+}
+
+/// Where a reply sits in a thread: the thread's first post and the post it answers.  Both are needed for Bluesky to
+/// show the posts as one thread.
+#[derive(Clone, PartialEq, Debug, Serialize)]
+pub struct ReplyRef {
+    pub root: StrongRef,
+    pub parent: StrongRef,
+}
+
+/// Public web address of a post, from its at://<did>/app.bsky.feed.post/<rkey> uri.
+pub fn post_url(uri: &str) -> Option<String> {
+    let (did, rkey) = uri.strip_prefix("at://")?.split_once("/app.bsky.feed.post/")?;
+    Some(format!("https://bsky.app/profile/{}/post/{}", did, rkey))
+}
+
+/// Link facets for the URLs in `text`, so they're clickable.  Bluesky doesn't detect links on its own.
+fn link_facets(text: &str) -> Vec<serde_json::Value> {
+    links::find_urls(text)
+        .into_iter()
+        .map(|(range, url)| {
+            serde_json::json!({
+                "index": { "byteStart": range.start, "byteEnd": range.end },
+                "features": [{ "$type": "app.bsky.richtext.facet#link", "uri": url }],
+            })
+        })
+        .collect()
+}
+
+/// Tag facets for the `#hashtags` in `text` (see `entities::hashtags`), so they're clickable and the post shows up
+/// in the tag's feed.
+fn tag_facets(text: &str) -> Vec<serde_json::Value> {
+    entities::hashtags(text)
+        .into_iter()
+        .filter(|(_, tag)| tag.chars().count() <= MAX_TAG_CHARS)
+        .map(|(range, tag)| {
+            serde_json::json!({
+                "index": { "byteStart": range.start, "byteEnd": range.end },
+                "features": [{ "$type": "app.bsky.richtext.facet#tag", "tag": tag }],
+            })
+        })
+        .collect()
+}
+
+/// The `@handle` mentions in `text`, with the byte ranges of the whole mention.  Only full handles (with a dot, like
+/// `@alice.bsky.social`) count, so email addresses and plain `@word`s are left alone.
+fn find_mentions(text: &str) -> Vec<(Range<usize>, String)> {
+    let mut mentions = Vec::new();
+    for (start, _) in text.match_indices('@') {
+        let preceded_by_word = text[..start]
+            .chars()
+            .next_back()
+            .is_some_and(|c| !c.is_whitespace() && c != '(');
+        if preceded_by_word {
+            continue;
+        }
+        let body_start = start + 1;
+        let body_len = text[body_start..]
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '-'))
+            .unwrap_or(text.len() - body_start);
+        // Sentence punctuation right after a handle isn't part of it
+        let handle = text[body_start..body_start + body_len].trim_end_matches(['.', '-']);
+        if handle.contains('.') && !handle.starts_with('.') {
+            mentions.push((start..body_start + handle.len(), handle.to_ascii_lowercase()));
+        }
+    }
+    mentions
+}
+
+/// Looks up the DID of `handle` with `com.atproto.identity.resolveHandle`.
+async fn resolve_handle(client: &Client, handle: &str) -> Option<String> {
+    #[derive(Deserialize)]
+    struct ResolveHandleResponse {
+        did: String,
+    }
+
+    let started = Instant::now();
+    match http::send_idempotent(
+        client
+            .get(format!("{}/xrpc/com.atproto.identity.resolveHandle", api_base_url()))
+            .query(&[("handle", handle)]),
+    )
+    .await
+    {
+        Ok(response) if response.status().is_success() => response
+            .json::<ResolveHandleResponse>()
+            .await
+            .ok()
+            .map(|resolved| resolved.did),
+        Ok(response) => {
+            let status = response.status();
+            let duration_ms = started.elapsed().as_millis() as u64;
+            warn!(
+                platform = "bluesky",
+                request = "resolveHandle",
+                handle,
+                status = status.as_u16(),
+                duration_ms,
+                "Couldn't resolve handle; posting the mention as plain text"
+            );
+            None
+        }
+        Err(err) => {
+            error!(platform = "bluesky", request = "resolveHandle", handle, error = ?err, "Error resolving handle");
+            None
+        }
+    }
+}
+
+/// Mention facets for the handles in `text` that resolve, so the people mentioned are notified.
+async fn mention_facets(client: &Client, text: &str) -> Vec<serde_json::Value> {
+    let mut facets = Vec::new();
+    for (range, handle) in find_mentions(text) {
+        if let Some(did) = resolve_handle(client, &handle).await {
+            facets.push(serde_json::json!({
+                "index": { "byteStart": range.start, "byteEnd": range.end },
+                "features": [{ "$type": "app.bsky.richtext.facet#mention", "did": did }],
+            }));
+        }
+    }
+    facets
+}
+
+/// A preview card for the first link in `text`.  Bluesky only shows cards the client attaches.
+async fn link_card(text: &str) -> Option<serde_json::Value> {
+    let (_, url) = links::find_urls(text).into_iter().next()?;
+    let preview = links::fetch_preview(&url).await?;
+    Some(serde_json::json!({
+        "$type": "app.bsky.embed.external",
+        "external": {
+            "uri": preview.url,
+            "title": preview.title,
+            "description": preview.description,
+        },
+    }))
+}
+
+/// Uploads an image as a blob, returning the blob reference to embed.
+async fn upload_image(
+    client: &Client,
+    token: &str,
+    image: &media::LoadedImage,
+) -> Result<serde_json::Value, PostError> {
+    #[derive(Deserialize)]
+    struct UploadBlobResponse {
+        blob: serde_json::Value,
+    }
+
+    let started = Instant::now();
+    match http::send(
+        client
+            .post(format!("{}/xrpc/com.atproto.repo.uploadBlob", api_base_url()))
+            .bearer_auth(token)
+            .header("Content-Type", image.mime_type)
+            .body(image.bytes.clone()),
+    )
+    .await
+    {
+        Ok(response) if response.status().is_success() => response
+            .json::<UploadBlobResponse>()
+            .await
+            .map(|uploaded| uploaded.blob)
+            .map_err(|_| PostError::Rejected),
+        Ok(response) => {
+            let status = response.status();
+            let duration_ms = started.elapsed().as_millis() as u64;
+            let body = response.text().await.unwrap_or_default();
+            warn!(
+                platform = "bluesky",
+                request = "uploadBlob",
+                status = status.as_u16(),
+                duration_ms,
+                %body,
+                "Image upload failed"
+            );
+            Err(PostError::from_status(status))
+        }
+        Err(err) => {
+            error!(platform = "bluesky", request = "uploadBlob", error = ?err, "Error uploading image");
+            Err(PostError::Network)
+        }
+    }
+}
+
+/// Uploads the attached images and builds the embed showing them.  The aspect ratio lets clients lay the images out
+/// before they load, without cropping them badly.
+async fn image_embed(
+    client: &Client,
+    token: &str,
+    images: &[media::ImageAttachment],
+) -> Result<serde_json::Value, PostError> {
+    let max_bytes = limits::default_for("Bluesky").max_image_bytes.unwrap_or(u64::MAX);
+    let mut embedded = Vec::new();
+    for attachment in images {
+        let image = media::load(attachment).map_err(|err| {
+            error!(platform = "bluesky", error = %err, "Can't attach image");
+            PostError::Rejected
+        })?;
+        if image.bytes.len() as u64 > max_bytes {
+            error!(
+                platform = "bluesky",
+                path = %attachment.path,
+                bytes = image.bytes.len(),
+                max_bytes,
+                "Image is too large for Bluesky"
+            );
+            return Err(PostError::Rejected);
+        }
+        let blob = upload_image(client, token, &image).await?;
+        let mut entry = serde_json::json!({ "image": blob, "alt": attachment.alt });
+        if let Some((width, height)) = image.dimensions {
+            entry["aspectRatio"] = serde_json::json!({ "width": width, "height": height });
+        }
+        embedded.push(entry);
+    }
+    Ok(serde_json::json!({ "$type": "app.bsky.embed.images", "images": embedded }))
+}
+
+/// Posts to Bluesky, returning the at:// uri of the created record, or `None` when the record was created but the
+/// response didn't say which it is.  Attached images are uploaded and embedded; otherwise, with link previews on, the
+/// first link gets a preview card.
+pub async fn post_to_bluesky(
+    token: &str,
+    text: &str,
+    user_did: &str,
+    reply: Option<&ReplyRef>,
+    options: &PostOptions,
+) -> Result<Option<StrongRef>, PostError> {
+    let client = http::client();
     let mut current_token = token.to_string();
+    let mut last_error = PostError::Rejected;
+    // A post has room for a single embed; images win over the link card
+    let card = if options.link_previews && options.images.is_empty() {
+        link_card(text).await
+    } else {
+        None
+    };
+    let mut facets = link_facets(text);
+    facets.extend(tag_facets(text));
+    facets.extend(mention_facets(&client, text).await);
 
     for _ in 0..2 {
-        // Attempt to post
-        if try_post(&client, &current_token, text, user_did).await {
-            return true;
+        // Images are uploaded with the token in use, so they're redone after a refresh
+        let embed = match options.images.as_slice() {
+            [] => Ok(card.clone()),
+            images => image_embed(&client, &current_token, images).await.map(Some),
+        };
+        let result = match embed {
+            Ok(embed) => try_post(&client, &current_token, text, user_did, reply, facets.clone(), embed).await,
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(created) => return Ok(created),
+            Err(err) => last_error = err,
         }
 
         // If posting fails due to authentication, attempt to refresh or reauthorize
         if !refresh_or_reauthorize(&mut current_token).await {
+            if last_error != PostError::Network {
+                last_error = PostError::Unauthorized;
+            }
             break;
         }
     }
 
-    println!("All attempts to post to Bluesky failed.");
-    false
+    error!(platform = "bluesky", "All attempts to post to Bluesky failed");
+    Err(last_error)
 }
 
-async fn try_post(client: &Client, token: &str, text: &str, user_did: &str) -> bool {
+async fn try_post(
+    client: &Client,
+    token: &str,
+    text: &str,
+    user_did: &str,
+    reply: Option<&ReplyRef>,
+    facets: Vec<serde_json::Value>,
+    embed: Option<serde_json::Value>,
+) -> Result<Option<StrongRef>, PostError> {
     use chrono::Utc;
 
     #[derive(Serialize)]
@@ -201,6 +511,12 @@ async fn try_post(client: &Client, token: &str, text: &str, user_did: &str) -> b
     struct Record {
         text: String,
         created_at: String,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        facets: Vec<serde_json::Value>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        embed: Option<serde_json::Value>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reply: Option<ReplyRef>,
     }
 
     #[derive(Serialize)]
@@ -219,28 +535,241 @@ async fn try_post(client: &Client, token: &str, text: &str, user_did: &str) -> b
         record: Record {
             text: text.to_string(),
             created_at: Utc::now().to_rfc3339(),
+            facets,
+            embed,
+            reply: reply.cloned(),
         },
     };
 
-    match client
-        .post("https://bsky.social/xrpc/com.atproto.repo.createRecord")
-        .bearer_auth(token)
-        .json(&post_data)
-        .send()
+    let started = Instant::now();
+    match http::send(
+        client
+            .post(format!("{}/xrpc/com.atproto.repo.createRecord", api_base_url()))
+            .bearer_auth(token)
+            .json(&post_data),
+    )
+    .await
+    {
+        Ok(response) if response.status().is_success() => {
+            let status = response.status();
+            let duration_ms = started.elapsed().as_millis() as u64;
+            match response.json::<StrongRef>().await {
+                Ok(created) => {
+                    info!(
+                        platform = "bluesky",
+                        request = "createRecord",
+                        status = status.as_u16(),
+                        duration_ms,
+                        post_id = %created.uri,
+                        "Posted record"
+                    );
+                    Ok(Some(created))
+                }
+                Err(_) => {
+                    // The post is up, so it's a success; failing would post it again after a token refresh
+                    error!(
+                        platform = "bluesky",
+                        request = "createRecord",
+                        "Posted, but failed to parse the createRecord response"
+                    );
+                    Ok(None)
+                }
+            }
+        }
+        Ok(response) => {
+            let status = response.status();
+            let duration_ms = started.elapsed().as_millis() as u64;
+            let body = response.text().await.unwrap_or_default();
+            warn!(
+                platform = "bluesky",
+                request = "createRecord",
+                status = status.as_u16(),
+                duration_ms,
+                %body,
+                "Post failed"
+            );
+            Err(PostError::from_status(status))
+        }
+        Err(err) => {
+            error!(platform = "bluesky", request = "createRecord", error = ?err, "Error posting to Bluesky");
+            Err(PostError::Network)
+        }
+    }
+}
+
+/// Deletes one of the account's posts by its at://<did>/<collection>/<rkey> uri with `com.atproto.repo.deleteRecord`,
+/// refreshing the token once when it's refused.  Returns whether it is gone.
+pub async fn delete_post(token: &str, uri: &str) -> bool {
+    let parts = uri.strip_prefix("at://").and_then(|rest| {
+        let (repo, rest) = rest.split_once('/')?;
+        let (collection, rkey) = rest.split_once('/')?;
+        Some((repo, collection, rkey))
+    });
+    let Some((repo, collection, rkey)) = parts else {
+        warn!(platform = "bluesky", uri, "Not a record uri; can't delete it");
+        return false;
+    };
+    let body = serde_json::json!({ "repo": repo, "collection": collection, "rkey": rkey });
+
+    let client = http::client();
+    let mut current_token = token.to_string();
+    for _ in 0..2 {
+        let started = Instant::now();
+        match http::send_idempotent(
+            client
+                .post(format!("{}/xrpc/com.atproto.repo.deleteRecord", api_base_url()))
+                .bearer_auth(&current_token)
+                .json(&body),
+        )
         .await
+        {
+            // Deleting a record that's already gone succeeds too
+            Ok(response) if response.status().is_success() => {
+                info!(
+                    platform = "bluesky",
+                    request = "deleteRecord",
+                    status = response.status().as_u16(),
+                    duration_ms = started.elapsed().as_millis() as u64,
+                    post_id = uri,
+                    "Deleted record"
+                );
+                return true;
+            }
+            Ok(response) => {
+                let status = response.status();
+                let duration_ms = started.elapsed().as_millis() as u64;
+                let body = response.text().await.unwrap_or_default();
+                warn!(
+                    platform = "bluesky",
+                    request = "deleteRecord",
+                    status = status.as_u16(),
+                    duration_ms,
+                    %body,
+                    "Failed to delete record"
+                );
+            }
+            Err(err) => {
+                error!(platform = "bluesky", request = "deleteRecord", error = ?err, "Error deleting record");
+                return false;
+            }
+        }
+        if !refresh_or_reauthorize(&mut current_token).await {
+            break;
+        }
+    }
+    false
+}
+
+/// Fetches like/repost/reply counts for a post.  Bluesky doesn't expose impressions.
+pub async fn fetch_engagement(token: &str, uri: &str) -> Option<Engagement> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct PostView {
+        like_count: Option<u64>,
+        repost_count: Option<u64>,
+        quote_count: Option<u64>,
+        reply_count: Option<u64>,
+    }
+
+    #[derive(Deserialize)]
+    struct GetPostsResponse {
+        posts: Vec<PostView>,
+    }
+
+    let client = http::client();
+    let started = Instant::now();
+    match http::send_idempotent(
+        client
+            .get(format!("{}/xrpc/app.bsky.feed.getPosts", api_base_url()))
+            .query(&[("uris", uri)])
+            .bearer_auth(token),
+    )
+    .await
+    {
+        Ok(response) if response.status().is_success() => {
+            let post = response
+                .json::<GetPostsResponse>()
+                .await
+                .ok()?
+                .posts
+                .into_iter()
+                .next()?;
+            Some(Engagement {
+                impressions: None,
+                likes: post.like_count,
+                reposts: Some(post.repost_count.unwrap_or(0) + post.quote_count.unwrap_or(0)),
+                replies: post.reply_count,
+            })
+        }
+        Ok(response) => {
+            let status = response.status();
+            let duration_ms = started.elapsed().as_millis() as u64;
+            let body = response.text().await.unwrap_or_default();
+            warn!(
+                platform = "bluesky",
+                request = "getPosts",
+                status = status.as_u16(),
+                duration_ms,
+                %body,
+                "Failed to fetch post"
+            );
+            None
+        }
+        Err(err) => {
+            error!(platform = "bluesky", request = "getPosts", error = ?err, "Error fetching post");
+            None
+        }
+    }
+}
+
+/// Accounts whose handle or name starts with `query`, as (handle, display name), from
+/// `app.bsky.actor.searchActorsTypeahead`.
+pub async fn search_handles(token: &str, query: &str) -> Vec<(String, String)> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Actor {
+        handle: String,
+        display_name: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct TypeaheadResponse {
+        actors: Vec<Actor>,
+    }
+
+    let client = http::client();
+    let started = Instant::now();
+    match http::send_idempotent(
+        client
+            .get(format!("{}/xrpc/app.bsky.actor.searchActorsTypeahead", api_base_url()))
+            .query(&[("q", query), ("limit", "8")])
+            .bearer_auth(token),
+    )
+    .await
     {
-        Ok(response) if response.status().is_success() => true,
+        Ok(response) if response.status().is_success() => match response.json::<TypeaheadResponse>().await {
+            Ok(found) => found
+                .actors
+                .into_iter()
+                .map(|actor| (actor.handle, actor.display_name.unwrap_or_default()))
+                .collect(),
+            Err(_) => Vec::new(),
+        },
         Ok(response) => {
-            println!(
-                "Post failed with status {}: {:?}",
-                response.status(),
-                response.text().await
+            let status = response.status();
+            let duration_ms = started.elapsed().as_millis() as u64;
+            warn!(
+                platform = "bluesky",
+                request = "searchActorsTypeahead",
+                status = status.as_u16(),
+                duration_ms,
+                "Failed to search handles"
             );
-            false
+            Vec::new()
         }
         Err(err) => {
-            println!("Error posting to Bluesky: {:?}", err);
-            false
+            error!(platform = "bluesky", request = "searchActorsTypeahead", error = ?err, "Error searching handles");
+            Vec::new()
         }
     }
 }
@@ -256,7 +785,7 @@ async fn refresh_or_reauthorize(current_token: &mut String) -> bool {
             return true;
         }
     }
-    println!("Failed to refresh or reauthorize token.");
+    error!(platform = "bluesky", "Failed to refresh or reauthorize token");
     false
 }
 
@@ -271,11 +800,12 @@ async fn send_auth_request(
     client: &Client,
     auth_data: &BlueskyAuthRequest,
 ) -> Result<reqwest::Response, reqwest::Error> {
-    client
-        .post("https://bsky.social/xrpc/com.atproto.server.createSession")
-        .json(auth_data)
-        .send()
-        .await
+    http::send(
+        client
+            .post(format!("{}/xrpc/com.atproto.server.createSession", api_base_url()))
+            .json(auth_data),
+    )
+    .await
 }
 
 #[derive(Serialize)]
@@ -0,0 +1,87 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::history::HistoryEntry;
+use crate::settings;
+
+/// Share of a cap after which posting warns that the cap is close.
+const WARN_RATIO: f64 = 0.8;
+
+/// Most posts allowed on one platform in a rolling hour and day.  `None` leaves that window uncapped.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct PostingCap {
+    pub per_hour: Option<u32>,
+    pub per_day: Option<u32>,
+}
+
+/// Where a platform stands against its cap before one more post.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Status {
+    Within,
+    /// Posting is still allowed, but `used` of `cap` posts in the window are gone.
+    Approaching {
+        used: u32,
+        cap: u32,
+        window: &'static str,
+    },
+    /// No room left until `until`, when the oldest post in the window leaves it.
+    Exceeded {
+        until: DateTime<Utc>,
+    },
+}
+
+/// Posts that went out on `platform` in the last hour and the last day.
+pub fn usage(history: &[HistoryEntry], platform: &str) -> (u32, u32) {
+    let times = post_times(history, platform, TimeDelta::days(1));
+    let hour_ago = Utc::now() - TimeDelta::hours(1);
+    let last_hour = times.iter().filter(|time| **time > hour_ago).count() as u32;
+    (last_hour, times.len() as u32)
+}
+
+/// Checks one more post to `platform` against its configured cap, counting the successful posts in `history`.
+pub fn check(history: &[HistoryEntry], platform: &str) -> Status {
+    let Some(cap) = settings::get().posting_caps.get(platform).copied() else {
+        return Status::Within;
+    };
+    let windows = [
+        (cap.per_hour, TimeDelta::hours(1), "hour"),
+        (cap.per_day, TimeDelta::days(1), "day"),
+    ];
+
+    let mut status = Status::Within;
+    for (limit, window, name) in windows {
+        let Some(limit) = limit else {
+            continue;
+        };
+        let times = post_times(history, platform, window);
+        let used = times.len() as u32;
+        if used >= limit {
+            // Room frees up once enough of the oldest posts have aged out of the window
+            let until = times[(used - limit) as usize] + window;
+            match status {
+                Status::Exceeded { until: later } if later >= until => {}
+                _ => status = Status::Exceeded { until },
+            }
+        } else if f64::from(used + 1) >= f64::from(limit) * WARN_RATIO && status == Status::Within {
+            status = Status::Approaching {
+                used,
+                cap: limit,
+                window: name,
+            };
+        }
+    }
+    status
+}
+
+/// Times of the successful posts on `platform` within `window` of now, oldest first.
+fn post_times(history: &[HistoryEntry], platform: &str, window: TimeDelta) -> Vec<DateTime<Utc>> {
+    let since = Utc::now() - window;
+    let mut times: Vec<DateTime<Utc>> = history
+        .iter()
+        .filter(|entry| entry.posted_at > since)
+        .filter(|entry| entry.results.iter().any(|r| r.platform == platform && r.success))
+        .map(|entry| entry.posted_at)
+        .collect();
+    times.sort();
+    times
+}
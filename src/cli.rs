@@ -0,0 +1,650 @@
+use chrono::Utc;
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use std::io::Read;
+use std::process::ExitCode;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use tokio::sync::Mutex;
+use tracing::warn;
+
+#[cfg(feature = "bluesky")]
+use crate::bluesky;
+use crate::cron::CronSchedule;
+#[cfg(feature = "email")]
+use crate::email;
+#[cfg(feature = "hashnode")]
+use crate::hashnode;
+#[cfg(feature = "linkedin")]
+use crate::linkedin;
+#[cfg(feature = "mastodon")]
+use crate::mastodon;
+#[cfg(feature = "medium")]
+use crate::medium;
+#[cfg(feature = "plugins")]
+use crate::plugins;
+use crate::posts::{self, PostError};
+#[cfg(feature = "twitter")]
+use crate::twitter;
+use crate::{
+    archive, engagement, history, import, inbox, limits, media, outbox, publish, queues, report, scheduler, shared,
+    timezone,
+};
+
+// Exit codes of the `post` command, for scripts and CI.  Success is 0.
+const EXIT_FAILED: u8 = 1;
+const EXIT_INVALID_INPUT: u8 = 2; // Same as clap's code for bad arguments
+const EXIT_PARTIAL_FAILURE: u8 = 3;
+const EXIT_AUTH_REQUIRED: u8 = 4;
+const EXIT_RATE_LIMITED: u8 = 5;
+
+/// Post to several social platforms at once.  Without a subcommand, the desktop app starts.
+#[derive(Parser)]
+#[command(name = "multique", version)]
+pub struct Cli {
+    /// Run without a window: post scheduled posts, watch releases and serve the local API until stopped.
+    #[arg(long, conflicts_with = "command")]
+    pub daemon: bool,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Publish a post without opening the window, using the tokens authorized in the app.
+    Post {
+        /// The text to post, or "-" to read it from stdin.
+        text: String,
+        /// Comma-separated platforms (e.g. twitter,mastodon), or "all" for every authorized one.
+        #[arg(long, value_delimiter = ',', default_value = "all")]
+        platforms: Vec<String>,
+        /// Print the outcome as JSON on stdout, for scripts and CI.
+        #[arg(long)]
+        json: bool,
+        /// Post links without a preview card, where the platform allows it (Bluesky).
+        #[arg(long)]
+        no_link_previews: bool,
+        /// Attach an image (PNG, JPEG, GIF or WebP); repeat for several.  Only Bluesky uploads images so far.
+        #[arg(long = "image")]
+        images: Vec<String>,
+        /// Alt text for the images, in the same order as --image.
+        #[arg(long = "alt")]
+        alts: Vec<String>,
+    },
+    /// Add a post to the schedule, published by the app (or another instance sharing the schedule) when due.
+    Schedule {
+        /// The text to post, or "-" to read it from stdin.
+        text: String,
+        /// When to post, as "YYYY-MM-DD HH:MM" in the configured time zone.
+        #[arg(long, required_unless_present = "cron")]
+        at: Option<String>,
+        /// Repeat on a cron schedule, e.g. "0 9 * * MON-FRI" (minute hour day-of-month month day-of-week), in the
+        /// configured time zone.  Without --at, the first post is the next match.
+        #[arg(long)]
+        cron: Option<String>,
+        /// Stop repeating after this many posts.
+        #[arg(long, requires = "cron")]
+        count: Option<u32>,
+        /// Stop repeating after this time, as "YYYY-MM-DD HH:MM".
+        #[arg(long, requires = "cron")]
+        until: Option<String>,
+        /// Comma-separated platforms (e.g. twitter,mastodon), or "all" for every authorized one.
+        #[arg(long, value_delimiter = ',', default_value = "all")]
+        platforms: Vec<String>,
+        /// Post links without a preview card, where the platform allows it (Bluesky).
+        #[arg(long)]
+        no_link_previews: bool,
+        /// Attach an image (PNG, JPEG, GIF or WebP); repeat for several.  Only Bluesky uploads images so far.
+        #[arg(long = "image")]
+        images: Vec<String>,
+        /// Alt text for the images, in the same order as --image.
+        #[arg(long = "alt")]
+        alts: Vec<String>,
+    },
+    /// Write every post in history to a folder, as Markdown and JSON files with copies of the images.
+    Export {
+        /// Folder to write the archive to; created if missing.
+        dir: String,
+    },
+    /// Print a summary of the posts sent lately, per platform, as Markdown.
+    Report {
+        #[arg(long, value_enum, default_value_t)]
+        period: report::Period,
+    },
+    /// Hold scheduled posts, everywhere or in one queue, until resumed.  Posts that come due meanwhile wait.
+    Pause {
+        /// Only pause this queue.
+        #[arg(long)]
+        queue: Option<String>,
+    },
+    /// Let paused scheduled posts go out again; the ones that came due meanwhile go out on the next check.
+    Resume {
+        /// Only resume this queue.
+        #[arg(long)]
+        queue: Option<String>,
+    },
+    /// Bring over scheduled and past posts from a Buffer or Hootsuite CSV export.
+    Import {
+        /// The exported CSV file.
+        file: String,
+        /// Comma-separated platforms for posts whose network the export doesn't name, or "all".
+        #[arg(long, value_delimiter = ',', default_value = "all")]
+        platforms: Vec<String>,
+    },
+}
+
+/// Outcome of a CLI post on one platform, as printed with `--json`.
+#[derive(Serialize)]
+struct PlatformOutcome {
+    platform: String,
+    status: &'static str, // "posted", "failed" or "skipped"
+    post_id: Option<String>,
+    url: Option<String>,
+    reason: Option<PostError>,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct PostOutcome {
+    success: bool,
+    results: Vec<PlatformOutcome>,
+}
+
+/// Platforms compiled into this build.
+pub fn known_platforms() -> Vec<&'static str> {
+    #[cfg_attr(not(any(feature = "mastodon", feature = "plugins")), allow(unused_mut))]
+    let mut platforms = vec![
+        #[cfg(feature = "twitter")]
+        "Twitter",
+        #[cfg(feature = "bluesky")]
+        "Bluesky",
+        #[cfg(feature = "mastodon")]
+        "Mastodon",
+        #[cfg(feature = "linkedin")]
+        "LinkedIn",
+        #[cfg(feature = "medium")]
+        "Medium",
+        #[cfg(feature = "hashnode")]
+        "Hashnode",
+        #[cfg(feature = "email")]
+        "Email",
+        #[cfg(feature = "demo")]
+        "Demo",
+    ];
+    #[cfg(feature = "mastodon")]
+    platforms.extend(mastodon::load_accounts().iter().map(mastodon::Account::platform_key));
+    #[cfg(feature = "plugins")]
+    platforms.extend(plugins::load().iter().map(|plugin| plugin.name));
+    platforms
+}
+
+pub fn run(command: Command) -> ExitCode {
+    let rt = Runtime::new().unwrap();
+    match command {
+        Command::Post {
+            text,
+            platforms,
+            json,
+            no_link_previews,
+            images,
+            alts,
+        } => match read_text(text) {
+            Ok(text) => {
+                let options = match post_options(no_link_previews, images, alts) {
+                    Ok(options) => options,
+                    Err(code) => return code,
+                };
+                rt.block_on(post(&text, &platforms, &options, json))
+            }
+            Err(code) => code,
+        },
+        Command::Schedule {
+            text,
+            at,
+            cron,
+            count,
+            until,
+            platforms,
+            no_link_previews,
+            images,
+            alts,
+        } => match read_text(text) {
+            Ok(text) => {
+                let options = match post_options(no_link_previews, images, alts) {
+                    Ok(options) => options,
+                    Err(code) => return code,
+                };
+                schedule(
+                    &text,
+                    at.as_deref(),
+                    cron.as_deref(),
+                    count,
+                    until.as_deref(),
+                    &platforms,
+                    &options,
+                )
+            }
+            Err(code) => code,
+        },
+        Command::Export { dir } => match archive::export(std::path::Path::new(&dir)) {
+            Ok(count) => {
+                println!("Exported {} posts to {}", count, dir);
+                ExitCode::SUCCESS
+            }
+            Err(err) => {
+                eprintln!("{}", err);
+                ExitCode::from(EXIT_FAILED)
+            }
+        },
+        Command::Import { file, platforms } => import(&file, &platforms),
+        Command::Pause { queue } => set_paused(queue.as_deref(), true),
+        Command::Resume { queue } => set_paused(queue.as_deref(), false),
+        Command::Report { period } => {
+            let report = report::build(&history::load_history(), &engagement::load_samples(), period);
+            print!("{}", report.to_markdown());
+            ExitCode::SUCCESS
+        }
+    }
+}
+
+/// Pauses or resumes the whole schedule, or only `queue`.
+fn set_paused(queue: Option<&str>, paused: bool) -> ExitCode {
+    let action = if paused { "Paused" } else { "Resumed" };
+    match queue {
+        Some(queue) if !queues::set_paused(queue, paused) => {
+            eprintln!("No queue named \"{}\"", queue);
+            return ExitCode::from(EXIT_FAILED);
+        }
+        Some(queue) => println!("{} queue \"{}\"", action, queue),
+        None if paused => {
+            scheduler::pause();
+            println!("{} scheduled posts", action);
+        }
+        None => {
+            scheduler::resume();
+            println!("{} scheduled posts", action);
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+/// The text argument, read from stdin when it is "-".
+fn read_text(text: String) -> Result<String, ExitCode> {
+    if text != "-" {
+        return Ok(text);
+    }
+    let mut input = String::new();
+    if let Err(err) = std::io::stdin().read_to_string(&mut input) {
+        eprintln!("Failed to read stdin: {}", err);
+        return Err(ExitCode::from(EXIT_INVALID_INPUT));
+    }
+    // Drop the newline `echo` and most editors add
+    Ok(input.trim_end_matches(['\n', '\r']).to_string())
+}
+
+fn post_options(
+    no_link_previews: bool,
+    images: Vec<String>,
+    alts: Vec<String>,
+) -> Result<posts::PostOptions, ExitCode> {
+    if alts.len() > images.len() {
+        eprintln!("More --alt texts than --image attachments");
+        return Err(ExitCode::from(EXIT_INVALID_INPUT));
+    }
+    let mut alts = alts.into_iter();
+    let mut attachments = Vec::new();
+    for path in images {
+        if media::mime_type(&path).is_none() {
+            eprintln!("{} isn't a PNG, JPEG, GIF or WebP image", path);
+            return Err(ExitCode::from(EXIT_INVALID_INPUT));
+        }
+        let alt = alts.next().unwrap_or_default();
+        attachments.push(media::ImageAttachment { path, alt });
+    }
+    Ok(posts::PostOptions {
+        link_previews: !no_link_previews,
+        images: attachments,
+        ..Default::default()
+    })
+}
+
+/// Resolves platform names case-insensitively; "all" selects every known platform.
+pub fn resolve_platforms(requested: &[String]) -> Result<Vec<String>, String> {
+    let known = known_platforms();
+    if requested.iter().any(|p| p.eq_ignore_ascii_case("all")) {
+        return Ok(known.iter().map(|p| p.to_string()).collect());
+    }
+    requested
+        .iter()
+        .map(|name| {
+            known
+                .iter()
+                .find(|p| p.eq_ignore_ascii_case(name.trim()))
+                .map(|p| p.to_string())
+                .ok_or_else(|| format!("Unknown platform \"{}\"; expected one of {}", name, known.join(", ")))
+        })
+        .collect()
+}
+
+/// Loads stored tokens into a fresh app state, refreshing the Bluesky session.  Unlike the app, never prompts.
+pub async fn load_state() -> Arc<Mutex<posts::AppState>> {
+    #[cfg_attr(
+        not(any(
+            feature = "twitter",
+            feature = "bluesky",
+            feature = "mastodon",
+            feature = "linkedin",
+            feature = "medium",
+            feature = "hashnode",
+            feature = "plugins"
+        )),
+        allow(unused_mut)
+    )]
+    let mut state = posts::AppState {
+        detected_limits: limits::detect().await,
+        inbox: inbox::load_inbox(),
+        outbox_count: outbox::load_outbox().len(),
+        engagement_samples: engagement::load_samples(),
+        ..Default::default()
+    };
+
+    #[cfg(feature = "twitter")]
+    {
+        state.twitter_authorized = twitter::load_bearer_token().is_some();
+    }
+    #[cfg(feature = "mastodon")]
+    {
+        state.mastodon_authorized = mastodon::load_tokens().is_some();
+        state.mastodon_accounts = mastodon::load_accounts();
+    }
+    #[cfg(feature = "linkedin")]
+    {
+        state.linkedin_authorized = linkedin::load_bearer_token().is_some();
+    }
+    #[cfg(feature = "medium")]
+    {
+        state.medium_authorized = medium::load_token().is_some();
+    }
+    #[cfg(feature = "hashnode")]
+    {
+        state.hashnode_authorized = hashnode::load_token().is_some();
+    }
+    #[cfg(feature = "plugins")]
+    {
+        state.plugins_authorized = plugins::load()
+            .iter()
+            .filter(|plugin| plugin.load_token().is_some())
+            .map(|plugin| plugin.name.to_string())
+            .collect();
+    }
+    #[cfg(feature = "bluesky")]
+    {
+        let session = match bluesky::load_tokens() {
+            Some(tokens) => {
+                let refreshed = bluesky::refresh_access_token(&tokens.refresh_jwt).await;
+                if refreshed.is_none() {
+                    warn!(platform = "bluesky", "Bluesky session expired; reauthorize in the app");
+                }
+                refreshed
+            }
+            None => None,
+        };
+        // Without a session (e.g. a fresh container), log in with BLUESKY_USERNAME and BLUESKY_PASSWORD when set
+        let session = match session {
+            None if std::env::var("BLUESKY_USERNAME").is_ok() => bluesky::reauthorize_bluesky().await,
+            session => session,
+        };
+        if let Some(session) = session {
+            state.bluesky_token = Some(session.access_jwt);
+            state.did = Some(session.did);
+            state.bluesky_authorized = true;
+        }
+    }
+
+    Arc::new(Mutex::new(state))
+}
+
+pub fn is_authorized(state: &posts::AppState, platform: &str) -> bool {
+    match platform {
+        "Twitter" => state.twitter_authorized,
+        "Bluesky" => state.bluesky_authorized,
+        "Mastodon" => state.mastodon_authorized,
+        "LinkedIn" => state.linkedin_authorized,
+        "Medium" => state.medium_authorized,
+        "Hashnode" => state.hashnode_authorized,
+        "Demo" => true,
+        #[cfg(feature = "email")]
+        "Email" => email::is_configured(),
+        #[cfg(feature = "mastodon")]
+        name if name.starts_with(mastodon::ACCOUNT_PREFIX) => state.mastodon_account_authorized(name),
+        name => state.plugins_authorized.contains(name),
+    }
+}
+
+fn describe(error: PostError) -> &'static str {
+    match error {
+        PostError::Unauthorized => "Not authorized; authorize the platform in the app",
+        PostError::RateLimited => "Rate limited; try again later",
+        PostError::Rejected => "The platform rejected the post; see the log",
+        PostError::Network => "The platform could not be reached",
+    }
+}
+
+/// Exit code summarizing the outcome.  Authorization problems take precedence over rate limits, which take
+/// precedence over other failures, since they tell a script what to do next.
+fn exit_code(results: &[PlatformOutcome]) -> ExitCode {
+    if results.iter().all(|r| r.status == "posted") {
+        return ExitCode::SUCCESS;
+    }
+    if results.iter().any(|r| r.reason == Some(PostError::Unauthorized)) {
+        return ExitCode::from(EXIT_AUTH_REQUIRED);
+    }
+    if results.iter().any(|r| r.reason == Some(PostError::RateLimited)) {
+        return ExitCode::from(EXIT_RATE_LIMITED);
+    }
+    if results.iter().any(|r| r.status == "posted") {
+        return ExitCode::from(EXIT_PARTIAL_FAILURE);
+    }
+    ExitCode::from(EXIT_FAILED)
+}
+
+async fn post(text: &str, requested: &[String], options: &posts::PostOptions, json: bool) -> ExitCode {
+    let platforms = match resolve_platforms(requested) {
+        Ok(platforms) => platforms,
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCode::from(EXIT_INVALID_INPUT);
+        }
+    };
+    if text.trim().is_empty() {
+        eprintln!("Nothing to post");
+        return ExitCode::from(EXIT_INVALID_INPUT);
+    }
+
+    let state = load_state().await;
+    let idempotency_key = history::new_idempotency_key();
+    let entry = publish::publish(
+        &state,
+        text,
+        &platforms,
+        &idempotency_key,
+        options,
+        &shared::current_user(),
+    )
+    .await;
+    let state = state.lock().await;
+
+    let results: Vec<PlatformOutcome> = platforms
+        .iter()
+        .map(|platform| {
+            let result = entry
+                .as_ref()
+                .and_then(|entry| entry.results.iter().find(|r| &r.platform == platform));
+            match result {
+                Some(result) if result.success => PlatformOutcome {
+                    platform: platform.clone(),
+                    status: "posted",
+                    post_id: result.post_id.clone(),
+                    url: result.url.clone(),
+                    reason: None,
+                    error: None,
+                },
+                Some(result) => {
+                    let reason = result.error.unwrap_or(PostError::Rejected);
+                    PlatformOutcome {
+                        platform: platform.clone(),
+                        status: "failed",
+                        post_id: None,
+                        url: None,
+                        reason: Some(reason),
+                        error: Some(describe(reason).to_string()),
+                    }
+                }
+                // Not attempted: either not authorized or over the limit with "Skip" chosen
+                None if !is_authorized(&state, platform) => PlatformOutcome {
+                    platform: platform.clone(),
+                    status: "skipped",
+                    post_id: None,
+                    url: None,
+                    reason: Some(PostError::Unauthorized),
+                    error: Some(describe(PostError::Unauthorized).to_string()),
+                },
+                None => PlatformOutcome {
+                    platform: platform.clone(),
+                    status: "skipped",
+                    post_id: None,
+                    url: None,
+                    reason: None,
+                    error: Some("The text is over the limit and this platform is set to skip".to_string()),
+                },
+            }
+        })
+        .collect();
+    let outcome = PostOutcome {
+        success: results.iter().all(|r| r.status == "posted"),
+        results,
+    };
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&outcome).expect("Failed to serialize outcome")
+        );
+    } else {
+        for result in &outcome.results {
+            let icon = match result.status {
+                "posted" => "✅",
+                "failed" => "❌",
+                _ => "⏭",
+            };
+            let detail = result.url.as_deref().or(result.error.as_deref()).unwrap_or_default();
+            println!("{} {:<9} {}", icon, result.platform, detail);
+        }
+    }
+
+    exit_code(&outcome.results)
+}
+
+fn schedule(
+    text: &str,
+    at: Option<&str>,
+    cron: Option<&str>,
+    count: Option<u32>,
+    until: Option<&str>,
+    requested: &[String],
+    options: &posts::PostOptions,
+) -> ExitCode {
+    let invalid = |message: String| {
+        eprintln!("{}", message);
+        ExitCode::from(EXIT_INVALID_INPUT)
+    };
+    let platforms = match resolve_platforms(requested) {
+        Ok(platforms) => platforms,
+        Err(err) => return invalid(err),
+    };
+    if text.trim().is_empty() {
+        return invalid("Nothing to post".to_string());
+    }
+
+    let recurrence = match cron {
+        Some(expression) => {
+            if let Err(err) = CronSchedule::parse(expression) {
+                return invalid(format!("Invalid cron expression: {}", err));
+            }
+            let until = match until.map(timezone::parse).transpose() {
+                Ok(until) => until,
+                Err(err) => return invalid(format!("--until: {}", err)),
+            };
+            Some(scheduler::Recurrence {
+                frequency: scheduler::Frequency::Daily, // Unused when there is a cron expression
+                cron: Some(expression.to_string()),
+                until,
+                remaining: count.filter(|count| *count > 0),
+            })
+        }
+        None => None,
+    };
+
+    let scheduled_at = match (at, cron) {
+        (Some(at), _) => match timezone::parse(at) {
+            Ok(scheduled_at) => scheduled_at,
+            Err(err) => return invalid(format!("--at: {}", err)),
+        },
+        (None, Some(expression)) => {
+            let first = CronSchedule::parse(expression)
+                .ok()
+                .and_then(|cron| cron.next_after(timezone::to_wall_clock(Utc::now())))
+                .and_then(|wall_clock| timezone::from_wall_clock(wall_clock).ok());
+            match first {
+                Some(first) => first,
+                None => return invalid(format!("\"{}\" never matches", expression)),
+            }
+        }
+        (None, None) => unreachable!("clap requires --at or --cron"),
+    };
+    if scheduled_at <= Utc::now() {
+        return invalid("That time has already passed".to_string());
+    }
+
+    let id = scheduler::schedule(
+        text,
+        &platforms,
+        scheduled_at,
+        &history::new_idempotency_key(),
+        recurrence,
+        None,
+        options,
+    );
+    println!("Scheduled {} for {}", id, timezone::format(scheduled_at));
+    ExitCode::SUCCESS
+}
+
+fn import(file: &str, requested: &[String]) -> ExitCode {
+    let platforms = match resolve_platforms(requested) {
+        Ok(platforms) => platforms,
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCode::from(EXIT_INVALID_INPUT);
+        }
+    };
+    let csv = match std::fs::read_to_string(file) {
+        Ok(csv) => csv,
+        Err(err) => {
+            eprintln!("Failed to read {}: {}", file, err);
+            return ExitCode::from(EXIT_INVALID_INPUT);
+        }
+    };
+    match import::import_csv(&csv, &platforms) {
+        Ok(summary) => {
+            println!(
+                "Imported {} scheduled and {} past posts ({} skipped)",
+                summary.scheduled, summary.history, summary.skipped
+            );
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::from(EXIT_INVALID_INPUT)
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tracing::{error, info};
+
+use crate::profiles::{self, ProfileConfig};
+use crate::queues::{self, Queue};
+use crate::settings::{self, Settings};
+
+const BUNDLE_VERSION: u32 = 1;
+
+/// Everything needed to reproduce this machine's configuration elsewhere, minus secrets.  Tokens are deliberately
+/// left out; they belong to the machine that authorized them.
+#[derive(Serialize, Deserialize)]
+pub struct ConfigBundle {
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub settings: Settings,
+    pub profiles: HashMap<String, ProfileConfig>,
+    /// Missing from exports made before queues existed, in which case local queues are kept.
+    #[serde(default)]
+    pub queues: Option<Vec<Queue>>,
+}
+
+impl ConfigBundle {
+    /// Snapshot of the current configuration.
+    pub fn current() -> Self {
+        Self {
+            version: BUNDLE_VERSION,
+            exported_at: Utc::now(),
+            settings: settings::get().with_secrets_from(&Settings::default()),
+            profiles: profiles::list()
+                .into_iter()
+                .map(|profile| {
+                    let config = profiles::load_config_for(&profile);
+                    (profile, config)
+                })
+                .collect(),
+            queues: Some(queues::load_queues()),
+        }
+    }
+
+    /// Applies the bundle to this machine, keeping the locally active profile and the local secrets.
+    pub fn apply(self) {
+        for (profile, config) in &self.profiles {
+            if profile != profiles::DEFAULT_PROFILE && !profiles::list().contains(profile) {
+                profiles::create(profile);
+            }
+            profiles::save_config_for(profile, config);
+        }
+
+        if let Some(queues) = &self.queues {
+            queues::save_queues(queues);
+        }
+
+        let mut imported = self.settings.with_secrets_from(&settings::get());
+        imported.active_profile = profiles::active();
+        settings::update(imported);
+    }
+}
+
+/// Writes all non-secret configuration to `path` as JSON.
+pub fn export_to(path: &Path) -> bool {
+    let json = serde_json::to_string_pretty(&ConfigBundle::current()).expect("Failed to serialize config bundle");
+    match fs::write(path, json) {
+        Ok(()) => {
+            info!(path = %path.display(), "Exported configuration");
+            true
+        }
+        Err(err) => {
+            error!(path = %path.display(), error = ?err, "Failed to export configuration");
+            false
+        }
+    }
+}
+
+/// Reads a bundle written by `export_to` and applies it.
+pub fn import_from(path: &Path) -> bool {
+    let bundle = match fs::read_to_string(path).map(|json| serde_json::from_str::<ConfigBundle>(&json)) {
+        Ok(Ok(bundle)) => bundle,
+        Ok(Err(err)) => {
+            error!(path = %path.display(), error = ?err, "Not a valid configuration export");
+            return false;
+        }
+        Err(err) => {
+            error!(path = %path.display(), error = ?err, "Failed to read configuration export");
+            return false;
+        }
+    };
+
+    if bundle.version > BUNDLE_VERSION {
+        error!(
+            path = %path.display(),
+            version = bundle.version,
+            "Configuration export is from a newer version of multique"
+        );
+        return false;
+    }
+
+    bundle.apply();
+    info!(path = %path.display(), "Imported configuration");
+    true
+}
@@ -0,0 +1,82 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::{http, outbox, posts, publish, settings, shared};
+
+const ONLINE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const OFFLINE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Hosts pinged to decide whether we're online.  Any HTTP response counts, even an error status, since it proves the
+/// network path works.
+fn health_urls() -> Vec<String> {
+    vec![
+        settings::api_base_url("mastodon", "https://fosstodon.org"),
+        settings::api_base_url("bluesky", "https://bsky.social"),
+        settings::api_base_url("twitter", "https://api.twitter.com"),
+    ]
+}
+
+pub async fn is_online() -> bool {
+    let client = http::client();
+    for url in health_urls() {
+        if client.head(&url).timeout(Duration::from_secs(5)).send().await.is_ok() {
+            return true;
+        }
+    }
+    false
+}
+
+/// Polls connectivity in the background, toggling the offline banner and flushing the outbox when the network returns.
+pub async fn monitor(state: Arc<Mutex<posts::AppState>>) {
+    loop {
+        let online = is_online().await;
+        let was_offline = {
+            let mut state = state.lock().await;
+            let was_offline = state.offline;
+            state.offline = !online;
+            was_offline
+        };
+
+        if online && was_offline {
+            info!("Connectivity restored");
+        } else if !online && !was_offline {
+            warn!("Connectivity lost; new posts will be held in the outbox");
+        }
+
+        if online {
+            flush_outbox(&state).await;
+        }
+
+        tokio::time::sleep(if online {
+            ONLINE_CHECK_INTERVAL
+        } else {
+            OFFLINE_CHECK_INTERVAL
+        })
+        .await;
+    }
+}
+
+/// Sends everything held in the outbox, oldest first.
+async fn flush_outbox(state: &Arc<Mutex<posts::AppState>>) {
+    let items = outbox::load_outbox();
+    if items.is_empty() {
+        return;
+    }
+
+    info!(count = items.len(), "Sending posts held in the outbox");
+    outbox::save_outbox(&[]);
+    state.lock().await.outbox_count = 0;
+    for item in items {
+        publish::publish(
+            state,
+            &item.text,
+            &item.platforms,
+            &item.idempotency_key,
+            &item.options,
+            &shared::current_user(),
+        )
+        .await;
+    }
+}
@@ -0,0 +1,161 @@
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Timelike};
+
+/// A standard five-field cron expression ("minute hour day-of-month month day-of-week"), evaluated against wall-clock
+/// times in the configured zone.
+///
+/// Fields accept `*`, numbers, ranges (`1-5`), lists (`1,15`) and steps (`*/15`, `9-17/2`).  Months and weekdays
+/// also accept names (`JAN`, `MON`), and Sunday is both 0 and 7.  As in Vixie cron, when both day fields are
+/// restricted a day matches if either does.  The shorthands `@hourly`, `@daily`, `@weekly`, `@monthly` and `@yearly`
+/// are understood too.
+#[derive(Clone, Debug)]
+pub struct CronSchedule {
+    minutes: [bool; 60],
+    hours: [bool; 24],
+    days_of_month: [bool; 32], // Index 0 unused
+    months: [bool; 13],        // Index 0 unused
+    days_of_week: [bool; 7],   // 0 = Sunday
+    any_day_of_month: bool,
+    any_day_of_week: bool,
+}
+
+const MONTH_NAMES: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// How far ahead to look for a match; covers expressions that only match on February 29th.
+const SEARCH_DAYS: i64 = 366 * 8;
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<CronSchedule, String> {
+        let expression = match expression.trim() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            expression => expression,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, day_of_week] = fields[..] else {
+            return Err(format!(
+                "Expected 5 fields (minute hour day-of-month month day-of-week), got {}",
+                fields.len()
+            ));
+        };
+
+        let mut schedule = CronSchedule {
+            minutes: [false; 60],
+            hours: [false; 24],
+            days_of_month: [false; 32],
+            months: [false; 13],
+            days_of_week: [false; 7],
+            any_day_of_month: day_of_month == "*",
+            any_day_of_week: day_of_week == "*",
+        };
+        parse_field(minute, 0, 59, &[], &mut schedule.minutes).map_err(|err| format!("minute: {}", err))?;
+        parse_field(hour, 0, 23, &[], &mut schedule.hours).map_err(|err| format!("hour: {}", err))?;
+        parse_field(day_of_month, 1, 31, &[], &mut schedule.days_of_month)
+            .map_err(|err| format!("day of month: {}", err))?;
+        parse_field(month, 1, 12, &MONTH_NAMES, &mut schedule.months).map_err(|err| format!("month: {}", err))?;
+
+        // Parsed with 7 allowed, then folded onto Sunday
+        let mut days_of_week = [false; 8];
+        parse_field(day_of_week, 0, 7, &WEEKDAY_NAMES, &mut days_of_week)
+            .map_err(|err| format!("day of week: {}", err))?;
+        schedule.days_of_week.copy_from_slice(&days_of_week[..7]);
+        schedule.days_of_week[0] |= days_of_week[7];
+
+        Ok(schedule)
+    }
+
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        if !self.months[date.month() as usize] {
+            return false;
+        }
+        let day_of_month = self.days_of_month[date.day() as usize];
+        let day_of_week = self.days_of_week[date.weekday().num_days_from_sunday() as usize];
+        match (self.any_day_of_month, self.any_day_of_week) {
+            (true, true) => true,
+            (true, false) => day_of_week,
+            (false, true) => day_of_month,
+            (false, false) => day_of_month || day_of_week,
+        }
+    }
+
+    /// The first matching wall-clock minute strictly after `after`, or `None` if the expression can never match
+    /// (such as February 30th).
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + TimeDelta::minutes(1);
+        let mut date = start.date();
+        for _ in 0..SEARCH_DAYS {
+            if self.matches_day(date) {
+                let earliest = if date == start.date() {
+                    start.time()
+                } else {
+                    NaiveTime::MIN
+                };
+                if let Some(time) = self.first_time_from(earliest) {
+                    return Some(date.and_time(time));
+                }
+            }
+            date = date.succ_opt()?;
+        }
+        None
+    }
+
+    fn first_time_from(&self, earliest: NaiveTime) -> Option<NaiveTime> {
+        for hour in earliest.hour()..24 {
+            if !self.hours[hour as usize] {
+                continue;
+            }
+            let first_minute = if hour == earliest.hour() { earliest.minute() } else { 0 };
+            if let Some(minute) = (first_minute..60).find(|&minute| self.minutes[minute as usize]) {
+                return NaiveTime::from_hms_opt(hour, minute, 0);
+            }
+        }
+        None
+    }
+}
+
+/// Marks the values matched by one comma-separated field in `allowed`, which is indexed by value.
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str], allowed: &mut [bool]) -> Result<(), String> {
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().map_err(|_| format!("invalid step \"{}\"", step))?;
+                if step == 0 {
+                    return Err("step can't be 0".to_string());
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            range => match range.split_once('-') {
+                Some((start, end)) => (parse_value(start, min, names)?, parse_value(end, min, names)?),
+                // "5/15" means from 5 to the end, every 15
+                None if part.contains('/') => (parse_value(range, min, names)?, max),
+                None => {
+                    let value = parse_value(range, min, names)?;
+                    (value, value)
+                }
+            },
+        };
+        if start < min || end > max || start > end {
+            return Err(format!("\"{}\" is outside {}-{}", part, min, max));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            allowed[value as usize] = true;
+        }
+    }
+    Ok(())
+}
+
+fn parse_value(value: &str, min: u32, names: &[&str]) -> Result<u32, String> {
+    if let Some(index) = names.iter().position(|name| name.eq_ignore_ascii_case(value)) {
+        return Ok(index as u32 + min);
+    }
+    value.parse().map_err(|_| format!("invalid value \"{}\"", value))
+}
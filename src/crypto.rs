@@ -0,0 +1,57 @@
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+const SEALED_VERSION: u32 = 1;
+const PBKDF2_ROUNDS: u32 = 600_000;
+
+/// Passphrase-encrypted payload.  The key is derived with PBKDF2-HMAC-SHA256 and the data sealed with AES-256-GCM,
+/// so a wrong passphrase or any tampering makes `open` fail.
+#[derive(Serialize, Deserialize)]
+pub struct Sealed {
+    pub version: u32,
+    pub salt: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, PBKDF2_ROUNDS, &mut key);
+    key
+}
+
+pub fn seal(passphrase: &str, plaintext: &[u8]) -> Sealed {
+    let salt: [u8; 16] = rand::random();
+    let nonce: [u8; 12] = rand::random();
+    let cipher = Aes256Gcm::new_from_slice(&derive_key(passphrase, &salt)).expect("Key has the wrong length");
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .expect("Failed to encrypt");
+
+    Sealed {
+        version: SEALED_VERSION,
+        salt: STANDARD.encode(salt),
+        nonce: STANDARD.encode(nonce),
+        ciphertext: STANDARD.encode(ciphertext),
+    }
+}
+
+/// Decrypts a sealed payload, returning `None` for a wrong passphrase or corrupted data.
+pub fn open(passphrase: &str, sealed: &Sealed) -> Option<Vec<u8>> {
+    if sealed.version != SEALED_VERSION {
+        return None;
+    }
+    let salt = STANDARD.decode(&sealed.salt).ok()?;
+    let nonce = STANDARD.decode(&sealed.nonce).ok()?;
+    let ciphertext = STANDARD.decode(&sealed.ciphertext).ok()?;
+    if nonce.len() != 12 {
+        return None;
+    }
+
+    let cipher = Aes256Gcm::new_from_slice(&derive_key(passphrase, &salt)).ok()?;
+    cipher.decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref()).ok()
+}
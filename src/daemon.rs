@@ -0,0 +1,66 @@
+use std::process::ExitCode;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use tracing::{error, info};
+
+#[cfg(feature = "mqtt")]
+use crate::mqtt;
+#[cfg(feature = "mastodon")]
+use crate::streaming;
+use crate::{api, cli, connectivity, engagement, expiry, github, scheduler, settings};
+
+/// Runs without a window, for a home server or VPS: loads the stored tokens, then posts scheduled posts and queues,
+/// watches releases, samples engagement, deletes expired posts, posts MQTT messages (with the `mqtt` feature),
+/// streams Mastodon notifications into the inbox and serves the local API (`settings.api_address`) until interrupted
+/// or terminated.
+pub fn run() -> ExitCode {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let state = cli::load_state().await;
+        tokio::spawn(connectivity::monitor(Arc::clone(&state)));
+        tokio::spawn(scheduler::run(Arc::clone(&state)));
+        tokio::spawn(github::watch(Arc::clone(&state)));
+        tokio::spawn(engagement::track(Arc::clone(&state)));
+        tokio::spawn(expiry::run(Arc::clone(&state)));
+        #[cfg(feature = "mqtt")]
+        tokio::spawn(mqtt::listen(Arc::clone(&state)));
+        #[cfg(feature = "mastodon")]
+        tokio::spawn(streaming::listen(Arc::clone(&state)));
+
+        let address = settings::get().api_address;
+        if !address.is_empty() {
+            let state = Arc::clone(&state);
+            tokio::spawn(async move {
+                if let Err(err) = api::serve(state, &address).await {
+                    error!(address, error = ?err, "Local API stopped");
+                }
+            });
+        }
+        info!("Running as a daemon; stop with Ctrl+C or SIGTERM");
+
+        match shutdown().await {
+            Ok(()) => {
+                info!("Daemon stopped");
+                ExitCode::SUCCESS
+            }
+            Err(err) => {
+                error!(error = ?err, "Failed to listen for shutdown signals");
+                ExitCode::FAILURE
+            }
+        }
+    })
+}
+
+/// Waits for Ctrl+C, or on Unix for SIGTERM as sent by systemd and `docker stop`.
+async fn shutdown() -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result,
+            _ = terminate.recv() => Ok(()),
+        }
+    }
+    #[cfg(not(unix))]
+    tokio::signal::ctrl_c().await
+}
@@ -0,0 +1,66 @@
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tracing::info;
+
+use crate::engagement::Engagement;
+use crate::posts::PostError;
+use crate::profiles;
+
+const POSTS_FILE: &str = "demo_posts.json";
+
+/// A post "published" to the Demo platform.  Nothing leaves the machine; posts are only recorded locally.
+#[derive(Serialize, Deserialize)]
+pub struct DemoPost {
+    pub id: String,
+    pub text: String,
+    pub created_at: DateTime<Utc>,
+}
+
+pub fn load_posts() -> Vec<DemoPost> {
+    let path = profiles::path(POSTS_FILE);
+    if path.exists() {
+        let json = fs::read_to_string(path).expect("Failed to read demo posts file");
+        serde_json::from_str(&json).unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+fn save_posts(posts: &[DemoPost]) {
+    let json = serde_json::to_string_pretty(posts).expect("Failed to serialize demo posts");
+    fs::write(profiles::path(POSTS_FILE), json).expect("Failed to write demo posts file");
+}
+
+/// Accepts a post instantly and records it to the demo posts file, returning its id.
+pub async fn post_to_demo(text: &str) -> Result<String, PostError> {
+    let created_at = Utc::now();
+    let id = format!("demo-{}", created_at.timestamp_millis());
+
+    let mut posts = load_posts();
+    posts.push(DemoPost {
+        id: id.clone(),
+        text: text.to_string(),
+        created_at,
+    });
+    save_posts(&posts);
+
+    info!(platform = "demo", post_id = %id, "Recorded demo post");
+    Ok(id)
+}
+
+/// Makes up engagement numbers so the comparison view has something to show.
+pub async fn fetch_engagement(post_id: &str) -> Option<Engagement> {
+    if !load_posts().iter().any(|p| p.id == post_id) {
+        return None;
+    }
+
+    let mut rng = rand::thread_rng();
+    Some(Engagement {
+        impressions: Some(rng.gen_range(50..5000)),
+        likes: Some(rng.gen_range(0..200)),
+        reposts: Some(rng.gen_range(0..50)),
+        replies: Some(rng.gen_range(0..20)),
+    })
+}
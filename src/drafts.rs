@@ -0,0 +1,57 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::posts::PostOptions;
+
+const DRAFTS_FILE: &str = "drafts.json";
+
+/// Longest summary shown for a draft in the list.
+const SUMMARY_CHARS: usize = 40;
+
+/// A post or whole thread put aside to finish later, with everything the composer held for it.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Draft {
+    pub saved_at: DateTime<Utc>,
+    pub text: String,
+    /// Attachments, per-platform texts and the further posts of a thread with their own images.
+    #[serde(default)]
+    pub options: PostOptions,
+    /// Accounts picked for this post, when they differ from the checked platforms.
+    #[serde(default)]
+    pub targets: Option<Vec<String>>,
+}
+
+impl Draft {
+    /// The start of the text, and how many posts it has when it's a thread.
+    pub fn summary(&self) -> String {
+        let first_line = self
+            .text
+            .lines()
+            .find(|line| !line.trim().is_empty())
+            .unwrap_or_default();
+        let mut summary: String = first_line.chars().take(SUMMARY_CHARS).collect();
+        if first_line.chars().count() > SUMMARY_CHARS {
+            summary.push('…');
+        }
+        if !self.options.thread.is_empty() {
+            summary.push_str(&format!(" (thread of {})", self.options.thread.len() + 1));
+        }
+        summary
+    }
+}
+
+pub fn load_drafts() -> Vec<Draft> {
+    if Path::new(DRAFTS_FILE).exists() {
+        let json = fs::read_to_string(DRAFTS_FILE).expect("Failed to read drafts file");
+        serde_json::from_str(&json).unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+pub fn save_drafts(drafts: &[Draft]) {
+    let json = serde_json::to_string_pretty(drafts).expect("Failed to serialize drafts");
+    fs::write(DRAFTS_FILE, json).expect("Failed to write drafts file");
+}
@@ -0,0 +1,129 @@
+use serde::Deserialize;
+use std::time::Instant;
+use tracing::{error, info, warn};
+
+use crate::mail::Service;
+use crate::posts::PostError;
+use crate::{http, mail, markdown, settings};
+
+const BUTTONDOWN_API_URL: &str = "https://api.buttondown.email/v1/emails";
+const MAILGUN_API_URL: &str = "https://api.mailgun.net/v3";
+/// Longest subject taken from the first line of a post.
+const MAX_SUBJECT_CHARS: usize = 80;
+
+/// Whether settings have what the chosen service needs to send.
+pub fn is_configured() -> bool {
+    let settings = settings::get();
+    let set = |value: &str| !value.trim().is_empty();
+    match settings.newsletter_service {
+        Service::Smtp => set(&settings.smtp_host) && set(&settings.newsletter_to),
+        Service::Buttondown => set(&settings.newsletter_api_key),
+        Service::Mailgun => {
+            set(&settings.newsletter_api_key)
+                && set(&settings.mailgun_domain)
+                && set(&settings.newsletter_to)
+                && set(&settings.newsletter_from)
+        }
+    }
+}
+
+/// Sends the post as an email through the configured service, returning the message id when the service gives one.
+pub async fn send_post(text: &str) -> Result<Option<String>, PostError> {
+    let settings = settings::get();
+    let subject = markdown::title(text, MAX_SUBJECT_CHARS).unwrap_or_else(|| "New post".to_string());
+    match settings.newsletter_service {
+        Service::Smtp => {
+            let started = Instant::now();
+            match mail::send(
+                &settings,
+                &settings.newsletter_from,
+                &settings.newsletter_to,
+                &subject,
+                text.to_string(),
+            )
+            .await
+            {
+                Ok(reply) => {
+                    let duration_ms = started.elapsed().as_millis() as u64;
+                    info!(platform = "email", request = "smtp", duration_ms, %reply, "Sent post by email");
+                    Ok(None)
+                }
+                Err(err) => {
+                    error!(platform = "email", request = "smtp", error = %err, "Failed to send post by email");
+                    Err(PostError::Rejected)
+                }
+            }
+        }
+        Service::Buttondown => {
+            let request = http::client()
+                .post(BUTTONDOWN_API_URL)
+                .header("Authorization", format!("Token {}", settings.newsletter_api_key.trim()))
+                .json(&serde_json::json!({
+                    "subject": subject,
+                    "body": text,
+                    "status": "about_to_send",
+                }));
+            send_api(request, "buttondown").await
+        }
+        Service::Mailgun => {
+            let request = http::client()
+                .post(format!(
+                    "{}/{}/messages",
+                    MAILGUN_API_URL,
+                    settings.mailgun_domain.trim()
+                ))
+                .basic_auth("api", Some(settings.newsletter_api_key.trim()))
+                .form(&[
+                    ("from", settings.newsletter_from.trim()),
+                    ("to", settings.newsletter_to.trim()),
+                    ("subject", subject.as_str()),
+                    ("text", text),
+                ]);
+            send_api(request, "mailgun").await
+        }
+    }
+}
+
+/// Sends a request to a newsletter API that answers with the new message's `id`.
+async fn send_api(request: reqwest::RequestBuilder, service: &str) -> Result<Option<String>, PostError> {
+    #[derive(Deserialize)]
+    struct Created {
+        id: Option<String>,
+    }
+
+    let started = Instant::now();
+    match http::send(request).await {
+        Ok(response) => {
+            let status = response.status();
+            let duration_ms = started.elapsed().as_millis() as u64;
+            let body = response.text().await.unwrap_or_default();
+            if status.is_success() {
+                let id = serde_json::from_str::<Created>(&body)
+                    .ok()
+                    .and_then(|created| created.id);
+                info!(
+                    platform = "email",
+                    request = service,
+                    status = status.as_u16(),
+                    duration_ms,
+                    "Sent newsletter"
+                );
+                Ok(id)
+            } else {
+                warn!(
+                    platform = "email",
+                    request = service,
+                    status = status.as_u16(),
+                    duration_ms,
+                    %body,
+                    "Failed to send newsletter"
+                );
+                Err(PostError::from_status(status))
+            }
+        }
+        Err(err) => {
+            error!(platform = "email", request = service, error = ?err, "Error sending newsletter");
+            Err(PostError::Network)
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::debug;
+
+#[cfg(feature = "bluesky")]
+use crate::bluesky;
+#[cfg(feature = "demo")]
+use crate::demo;
+use crate::history::{self, HistoryEntry};
+#[cfg(feature = "mastodon")]
+use crate::mastodon;
+#[cfg(feature = "twitter")]
+use crate::twitter;
+use crate::{posts, shared};
+
+const SAMPLES_FILE: &str = "engagement_samples.json";
+/// Days after posting that a post's engagement keeps being sampled.
+pub const TRACKED_DAYS: i64 = 7;
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Engagement numbers for a single post on one platform.  A field is `None` when the platform doesn't expose it.
+#[derive(Clone, Default)]
+pub struct Engagement {
+    pub impressions: Option<u64>,
+    pub likes: Option<u64>,
+    pub reposts: Option<u64>,
+    pub replies: Option<u64>,
+}
+
+/// One row of the comparison view: the platform and whatever engagement could be fetched for it.
+#[derive(Clone)]
+pub struct ComparisonRow {
+    pub platform: String,
+    pub engagement: Option<Engagement>,
+}
+
+/// Fetches the current engagement of a history entry on every platform it was posted to.
+#[cfg_attr(not(feature = "bluesky"), allow(unused_variables))]
+pub async fn compare(entry: &HistoryEntry, bluesky_token: Option<String>) -> Vec<ComparisonRow> {
+    let mut rows = Vec::new();
+
+    for result in entry.results.iter().filter(|r| r.success) {
+        let engagement = match (result.platform.as_str(), result.post_id.as_deref()) {
+            #[cfg(feature = "twitter")]
+            ("Twitter", Some(id)) => match twitter::load_bearer_token() {
+                Some(token) => twitter::fetch_engagement(&token, id).await,
+                None => None,
+            },
+            #[cfg(feature = "bluesky")]
+            ("Bluesky", Some(uri)) => match bluesky_token.as_deref() {
+                Some(token) => bluesky::fetch_engagement(token, uri).await,
+                None => None,
+            },
+            #[cfg(feature = "mastodon")]
+            ("Mastodon", Some(id)) => mastodon::fetch_engagement(&mastodon::api_base_url(), id).await,
+            #[cfg(feature = "mastodon")]
+            (platform, Some(id)) if platform.starts_with(mastodon::ACCOUNT_PREFIX) => {
+                match mastodon::find_account(platform) {
+                    Some(account) => mastodon::fetch_engagement(&account.base_url(), id).await,
+                    None => None,
+                }
+            }
+            #[cfg(feature = "demo")]
+            ("Demo", Some(id)) => demo::fetch_engagement(id).await,
+            _ => None,
+        };
+
+        rows.push(ComparisonRow {
+            platform: result.platform.clone(),
+            engagement,
+        });
+    }
+
+    rows
+}
+
+/// Likes and reposts of a post on one platform at one moment, to chart how they grow after posting.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Sample {
+    pub platform: String,
+    pub at: DateTime<Utc>,
+    pub likes: Option<u64>,
+    pub reposts: Option<u64>,
+}
+
+/// Samples keyed by history entry id, oldest first.
+pub fn load_samples() -> HashMap<String, Vec<Sample>> {
+    let path = shared::path(SAMPLES_FILE);
+    if path.exists() {
+        let json = fs::read_to_string(path).expect("Failed to read engagement samples file");
+        serde_json::from_str(&json).unwrap_or_default()
+    } else {
+        HashMap::new()
+    }
+}
+
+pub fn save_samples(samples: &HashMap<String, Vec<Sample>>) {
+    let json = serde_json::to_string_pretty(samples).expect("Failed to serialize engagement samples");
+    fs::write(shared::path(SAMPLES_FILE), json).expect("Failed to write engagement samples file");
+}
+
+/// Adds the engagement just fetched for a history entry to its samples and saves them.
+pub fn record(samples: &mut HashMap<String, Vec<Sample>>, entry_id: &str, rows: &[ComparisonRow]) {
+    let at = Utc::now();
+    let entry_samples = samples.entry(entry_id.to_string()).or_default();
+    for row in rows {
+        if let Some(engagement) = &row.engagement {
+            entry_samples.push(Sample {
+                platform: row.platform.clone(),
+                at,
+                likes: engagement.likes,
+                reposts: engagement.reposts,
+            });
+        }
+    }
+    save_samples(samples);
+}
+
+/// Samples the engagement of every post from the last `TRACKED_DAYS` days each hour, so its growth can be charted.
+pub async fn track(state: Arc<Mutex<posts::AppState>>) {
+    loop {
+        let (offline, bluesky_token) = {
+            let state = state.lock().await;
+            (state.offline, state.bluesky_token.clone())
+        };
+        if !offline {
+            let since = Utc::now() - chrono::Duration::days(TRACKED_DAYS);
+            let recent: Vec<HistoryEntry> = history::load_history()
+                .into_iter()
+                .filter(|entry| entry.posted_at >= since)
+                .collect();
+            for entry in &recent {
+                let rows = compare(entry, bluesky_token.clone()).await;
+                record(&mut state.lock().await.engagement_samples, &entry.id, &rows);
+            }
+            debug!(posts = recent.len(), "Sampled engagement");
+        }
+        tokio::time::sleep(SAMPLE_INTERVAL).await;
+    }
+}
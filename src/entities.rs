@@ -0,0 +1,82 @@
+use std::ops::Range;
+
+use crate::links;
+
+/// Something in a post that platforms turn into a link.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Kind {
+    Link,
+    Mention,
+    Hashtag,
+}
+
+/// Whether the character before byte `start` keeps a `#` or `@` there from starting an entity, as in URL fragments
+/// or email addresses.
+fn preceded_by_word(text: &str, start: usize) -> bool {
+    text[..start]
+        .chars()
+        .next_back()
+        .is_some_and(|c| !c.is_whitespace() && c != '(')
+}
+
+/// The `#hashtags` in `text`, with the byte range of the whole tag and its name without `#`.  A tag starts at the
+/// beginning of the text or after whitespace or an opening bracket, and all-digit ones like `#1` aren't tags.
+pub fn hashtags(text: &str) -> Vec<(Range<usize>, &str)> {
+    let mut tags = Vec::new();
+    for (start, _) in text.match_indices('#') {
+        if preceded_by_word(text, start) {
+            continue;
+        }
+        let body_start = start + 1;
+        let body_len = text[body_start..]
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(text.len() - body_start);
+        let tag = &text[body_start..body_start + body_len];
+        if !tag.is_empty() && !tag.chars().all(|c| c.is_ascii_digit()) {
+            tags.push((start..body_start + body_len, tag));
+        }
+    }
+    tags
+}
+
+/// The `@user`, `@user@instance` and `@handle.domain` mentions in `text`, with their byte ranges, as the platforms
+/// would link at least one of them.
+pub fn mentions(text: &str) -> Vec<Range<usize>> {
+    let mut mentions = Vec::new();
+    for (start, _) in text.match_indices('@') {
+        if preceded_by_word(text, start) {
+            continue;
+        }
+        let body_start = start + 1;
+        let body_len = text[body_start..]
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '.' | '-' | '@')))
+            .unwrap_or(text.len() - body_start);
+        // Sentence punctuation right after a handle isn't part of it
+        let handle = text[body_start..body_start + body_len].trim_end_matches(['.', '-', '@']);
+        if !handle.is_empty() {
+            mentions.push(start..body_start + handle.len());
+        }
+    }
+    mentions
+}
+
+/// Every link, mention and hashtag in `text`, in order and without overlaps.
+pub fn find(text: &str) -> Vec<(Range<usize>, Kind)> {
+    let mut entities: Vec<(Range<usize>, Kind)> = links::find_urls(text)
+        .into_iter()
+        .map(|(range, _)| (range, Kind::Link))
+        .collect();
+    entities.extend(mentions(text).into_iter().map(|range| (range, Kind::Mention)));
+    entities.extend(hashtags(text).into_iter().map(|(range, _)| (range, Kind::Hashtag)));
+    entities.sort_by_key(|(range, _)| range.start);
+
+    let mut end = 0;
+    entities.retain(|(range, _)| {
+        let keep = range.start >= end;
+        if keep {
+            end = range.end;
+        }
+        keep
+    });
+    entities
+}
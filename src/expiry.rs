@@ -0,0 +1,65 @@
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::history::{self, HistoryEntry};
+use crate::{audit, posts, publish, shared};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Deletes posts once their `expires_at` comes, from every platform that can delete them, and marks them expired in
+/// history.  A post that couldn't be deleted everywhere is tried again on the next check.  Waits while offline.
+pub async fn run(state: Arc<Mutex<posts::AppState>>) {
+    loop {
+        let offline = state.lock().await.offline;
+        if !offline {
+            expire_due(&state).await;
+        }
+        tokio::time::sleep(CHECK_INTERVAL).await;
+    }
+}
+
+async fn expire_due(state: &Arc<Mutex<posts::AppState>>) {
+    // With a shared history, only one machine deletes at a time
+    let Some(_lock) = shared::FileLock::acquire("expiry") else {
+        return;
+    };
+    let now = Utc::now();
+    let due: Vec<HistoryEntry> = history::load_history()
+        .into_iter()
+        .filter(|entry| !entry.expired && entry.expires_at.is_some_and(|at| at <= now))
+        .collect();
+
+    for entry in due {
+        let mut all_deleted = true;
+        let deletable = entry
+            .results
+            .iter()
+            .filter(|result| result.success && publish::can_delete(&result.platform));
+        for result in deletable {
+            all_deleted &= publish::delete_published(result).await;
+        }
+        if !all_deleted {
+            warn!(entry_id = %entry.id, "Couldn't delete an expired post everywhere; trying again later");
+            continue;
+        }
+
+        // Read again, since posts may have been added while deleting
+        let mut entries = history::load_history();
+        if let Some(expired) = entries.iter_mut().find(|e| e.id == entry.id) {
+            expired.expired = true;
+        }
+        history::save_history(&entries);
+        if let Some(expired) = state.lock().await.history.iter_mut().find(|e| e.id == entry.id) {
+            expired.expired = true;
+        }
+        audit::record(
+            audit::Action::Delete,
+            None,
+            &format!("Deleted expired post {}", entry.id),
+        );
+        info!(entry_id = %entry.id, "Deleted expired post");
+    }
+}
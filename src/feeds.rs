@@ -0,0 +1,127 @@
+use tracing::{debug, error};
+
+use crate::html;
+use crate::http;
+
+/// The newest item of a blog's RSS or Atom feed, ready for the composer.
+pub struct FeedItem {
+    pub title: String,
+    pub link: String,
+    /// The item's content or summary, converted from HTML to plain text.
+    pub text: String,
+}
+
+impl FeedItem {
+    /// Text to start a post with: the title, the item's text and the link, a blank line apart.
+    pub fn compose(&self) -> String {
+        [self.title.as_str(), self.text.as_str(), self.link.as_str()]
+            .iter()
+            .filter(|part| !part.is_empty())
+            .copied()
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+/// Fetches the feed at `url` and returns its first item (feeds list the newest first).  `None` when the feed can't
+/// be fetched or has no items.
+pub async fn latest_item(url: &str) -> Option<FeedItem> {
+    let response = match http::send_idempotent(http::client().get(url)).await {
+        Ok(response) => response,
+        Err(err) => {
+            error!(url, error = ?err, "Error fetching feed");
+            return None;
+        }
+    };
+    if !response.status().is_success() {
+        debug!(url, status = response.status().as_u16(), "Failed to fetch feed");
+        return None;
+    }
+    let xml = response.text().await.ok()?;
+    parse_latest(&xml)
+}
+
+/// Reads the first `<item>` (RSS) or `<entry>` (Atom) of a feed.
+fn parse_latest(xml: &str) -> Option<FeedItem> {
+    let lower = xml.to_ascii_lowercase();
+    let start = ["<item>", "<item ", "<entry>", "<entry "]
+        .iter()
+        .filter_map(|tag| lower.find(tag))
+        .min()?;
+    let end = ["</item>", "</entry>"]
+        .iter()
+        .filter_map(|tag| lower[start..].find(tag))
+        .min()
+        .map_or(xml.len(), |end| start + end);
+    let item = &xml[start..end];
+
+    let title = element(item, "title").map(|title| html::to_plain_text(&title))?;
+    // RSS has the address as the element's text, Atom as its href
+    let link = element(item, "link")
+        .filter(|link| !link.trim().is_empty())
+        .or_else(|| link_href(item))
+        .unwrap_or_default();
+    let body = ["content:encoded", "description", "content", "summary"]
+        .iter()
+        .find_map(|name| element(item, name))
+        .unwrap_or_default();
+    Some(FeedItem {
+        title,
+        link: link.trim().to_string(),
+        text: html::to_plain_text(&body),
+    })
+}
+
+/// The content of the first `<name>` element: unwrapped when it's CDATA, with XML entities decoded otherwise.
+fn element(xml: &str, name: &str) -> Option<String> {
+    let lower = xml.to_ascii_lowercase();
+    let open = format!("<{}", name);
+    let mut from = 0;
+    let start = loop {
+        let found = from + lower[from..].find(&open)?;
+        // Don't take <content:encoded> for <content>, or <linkedin> for <link>
+        let next = lower[found + open.len()..].chars().next()?;
+        if next == '>' || next.is_whitespace() || next == '/' {
+            break found;
+        }
+        from = found + open.len();
+    };
+    let tag_end = start + lower[start..].find('>')?;
+    if xml[..tag_end].ends_with('/') {
+        return None;
+    }
+    let content_start = tag_end + 1;
+    let content_end = content_start + lower[content_start..].find(&format!("</{}>", name))?;
+    let content = xml[content_start..content_end].trim();
+    Some(
+        match content.strip_prefix("<![CDATA[").and_then(|c| c.strip_suffix("]]>")) {
+            Some(cdata) => cdata.to_string(),
+            None => html::decode_entities(content),
+        },
+    )
+}
+
+/// The `href` of an Atom `<link>`, preferring the `rel="alternate"` one (the post itself).
+fn link_href(xml: &str) -> Option<String> {
+    let lower = xml.to_ascii_lowercase();
+    let mut links = Vec::new();
+    let mut from = 0;
+    while let Some(found) = lower[from..].find("<link") {
+        let start = from + found;
+        let end = start + lower[start..].find('>')?;
+        let tag = &xml[start..end];
+        let tag_lower = &lower[start..end];
+        if let Some(href_start) = tag_lower.find("href=\"") {
+            let value = &tag[href_start + "href=\"".len()..];
+            let href = value.split('"').next().unwrap_or_default();
+            let alternate = !tag_lower.contains("rel=") || tag_lower.contains("rel=\"alternate\"");
+            links.push((alternate, html::decode_entities(href)));
+        }
+        from = end;
+    }
+    links
+        .iter()
+        .find(|(alternate, _)| *alternate)
+        .or(links.first())
+        .map(|(_, href)| href.clone())
+}
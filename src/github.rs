@@ -0,0 +1,166 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::{error, info, warn};
+
+use crate::{http, posts, publish, settings, shared};
+
+const SEEN_FILE: &str = "github_releases.json";
+const DEFAULT_API_BASE_URL: &str = "https://api.github.com";
+const POLL_INTERVAL: Duration = Duration::from_secs(600);
+
+pub const DEFAULT_TEMPLATE: &str = "🚀 {repo} {tag} is out!\n\n{name}\n{url}";
+
+/// A release of a watched repository.
+#[derive(Clone, Deserialize)]
+pub struct Release {
+    pub id: u64,
+    pub tag_name: String,
+    pub name: Option<String>,
+    pub html_url: String,
+}
+
+/// An announcement drafted for a new release, waiting for the user to post or dismiss it.
+#[derive(Clone)]
+pub struct ReleaseDraft {
+    pub repo: String,
+    pub text: String,
+}
+
+fn api_base_url() -> String {
+    settings::api_base_url("github", DEFAULT_API_BASE_URL)
+}
+
+/// Latest release id seen per repository, so each release is announced once.
+fn load_seen() -> HashMap<String, u64> {
+    let path = shared::path(SEEN_FILE);
+    if path.exists() {
+        let json = fs::read_to_string(&path).expect("Failed to read GitHub releases file");
+        serde_json::from_str(&json).unwrap_or_default()
+    } else {
+        HashMap::new()
+    }
+}
+
+fn save_seen(seen: &HashMap<String, u64>) {
+    let json = serde_json::to_string_pretty(seen).expect("Failed to serialize GitHub releases");
+    fs::write(shared::path(SEEN_FILE), json).expect("Failed to write GitHub releases file");
+}
+
+/// The repositories from `settings.github_repos`.
+pub fn watched_repos() -> Vec<String> {
+    settings::get()
+        .github_repos
+        .split(',')
+        .map(|repo| repo.trim().to_string())
+        .filter(|repo| !repo.is_empty())
+        .collect()
+}
+
+/// Fills in `{repo}`, `{tag}`, `{name}` and `{url}`.
+pub fn render(template: &str, repo: &str, release: &Release) -> String {
+    template
+        .replace("{repo}", repo)
+        .replace("{tag}", &release.tag_name)
+        .replace("{name}", release.name.as_deref().unwrap_or(&release.tag_name))
+        .replace("{url}", &release.html_url)
+}
+
+/// Fetches the latest published release of `repo` ("owner/name").  Uses `GITHUB_TOKEN` when set, for private
+/// repositories and a higher rate limit.
+pub async fn latest_release(repo: &str) -> Option<Release> {
+    let mut request = http::client()
+        .get(format!("{}/repos/{}/releases/latest", api_base_url(), repo))
+        .header("Accept", "application/vnd.github+json");
+    if let Ok(token) = std::env::var("GITHUB_TOKEN") {
+        request = request.bearer_auth(token);
+    }
+
+    let started = Instant::now();
+    match http::send_idempotent(request).await {
+        Ok(response) if response.status().is_success() => response.json::<Release>().await.ok(),
+        Ok(response) if response.status() == reqwest::StatusCode::NOT_FOUND => None, // No releases yet
+        Ok(response) => {
+            let status = response.status();
+            let duration_ms = started.elapsed().as_millis() as u64;
+            let body = response.text().await.unwrap_or_default();
+            warn!(
+                platform = "github",
+                request = "latest_release",
+                repo,
+                status = status.as_u16(),
+                duration_ms,
+                %body,
+                "Failed to fetch latest release"
+            );
+            None
+        }
+        Err(err) => {
+            error!(platform = "github", request = "latest_release", repo, error = ?err, "Error fetching release");
+            None
+        }
+    }
+}
+
+/// Polls the watched repositories and announces new releases: posted right away when auto-posting is on, otherwise
+/// drafted for the user.  The first time a repository is seen, its current release is only recorded.
+pub async fn watch(state: Arc<Mutex<posts::AppState>>) {
+    loop {
+        if !state.lock().await.offline {
+            check(&state).await;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+async fn check(state: &Arc<Mutex<posts::AppState>>) {
+    let repos = watched_repos();
+    if repos.is_empty() {
+        return;
+    }
+
+    let settings = settings::get();
+    let mut seen = load_seen();
+    for repo in &repos {
+        let Some(release) = latest_release(repo).await else {
+            continue;
+        };
+        // Ids only grow, so an older id showing up as latest means a newer release was deleted
+        let previous = seen.get(repo).copied();
+        if previous.is_some_and(|previous| previous >= release.id) {
+            continue;
+        }
+        seen.insert(repo.clone(), release.id);
+        save_seen(&seen);
+        if previous.is_none() {
+            info!(repo = %repo, tag = %release.tag_name, "Watching for releases newer than the current one");
+            continue;
+        }
+
+        info!(repo = %repo, tag = %release.tag_name, "New GitHub release");
+        let text = render(&settings.release_template, repo, &release);
+        if settings.release_auto_post {
+            // The release id makes the key stable, so a restart mid-post can't announce twice
+            let idempotency_key = format!("github-release-{}", release.id);
+            publish::publish(
+                state,
+                &text,
+                &settings.release_platforms,
+                &idempotency_key,
+                &posts::PostOptions::default(),
+                &shared::current_user(),
+            )
+            .await;
+        } else {
+            let mut state = state.lock().await;
+            state.toast(format!("New release of {}: announcement drafted", repo), true);
+            state.release_drafts.push(ReleaseDraft {
+                repo: repo.clone(),
+                text,
+            });
+        }
+    }
+}
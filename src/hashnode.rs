@@ -0,0 +1,212 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::Instant;
+use tracing::{error, info, warn};
+
+use crate::posts::PostError;
+use crate::{audit, http, markdown, settings, tokens};
+
+const TOKEN_FILE: &str = "hashnode_tokens.json";
+/// Upgrades of the stored `TokenData` written by older versions; see `tokens::load`.
+const TOKEN_MIGRATIONS: &[tokens::Migration] = &[];
+const DEFAULT_API_BASE_URL: &str = "https://gql.hashnode.com";
+const MAX_TITLE_CHARS: usize = 100;
+
+fn api_base_url() -> String {
+    settings::api_base_url("hashnode", DEFAULT_API_BASE_URL)
+}
+
+/// A personal access token, made under Hashnode's Account settings → Developer.
+#[derive(Serialize, Deserialize)]
+pub struct TokenData {
+    pub access_token: String,
+}
+
+pub fn save_token(access_token: &str) {
+    let token_data = TokenData {
+        access_token: access_token.to_string(),
+    };
+    tokens::save(TOKEN_FILE, TOKEN_MIGRATIONS, &token_data);
+}
+
+/// The stored token, or `HASHNODE_TOKEN` when none has been stored.
+pub fn load_token() -> Option<String> {
+    tokens::load::<TokenData>(TOKEN_FILE, TOKEN_MIGRATIONS)
+        .map(|token_data| token_data.access_token)
+        .or_else(|| std::env::var("HASHNODE_TOKEN").ok())
+}
+
+#[derive(Deserialize)]
+struct GraphQlResponse<T> {
+    data: Option<T>,
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+#[derive(Deserialize)]
+struct GraphQlError {
+    message: String,
+}
+
+/// Runs a GraphQL query, returning its data.  Hashnode answers errors with status 200 and an `errors` list, so both
+/// are checked.
+async fn query<T: DeserializeOwned>(
+    token: &str,
+    request: &str,
+    query: &str,
+    variables: serde_json::Value,
+) -> Result<T, PostError> {
+    let started = Instant::now();
+    let response = match http::send(
+        http::client()
+            .post(api_base_url())
+            .header("Authorization", token)
+            .json(&json!({ "query": query, "variables": variables })),
+    )
+    .await
+    {
+        Ok(response) => response,
+        Err(err) => {
+            error!(platform = "hashnode", request, error = ?err, "Error sending request to Hashnode");
+            return Err(PostError::Network);
+        }
+    };
+    let status = response.status();
+    let duration_ms = started.elapsed().as_millis() as u64;
+    let body = response.text().await.unwrap_or_default();
+    if !status.is_success() {
+        warn!(platform = "hashnode", request, status = status.as_u16(), duration_ms, %body, "Hashnode request failed");
+        return Err(PostError::from_status(status));
+    }
+    match serde_json::from_str::<GraphQlResponse<T>>(&body) {
+        Ok(GraphQlResponse {
+            data: Some(data),
+            errors,
+        }) if errors.is_empty() => Ok(data),
+        Ok(GraphQlResponse { errors, .. }) => {
+            let messages: Vec<&str> = errors.iter().map(|err| err.message.as_str()).collect();
+            warn!(platform = "hashnode", request, duration_ms, errors = %messages.join("; "), "Hashnode refused the request");
+            Err(PostError::Rejected)
+        }
+        Err(_) => {
+            error!(platform = "hashnode", request, "Failed to parse the Hashnode response");
+            Err(PostError::Rejected)
+        }
+    }
+}
+
+/// The id of the publication posts go to: the one at `settings.hashnode_host` (e.g. "blog.example.com"), or the
+/// token user's first publication when that is empty.
+async fn publication_id(token: &str) -> Result<String, PostError> {
+    #[derive(Deserialize)]
+    struct Id {
+        id: String,
+    }
+
+    let host = settings::get().hashnode_host.trim().to_string();
+    if !host.is_empty() {
+        #[derive(Deserialize)]
+        struct ByHost {
+            publication: Option<Id>,
+        }
+
+        let data: ByHost = query(
+            token,
+            "publication",
+            "query Publication($host: String!) { publication(host: $host) { id } }",
+            json!({ "host": host }),
+        )
+        .await?;
+        return data.publication.map(|publication| publication.id).ok_or_else(|| {
+            warn!(platform = "hashnode", %host, "No Hashnode publication at this host");
+            PostError::Rejected
+        });
+    }
+
+    #[derive(Deserialize)]
+    struct Me {
+        me: User,
+    }
+    #[derive(Deserialize)]
+    struct User {
+        publications: Connection,
+    }
+    #[derive(Deserialize)]
+    struct Connection {
+        edges: Vec<Edge>,
+    }
+    #[derive(Deserialize)]
+    struct Edge {
+        node: Id,
+    }
+
+    let data: Me = query(
+        token,
+        "me",
+        "query { me { publications(first: 1) { edges { node { id } } } } }",
+        json!({}),
+    )
+    .await?;
+    data.me
+        .publications
+        .edges
+        .into_iter()
+        .next()
+        .map(|edge| edge.node.id)
+        .ok_or_else(|| {
+            warn!(platform = "hashnode", "The Hashnode account has no publication");
+            PostError::Rejected
+        })
+}
+
+/// Checks `access_token` by looking up its publication and stores it.  Returns whether it works.
+pub async fn authorize_hashnode(access_token: &str) -> bool {
+    let access_token = access_token.trim();
+    if publication_id(access_token).await.is_err() {
+        return false;
+    }
+    save_token(access_token);
+    audit::record(audit::Action::Authorize, Some("Hashnode"), "");
+    info!(platform = "hashnode", "Authorized Hashnode");
+    true
+}
+
+/// Publishes the post, written in Markdown, to the Hashnode publication, titled with its first line.  Returns the
+/// post's id and address.
+pub async fn post_to_hashnode(token: &str, text: &str) -> Result<(String, String), PostError> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Published {
+        publish_post: Payload,
+    }
+    #[derive(Deserialize)]
+    struct Payload {
+        post: Post,
+    }
+    #[derive(Deserialize)]
+    struct Post {
+        id: String,
+        url: String,
+    }
+
+    let publication_id = publication_id(token).await?;
+    let title = markdown::title(text, MAX_TITLE_CHARS).unwrap_or_else(|| "Untitled".to_string());
+    let data: Published = query(
+        token,
+        "publishPost",
+        "mutation PublishPost($input: PublishPostInput!) { publishPost(input: $input) { post { id url } } }",
+        json!({
+            "input": {
+                "title": title,
+                "contentMarkdown": text,
+                "publicationId": publication_id,
+                "tags": [],
+            }
+        }),
+    )
+    .await?;
+    let post = data.publish_post.post;
+    info!(platform = "hashnode", request = "publishPost", post_id = %post.id, "Published post");
+    Ok((post.id, post.url))
+}
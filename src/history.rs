@@ -0,0 +1,180 @@
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+
+use crate::media::ImageAttachment;
+use crate::posts::PostError;
+use crate::shared;
+
+const HISTORY_FILE: &str = "post_history.json";
+
+/// Outcome of a post on a single platform.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PlatformResult {
+    pub platform: String,
+    pub success: bool,
+    /// Platform-specific identifier of the created post (tweet id, status id, at:// uri).
+    pub post_id: Option<String>,
+    /// Public web address of the post, when the platform provides one.
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Why the post failed, when it did.
+    #[serde(default)]
+    pub error: Option<PostError>,
+    /// Ids of every post of a thread, in order, so the thread can be deleted as a unit.  Empty for a single post.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub thread_ids: Vec<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: String,
+    pub text: String,
+    pub posted_at: DateTime<Utc>,
+    pub results: Vec<PlatformResult>,
+    /// Key of the compose action that produced this post, used to refuse resending it.
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+    /// Who posted it, for teams sharing a history.
+    #[serde(default)]
+    pub author: Option<String>,
+    /// Images attached to the post (and to the rest of its thread), as they were on disk when it was sent.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<ImageAttachment>,
+    /// When the post is to be deleted again; see `expiry`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Whether it was deleted from every platform that can delete once `expires_at` came.
+    #[serde(default)]
+    pub expired: bool,
+}
+
+impl HistoryEntry {
+    pub fn new(
+        text: &str,
+        results: Vec<PlatformResult>,
+        idempotency_key: &str,
+        author: &str,
+        images: Vec<ImageAttachment>,
+    ) -> Self {
+        let posted_at = Utc::now();
+        Self {
+            id: posted_at.timestamp_millis().to_string(),
+            text: text.to_string(),
+            posted_at,
+            results,
+            idempotency_key: Some(idempotency_key.to_string()),
+            author: Some(author.to_string()),
+            images,
+            expires_at: None,
+            expired: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum OutcomeFilter {
+    #[default]
+    Any,
+    Succeeded,
+    Failed,
+}
+
+/// Search and filters for the history view.  Empty fields match everything.
+#[derive(Default)]
+pub struct HistoryFilter {
+    /// Words that must all appear in the post text, case-insensitively.
+    pub query: String,
+    pub platform: Option<String>,
+    /// Inclusive date range as YYYY-MM-DD, in local time.  Unparseable dates are ignored.
+    pub from: String,
+    pub to: String,
+    /// Applies to the selected platform's result, or to the post as a whole (all succeeded / any failed).
+    pub outcome: OutcomeFilter,
+}
+
+impl HistoryFilter {
+    pub fn matches(&self, entry: &HistoryEntry) -> bool {
+        let text = entry.text.to_lowercase();
+        if !self
+            .query
+            .to_lowercase()
+            .split_whitespace()
+            .all(|word| text.contains(word))
+        {
+            return false;
+        }
+
+        let results: Vec<&PlatformResult> = match &self.platform {
+            Some(platform) => entry.results.iter().filter(|r| &r.platform == platform).collect(),
+            None => entry.results.iter().collect(),
+        };
+        if self.platform.is_some() && results.is_empty() {
+            return false;
+        }
+        match self.outcome {
+            OutcomeFilter::Any => {}
+            OutcomeFilter::Succeeded if !results.iter().all(|r| r.success) => return false,
+            OutcomeFilter::Failed if results.iter().all(|r| r.success) => return false,
+            _ => {}
+        }
+
+        let posted_on = entry.posted_at.with_timezone(&Local).date_naive();
+        let parse = |date: &str| NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok();
+        if parse(&self.from).is_some_and(|from| posted_on < from) {
+            return false;
+        }
+        if parse(&self.to).is_some_and(|to| posted_on > to) {
+            return false;
+        }
+        true
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.query.trim().is_empty()
+            || self.platform.is_some()
+            || !self.from.trim().is_empty()
+            || !self.to.trim().is_empty()
+            || self.outcome != OutcomeFilter::Any
+    }
+}
+
+/// Generates a random key identifying one compose action.
+pub fn new_idempotency_key() -> String {
+    let bytes: [u8; 16] = rand::random();
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Platforms that already have a successful post recorded under `idempotency_key`.
+pub fn posted_platforms(idempotency_key: &str) -> HashSet<String> {
+    load_history()
+        .into_iter()
+        .filter(|entry| entry.idempotency_key.as_deref() == Some(idempotency_key))
+        .flat_map(|entry| entry.results)
+        .filter(|result| result.success)
+        .map(|result| result.platform)
+        .collect()
+}
+
+pub fn load_history() -> Vec<HistoryEntry> {
+    let path = shared::path(HISTORY_FILE);
+    if path.exists() {
+        let json = fs::read_to_string(path).expect("Failed to read history file");
+        serde_json::from_str(&json).unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+pub fn save_history(entries: &[HistoryEntry]) {
+    let json = serde_json::to_string_pretty(entries).expect("Failed to serialize history");
+    fs::write(shared::path(HISTORY_FILE), json).expect("Failed to write history file");
+}
+
+/// Appends an entry to the history file.
+pub fn record(entry: &HistoryEntry) {
+    let mut entries = load_history();
+    entries.push(entry.clone());
+    save_history(&entries);
+}
@@ -0,0 +1,163 @@
+/// Tags whose end starts a new paragraph.
+const BLOCK_TAGS: [&str; 14] = [
+    "p",
+    "div",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "blockquote",
+    "pre",
+    "ul",
+    "ol",
+    "table",
+    "tr",
+];
+
+/// Converts HTML (a blog post or feed item) to plain text for the composer.  Scripts, styles and other markup are
+/// dropped; paragraphs and line breaks are kept, list items get bullets, and links keep their address as
+/// "text (url)" so it isn't lost with the markup.
+pub fn to_plain_text(html: &str) -> String {
+    let lower = html.to_ascii_lowercase();
+    let mut text = String::new();
+    let mut link: Option<(String, usize)> = None; // Address of the open link and where its text starts
+    let mut i = 0;
+    while i < html.len() {
+        let Some(open) = html[i..].find('<').map(|offset| i + offset) else {
+            push_text(&mut text, &html[i..]);
+            break;
+        };
+        push_text(&mut text, &html[i..open]);
+        if html[open..].starts_with("<!--") {
+            i = html[open..].find("-->").map_or(html.len(), |offset| open + offset + 3);
+            continue;
+        }
+        let Some(close) = html[open..].find('>').map(|offset| open + offset) else {
+            break;
+        };
+        let tag = &html[open + 1..close];
+        let closing = tag.starts_with('/');
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+        i = close + 1;
+
+        match name.as_str() {
+            "script" | "style" | "head" if !closing => {
+                // Skip everything up to the matching end tag
+                let end_tag = format!("</{}", name);
+                i = lower[i..].find(&end_tag).map_or(html.len(), |offset| i + offset);
+            }
+            "br" => text.push('\n'),
+            "li" if !closing => text.push_str("\n• "),
+            "a" if !closing => link = attribute(tag, "href").map(|href| (href, text.len())),
+            "a" => {
+                if let Some((href, start)) = link.take() {
+                    let label = text[start..].trim();
+                    if href.starts_with("http") && label != href {
+                        text.push_str(&format!(" ({})", href));
+                    }
+                }
+            }
+            name if BLOCK_TAGS.contains(&name) => text.push_str("\n\n"),
+            _ => {}
+        }
+    }
+    tidy(&text)
+}
+
+/// Appends text between tags, decoding entities and collapsing whitespace the way a browser shows it.
+fn push_text(text: &mut String, raw: &str) {
+    let decoded = decode_entities(raw);
+    for c in decoded.chars() {
+        if c.is_whitespace() {
+            if !text.ends_with(char::is_whitespace) && !text.is_empty() {
+                text.push(' ');
+            }
+        } else {
+            text.push(c);
+        }
+    }
+}
+
+/// Trims every line and leaves at most one blank line between paragraphs.
+fn tidy(text: &str) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() && lines.last().map_or(true, |last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    lines.join("\n").trim().to_string()
+}
+
+/// Value of the `name` attribute in the inside of a tag, quoted with either kind of quote.
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let start = lower.find(&format!("{}=", name))? + name.len() + 1;
+    let rest = &tag[start..];
+    let value = match rest.chars().next()? {
+        quote @ ('"' | '\'') => rest[1..].split(quote).next()?,
+        _ => rest.split(|c: char| c.is_whitespace() || c == '>').next()?,
+    };
+    Some(decode_entities(value))
+}
+
+/// Decodes the common named entities and numeric ones (`&#8217;`, `&#x2019;`).
+pub fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| Some((decode_entity(&rest[1..end + 1])?, end + 2)));
+        match entity {
+            Some((c, len)) => {
+                decoded.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_entity(name: &str) -> Option<char> {
+    if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "hellip" => '…',
+        "mdash" => '—',
+        "ndash" => '–',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "copy" => '©',
+        _ => return None,
+    })
+}
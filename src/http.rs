@@ -0,0 +1,63 @@
+use reqwest::{Client, RequestBuilder, Response};
+use std::time::Duration;
+use tracing::{error, warn};
+
+use crate::settings;
+
+/// Builds an HTTP client using the configured timeouts and User-Agent.
+pub fn client() -> Client {
+    let settings = settings::get();
+    let builder = || {
+        Client::builder()
+            .timeout(Duration::from_secs(settings.request_timeout_secs))
+            .connect_timeout(Duration::from_secs(settings.connect_timeout_secs))
+    };
+
+    match builder().user_agent(settings.user_agent.clone()).build() {
+        Ok(client) => client,
+        Err(err) => {
+            // Most likely a User-Agent containing characters that aren't valid in a header
+            error!(
+                user_agent = %settings.user_agent,
+                error = ?err,
+                "Invalid HTTP client settings, using the default User-Agent"
+            );
+            builder()
+                .user_agent(settings::default_user_agent())
+                .build()
+                .expect("Failed to build HTTP client")
+        }
+    }
+}
+
+/// Sends a request, retrying when the connection couldn't be established.  Timeouts are not retried because the
+/// server may already have acted on the request.
+pub async fn send(request: RequestBuilder) -> Result<Response, reqwest::Error> {
+    send_with_retries(request, false).await
+}
+
+/// Sends a request that is safe to repeat (e.g. a GET), retrying on connection failures and timeouts.
+pub async fn send_idempotent(request: RequestBuilder) -> Result<Response, reqwest::Error> {
+    send_with_retries(request, true).await
+}
+
+async fn send_with_retries(request: RequestBuilder, retry_timeouts: bool) -> Result<Response, reqwest::Error> {
+    let max_retries = settings::get().max_retries;
+    let mut attempt = 0;
+
+    loop {
+        // Streaming bodies can't be cloned, so those get a single attempt
+        let Some(current) = request.try_clone() else {
+            return request.send().await;
+        };
+
+        match current.send().await {
+            Err(err) if attempt < max_retries && (err.is_connect() || (retry_timeouts && err.is_timeout())) => {
+                attempt += 1;
+                warn!(attempt, max_retries, error = ?err, "Request failed, retrying");
+                tokio::time::sleep(Duration::from_millis(500 * 2u64.pow(attempt))).await;
+            }
+            result => return result,
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::collections::HashSet;
+use tracing::{info, warn};
+
+use crate::history::{self, HistoryEntry, PlatformResult};
+use crate::posts::PostOptions;
+use crate::{audit, scheduler, shared, timezone};
+
+/// Header names (lowercase) of the columns holding each field, across the Buffer and Hootsuite exports.
+const TEXT_COLUMNS: [&str; 6] = ["text", "message", "post text", "post content", "content", "body"];
+const TIME_COLUMNS: [&str; 10] = [
+    "posted at",
+    "sent at",
+    "published at",
+    "due at",
+    "scheduled at",
+    "scheduled date",
+    "date (gmt)",
+    "date",
+    "time",
+    "created at",
+];
+const PLATFORM_COLUMNS: [&str; 6] = [
+    "service",
+    "social network",
+    "network",
+    "channel",
+    "social profile",
+    "profile",
+];
+const STATUS_COLUMNS: [&str; 3] = ["status", "state", "type"];
+const LINK_COLUMNS: [&str; 3] = ["link", "url", "link url"];
+
+/// Formats times appear in, besides RFC 3339.  Without a zone they are read in the configured time zone.
+const TIME_FORMATS: [&str; 7] = [
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M:%S",
+    "%m/%d/%Y %H:%M",
+    "%m/%d/%Y %I:%M %p",
+    "%b %d, %Y %I:%M %p",
+    "%d %b %Y %H:%M",
+];
+
+/// What an import brought over.
+#[derive(Default)]
+pub struct Summary {
+    pub scheduled: usize,
+    pub history: usize,
+    /// Rows without text, already imported, or scheduled without a usable time.
+    pub skipped: usize,
+}
+
+/// Imports a CSV export from Buffer or Hootsuite: posts still waiting (by status, or dated in the future) join the
+/// schedule, and sent ones become history entries, so past content is searchable and counts toward posting caps.
+/// Rows whose network isn't recognised go to `default_platforms`.  Importing the same file twice doesn't duplicate
+/// history.
+pub fn import_csv(csv: &str, default_platforms: &[String]) -> Result<Summary, String> {
+    let mut rows = parse_csv(csv.trim_start_matches('\u{feff}')).into_iter();
+    let header: Vec<String> = rows
+        .next()
+        .ok_or("The file is empty")?
+        .iter()
+        .map(|name| name.trim().to_lowercase())
+        .collect();
+    let column = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| header.iter().position(|column| column == name))
+    };
+    let text_column = column(&TEXT_COLUMNS).ok_or("No column with the post text (Text, Message or Content)")?;
+    let time_column = column(&TIME_COLUMNS);
+    let platform_column = column(&PLATFORM_COLUMNS);
+    let status_column = column(&STATUS_COLUMNS);
+    let link_column = column(&LINK_COLUMNS);
+
+    let mut entries = history::load_history();
+    let mut seen: HashSet<(String, DateTime<Utc>)> = entries
+        .iter()
+        .map(|entry| (entry.text.clone(), entry.posted_at))
+        .collect();
+    let mut summary = Summary::default();
+    let author = shared::current_user();
+    for row in rows {
+        let field = |column: Option<usize>| column.and_then(|i| row.get(i)).map(|value| value.trim()).unwrap_or("");
+        let mut text = field(Some(text_column)).to_string();
+        if text.is_empty() {
+            summary.skipped += 1;
+            continue;
+        }
+        let link = field(link_column);
+        if !link.is_empty() && !text.contains(link) {
+            text = format!("{}\n\n{}", text, link);
+        }
+        let platforms = match platform(field(platform_column)) {
+            Some(platform) => vec![platform.to_string()],
+            None => default_platforms.to_vec(),
+        };
+        let time = parse_time(field(time_column));
+        let status = field(status_column).to_lowercase();
+        let waiting = ["scheduled", "pending", "queued", "draft", "buffer"]
+            .iter()
+            .any(|word| status.contains(word))
+            || time.is_some_and(|time| time > Utc::now());
+
+        match time {
+            Some(time) if waiting && time > Utc::now() => {
+                scheduler::schedule(
+                    &text,
+                    &platforms,
+                    time,
+                    &history::new_idempotency_key(),
+                    None,
+                    None,
+                    &PostOptions::default(),
+                );
+                summary.scheduled += 1;
+            }
+            Some(time) if !waiting && seen.insert((text.clone(), time)) => {
+                entries.push(HistoryEntry {
+                    id: format!("import-{}", history::new_idempotency_key()),
+                    text,
+                    posted_at: time,
+                    results: platforms
+                        .iter()
+                        .map(|platform| PlatformResult {
+                            platform: platform.clone(),
+                            success: true,
+                            post_id: None,
+                            url: None,
+                            error: None,
+                            thread_ids: Vec::new(),
+                        })
+                        .collect(),
+                    idempotency_key: None,
+                    author: Some(author.clone()),
+                    images: Vec::new(),
+                    expires_at: None,
+                    expired: false,
+                });
+                summary.history += 1;
+            }
+            _ => {
+                warn!(status = %status, "Skipped an imported post without a usable time");
+                summary.skipped += 1;
+            }
+        }
+    }
+
+    entries.sort_by_key(|entry| entry.posted_at);
+    history::save_history(&entries);
+    info!(
+        scheduled = summary.scheduled,
+        history = summary.history,
+        skipped = summary.skipped,
+        "Imported posts"
+    );
+    audit::record(
+        audit::Action::Import,
+        None,
+        &format!(
+            "Imported {} scheduled and {} past posts from CSV",
+            summary.scheduled, summary.history
+        ),
+    );
+    Ok(summary)
+}
+
+/// Our platform for a network named in an export ("Twitter", "X", "LinkedIn Company", "mastodon").
+fn platform(network: &str) -> Option<&'static str> {
+    let network = network.to_lowercase();
+    let word = |name: &str| network.split(|c: char| !c.is_alphanumeric()).any(|word| word == name);
+    if network.contains("twitter") || word("x") {
+        Some("Twitter")
+    } else if network.contains("bluesky") {
+        Some("Bluesky")
+    } else if network.contains("mastodon") {
+        Some("Mastodon")
+    } else if network.contains("linkedin") {
+        Some("LinkedIn")
+    } else {
+        None
+    }
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    if value.is_empty() {
+        return None;
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(time.with_timezone(&Utc));
+    }
+    // Buffer sometimes writes the zone out ("2024-05-01 09:30:00 UTC")
+    let (value, utc) = match value.strip_suffix(" UTC").or_else(|| value.strip_suffix(" GMT")) {
+        Some(value) => (value, true),
+        None => (value, false),
+    };
+    let naive = TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())?;
+    if utc {
+        Some(naive.and_utc())
+    } else {
+        timezone::from_wall_clock(naive).ok()
+    }
+}
+
+/// Splits CSV text into rows of fields.  Quoted fields may hold commas, line breaks and doubled quotes.
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                if row.iter().any(|field| !field.is_empty()) {
+                    rows.push(std::mem::take(&mut row));
+                }
+            }
+            c => field.push(c),
+        }
+    }
+    row.push(field);
+    if row.iter().any(|field| !field.is_empty()) {
+        rows.push(row);
+    }
+    rows
+}
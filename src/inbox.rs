@@ -0,0 +1,107 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::posts::{self, Visibility};
+
+const INBOX_FILE: &str = "inbox.json";
+
+/// Most notifications kept; older ones are dropped as new ones arrive.
+const MAX_NOTIFICATIONS: usize = 200;
+
+/// A mention, boost, favourite or follow received on one of the accounts.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Notification {
+    /// Id given by the platform, unique per platform.
+    pub id: String,
+    /// The account it was received on, e.g. "Mastodon" or "Mastodon@hachyderm.io".
+    pub platform: String,
+    /// What happened, as the platform names it, e.g. "mention" or "reblog".
+    pub kind: String,
+    /// Handle of the account that caused it.
+    pub from: String,
+    /// Plain text of the status involved, if any.
+    #[serde(default)]
+    pub text: String,
+    /// Web address of the status involved, if any.
+    pub url: Option<String>,
+    /// Id of the status involved, which a reply answers.
+    #[serde(default)]
+    pub status_id: Option<String>,
+    /// Who could see the status; a reply is kept as private.
+    #[serde(default)]
+    pub visibility: Visibility,
+    pub received_at: DateTime<Utc>,
+    /// Seen in the inbox.
+    #[serde(default)]
+    pub read: bool,
+}
+
+impl Notification {
+    /// Icon for the kind of notification.
+    pub fn icon(&self) -> &'static str {
+        match self.kind.as_str() {
+            "mention" => "💬",
+            "reblog" => "🔁",
+            "favourite" => "⭐",
+            "follow" | "follow_request" => "👤",
+            "poll" => "📊",
+            _ => "🔔",
+        }
+    }
+
+    /// Whether it can be answered from the inbox: a mention, with the status it was made in.
+    pub fn can_reply(&self) -> bool {
+        self.kind == "mention" && self.status_id.is_some()
+    }
+}
+
+/// How many notifications haven't been seen yet.
+pub fn unread(notifications: &[Notification]) -> usize {
+    notifications.iter().filter(|notification| !notification.read).count()
+}
+
+/// Marks every notification as seen, saving the inbox if any wasn't.
+pub fn mark_read(notifications: &mut [Notification]) {
+    if unread(notifications) == 0 {
+        return;
+    }
+    for notification in notifications.iter_mut() {
+        notification.read = true;
+    }
+    save_inbox(notifications);
+}
+
+/// Notifications, newest first.
+pub fn load_inbox() -> Vec<Notification> {
+    if Path::new(INBOX_FILE).exists() {
+        let json = fs::read_to_string(INBOX_FILE).expect("Failed to read inbox file");
+        serde_json::from_str(&json).unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+pub fn save_inbox(notifications: &[Notification]) {
+    let json = serde_json::to_string_pretty(notifications).expect("Failed to serialize inbox");
+    fs::write(INBOX_FILE, json).expect("Failed to write inbox file");
+}
+
+/// Puts a notification at the top of the inbox and saves it, unless it's already there (e.g. received again after
+/// reconnecting).
+pub async fn add(state: &Arc<Mutex<posts::AppState>>, notification: Notification) {
+    let mut state = state.lock().await;
+    if state
+        .inbox
+        .iter()
+        .any(|n| n.platform == notification.platform && n.id == notification.id)
+    {
+        return;
+    }
+    state.inbox.insert(0, notification);
+    state.inbox.truncate(MAX_NOTIFICATIONS);
+    save_inbox(&state.inbox);
+}
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+/// Smallest and largest share of the window the instructions and results panel may take.
+pub const MIN_SHARE: f32 = 0.15;
+pub const MAX_SHARE: f32 = 0.6;
+
+/// Where the instructions and results panel goes, chosen in settings.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum PanelPosition {
+    #[default]
+    Right,
+    Left,
+    Bottom,
+    Hidden,
+}
+
+impl PanelPosition {
+    pub const ALL: [PanelPosition; 4] = [
+        PanelPosition::Right,
+        PanelPosition::Left,
+        PanelPosition::Bottom,
+        PanelPosition::Hidden,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PanelPosition::Right => "Right",
+            PanelPosition::Left => "Left",
+            PanelPosition::Bottom => "Bottom",
+            PanelPosition::Hidden => "Hidden",
+        }
+    }
+
+    /// Whether the panel takes width from the composer rather than height.
+    pub fn is_beside(self) -> bool {
+        matches!(self, PanelPosition::Right | PanelPosition::Left)
+    }
+}
@@ -0,0 +1,253 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[cfg(feature = "mastodon")]
+use crate::mastodon;
+use crate::settings;
+#[cfg(feature = "twitter")]
+use crate::twitter;
+
+/// Posting limits of one platform, either built in or detected from the server.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PlatformLimits {
+    pub max_chars: usize,
+    pub max_media: Option<usize>,
+    pub max_image_bytes: Option<u64>,
+    pub max_video_bytes: Option<u64>,
+    /// Whether the values came from the server rather than the built-in defaults.
+    pub detected: bool,
+}
+
+/// Character limit of Twitter accounts with a paid subscription, which can post long-form tweets.
+pub const TWITTER_LONG_POST_MAX_CHARS: usize = 25_000;
+
+/// Built-in limits, used until (or unless) the server tells us otherwise.  Bluesky's 300 is fixed by the
+/// `app.bsky.feed.post` lexicon, so it is the same on every PDS and never detected.  Twitter's depends on the
+/// account's subscription, unless set in `settings.twitter_long_posts`.
+pub fn default_for(platform: &str) -> PlatformLimits {
+    let (max_chars, max_media) = match base_platform(platform) {
+        "Twitter" if settings::get().twitter_long_posts == Some(true) => (TWITTER_LONG_POST_MAX_CHARS, Some(4)),
+        "Twitter" => (280, Some(4)),
+        "Bluesky" => (300, Some(4)),
+        "Mastodon" if platform == "Mastodon" => (settings::get().mastodon_software.default_max_chars(), Some(4)),
+        "Mastodon" => (500, Some(4)),
+        "LinkedIn" => (3000, Some(9)),
+        "Medium" | "Hashnode" => (100_000, None),
+        "Email" => (100_000, None),
+        _ => (500, None),
+    };
+    PlatformLimits {
+        max_chars,
+        max_media,
+        // Bluesky's blob limit for images, which its PDS doesn't report
+        max_image_bytes: (platform == "Bluesky").then_some(1_000_000),
+        max_video_bytes: None,
+        detected: false,
+    }
+}
+
+/// The platform an account is on: additional Mastodon accounts ("Mastodon@hachyderm.io") behave like "Mastodon".
+pub fn base_platform(platform: &str) -> &str {
+    if platform.starts_with("Mastodon@") {
+        "Mastodon"
+    } else {
+        platform
+    }
+}
+
+/// Length of `text` as platforms count it for the character limit.
+pub fn count(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// What to do with text that is longer than a platform allows.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum OverLimit {
+    /// Split into a chain of replies, on platforms that support it (see `supports_threads`).
+    Thread,
+    /// Cut to fit, ending with an ellipsis and a link to the full post on another platform when there is one.
+    #[default]
+    Truncate,
+    /// Don't post to the platform at all.
+    Skip,
+}
+
+impl OverLimit {
+    pub const ALL: [OverLimit; 3] = [OverLimit::Thread, OverLimit::Truncate, OverLimit::Skip];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            OverLimit::Thread => "Thread",
+            OverLimit::Truncate => "Truncate + link",
+            OverLimit::Skip => "Skip",
+        }
+    }
+}
+
+/// Platforms where a post can be continued as a reply chain.
+pub fn supports_threads(platform: &str) -> bool {
+    matches!(base_platform(platform), "Twitter" | "Bluesky" | "Mastodon")
+}
+
+/// How the posts of a thread are numbered, so readers know more follows.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum ThreadNumbering {
+    /// No counters.
+    Off,
+    /// " (1/5)" after the text.
+    #[default]
+    Parentheses,
+    /// " 1/5" after the text.
+    Slash,
+    /// " [1/5]" after the text.
+    Brackets,
+    /// " 🧵 1/5" after the text.
+    Emoji,
+    /// "1/5 " before the text.
+    Prefix,
+}
+
+impl ThreadNumbering {
+    pub const ALL: [ThreadNumbering; 6] = [
+        ThreadNumbering::Off,
+        ThreadNumbering::Parentheses,
+        ThreadNumbering::Slash,
+        ThreadNumbering::Brackets,
+        ThreadNumbering::Emoji,
+        ThreadNumbering::Prefix,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ThreadNumbering::Off => "No numbers",
+            ThreadNumbering::Parentheses => "Text (1/5)",
+            ThreadNumbering::Slash => "Text 1/5",
+            ThreadNumbering::Brackets => "Text [1/5]",
+            ThreadNumbering::Emoji => "Text 🧵 1/5",
+            ThreadNumbering::Prefix => "1/5 Text",
+        }
+    }
+
+    /// `text` as post `number` (counting from 1) of a thread of `total`.
+    pub fn apply(self, text: &str, number: usize, total: usize) -> String {
+        match self {
+            ThreadNumbering::Off => text.to_string(),
+            ThreadNumbering::Parentheses => format!("{} ({}/{})", text, number, total),
+            ThreadNumbering::Slash => format!("{} {}/{}", text, number, total),
+            ThreadNumbering::Brackets => format!("{} [{}/{}]", text, number, total),
+            ThreadNumbering::Emoji => format!("{} 🧵 {}/{}", text, number, total),
+            ThreadNumbering::Prefix => format!("{}/{} {}", number, total, text),
+        }
+    }
+
+    /// Characters a counter adds to a post; assumes fewer than 100 posts.
+    pub fn room(self) -> usize {
+        count(&self.apply("", 99, 99))
+    }
+}
+
+/// The numbering style chosen in the settings.
+pub fn thread_numbering() -> ThreadNumbering {
+    settings::get().thread_numbering
+}
+
+/// Splits `text` into parts of at most `max_chars`, breaking between words where possible.  Numbering the parts is
+/// up to the caller, who leaves room for the counters in `max_chars`.
+pub fn split_thread(text: &str, max_chars: usize) -> Vec<String> {
+    let budget = max_chars.max(1);
+
+    let mut parts: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        let word_len = count(word);
+        let current_len = count(&current);
+        if current_len > 0 && current_len + 1 + word_len <= budget {
+            current.push(' ');
+            current.push_str(word);
+            continue;
+        }
+        if current_len > 0 {
+            parts.push(std::mem::take(&mut current));
+        }
+        // Words longer than a whole part (long URLs, mostly) are broken wherever they have to be
+        let chars: Vec<char> = word.chars().collect();
+        let mut chunks = chars.chunks(budget).peekable();
+        while let Some(chunk) = chunks.next() {
+            let chunk: String = chunk.iter().collect();
+            if chunks.peek().is_some() {
+                parts.push(chunk);
+            } else {
+                current = chunk;
+            }
+        }
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+/// Shortens `text` to at most `max_chars`, ending it with an ellipsis and, when given, a link to the full post.
+pub fn truncate(text: &str, max_chars: usize, link: Option<&str>) -> String {
+    if count(text) <= max_chars {
+        return text.to_string();
+    }
+    let suffix = match link {
+        Some(link) if count(link) + 2 < max_chars => format!("… {}", link),
+        _ => "…".to_string(),
+    };
+    let keep = max_chars.saturating_sub(count(&suffix));
+    let kept: String = text.chars().take(keep).collect();
+    format!("{}{}", kept.trim_end(), suffix)
+}
+
+/// The user's choice for text that is too long for `platform`.
+pub fn over_limit_choice(platform: &str) -> OverLimit {
+    settings::get().over_limit.get(platform).copied().unwrap_or_default()
+}
+
+/// Asks each configured server for its limits.  Platforms whose server can't be reached, or that don't publish
+/// limits, are missing from the result.
+pub async fn detect() -> HashMap<String, PlatformLimits> {
+    #[cfg_attr(not(any(feature = "mastodon", feature = "twitter")), allow(unused_mut))]
+    let mut detected = HashMap::new();
+
+    #[cfg(feature = "mastodon")]
+    if let Some(limits) = mastodon::fetch_instance_limits(&mastodon::api_base_url(), mastodon::software()).await {
+        tracing::info!(
+            platform = "mastodon",
+            max_chars = limits.max_chars,
+            "Detected instance limits"
+        );
+        detected.insert("Mastodon".to_string(), limits);
+    }
+    #[cfg(feature = "mastodon")]
+    for account in mastodon::load_accounts() {
+        if let Some(limits) = mastodon::fetch_instance_limits(&account.base_url(), account.software).await {
+            tracing::info!(
+                platform = "mastodon",
+                instance = %account.instance,
+                max_chars = limits.max_chars,
+                "Detected instance limits"
+            );
+            detected.insert(account.platform(), limits);
+        }
+    }
+
+    // Only asked when not set by hand, since it needs the user's token
+    #[cfg(feature = "twitter")]
+    if settings::get().twitter_long_posts.is_none() {
+        if let Some(token) = twitter::load_bearer_token() {
+            if let Some(limits) = twitter::fetch_account_limits(&token).await {
+                tracing::info!(
+                    platform = "twitter",
+                    max_chars = limits.max_chars,
+                    "Detected account limits"
+                );
+                detected.insert("Twitter".to_string(), limits);
+            }
+        }
+    }
+
+    detected
+}
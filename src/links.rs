@@ -0,0 +1,104 @@
+use std::ops::Range;
+use std::time::Duration;
+use tracing::debug;
+
+use crate::html::decode_entities;
+use crate::http;
+
+/// How long to wait for a page when building its preview card; posting shouldn't stall on a slow site.
+const PREVIEW_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Title and description of a linked page, for a preview card.
+pub struct LinkPreview {
+    pub url: String,
+    pub title: String,
+    pub description: String,
+}
+
+/// The http(s) URLs in `text`, with their UTF-8 byte ranges (what Bluesky facets index by).  Trailing sentence
+/// punctuation isn't part of a URL, and neither is a closing parenthesis unless the URL opened one, as in
+/// `(see https://en.wikipedia.org/wiki/Rust_(programming_language))`.
+pub fn find_urls(text: &str) -> Vec<(Range<usize>, String)> {
+    let mut urls = Vec::new();
+    let mut offset = 0;
+    for word in text.split_inclusive(char::is_whitespace) {
+        let trimmed = word.trim_end();
+        let start = trimmed.find("https://").or_else(|| trimmed.find("http://"));
+        if let Some(start) = start {
+            let url = trim_url(&trimmed[start..]);
+            let scheme_len = if url.starts_with("https://") {
+                "https://".len()
+            } else {
+                "http://".len()
+            };
+            if url.len() > scheme_len {
+                urls.push((offset + start..offset + start + url.len(), url.to_string()));
+            }
+        }
+        offset += word.len();
+    }
+    urls
+}
+
+fn trim_url(mut url: &str) -> &str {
+    loop {
+        let unbalanced_paren = url.ends_with(')') && url.matches(')').count() > url.matches('(').count();
+        let punctuation = url.ends_with(['.', ',', ';', ':', '!', '?', '"', '\'', ']', '>', '…', '”', '’', '»']);
+        if !unbalanced_paren && !punctuation {
+            return url;
+        }
+        url = &url[..url.len() - url.chars().next_back().map_or(0, char::len_utf8)];
+    }
+}
+
+/// Fetches `url` and reads its Open Graph (or plain HTML) title and description.  `None` when the page can't be
+/// fetched or has no title.
+pub async fn fetch_preview(url: &str) -> Option<LinkPreview> {
+    let response = http::send_idempotent(http::client().get(url).timeout(PREVIEW_TIMEOUT))
+        .await
+        .ok()?;
+    if !response.status().is_success() {
+        debug!(url, status = response.status().as_u16(), "No preview for link");
+        return None;
+    }
+    let html = response.text().await.ok()?;
+
+    let title = meta_content(&html, "og:title").or_else(|| title_tag(&html))?;
+    let description = meta_content(&html, "og:description")
+        .or_else(|| meta_content(&html, "description"))
+        .unwrap_or_default();
+    Some(LinkPreview {
+        url: url.to_string(),
+        title,
+        description,
+    })
+}
+
+/// Value of `<meta property="name" content="...">` (or `name="name"`), whichever attribute order the page uses.
+fn meta_content(html: &str, name: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let mut search_from = 0;
+    while let Some(found) = lower[search_from..].find("<meta") {
+        let start = search_from + found;
+        let end = start + lower[start..].find('>')?;
+        let tag = &html[start..end];
+        let tag_lower = &lower[start..end];
+        let names = [format!("property=\"{}\"", name), format!("name=\"{}\"", name)];
+        if names.iter().any(|attribute| tag_lower.contains(attribute.as_str())) {
+            let value_start = tag_lower.find("content=\"")? + "content=\"".len();
+            let value_end = value_start + tag[value_start..].find('"')?;
+            return Some(decode_entities(tag[value_start..value_end].trim()));
+        }
+        search_from = end;
+    }
+    None
+}
+
+fn title_tag(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let start = lower.find("<title")?;
+    let start = start + lower[start..].find('>')? + 1;
+    let end = start + lower[start..].find("</title>")?;
+    let title = decode_entities(html[start..end].trim());
+    (!title.is_empty()).then_some(title)
+}
@@ -0,0 +1,229 @@
+use std::collections::HashSet;
+
+use crate::autocomplete;
+use crate::entities;
+use crate::limits::{self, PlatformLimits};
+use crate::links;
+use crate::posts::PostOptions;
+use crate::publish;
+use crate::rules::{ContentRules, Enforcement};
+
+/// Fewest words besides links for a post to say something about the link it shares.
+const MIN_WORDS_AROUND_LINK: usize = 3;
+
+/// Something worth a second look before posting.  Only broken content rules set to block stop the post.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Warning {
+    /// The account it applies to, or `None` when it applies everywhere.
+    pub platform: Option<String>,
+    pub message: String,
+    /// Whether the post can't go out until it's fixed, rather than being posted anyway.
+    pub blocking: bool,
+}
+
+impl Warning {
+    fn everywhere(message: String) -> Self {
+        Self {
+            platform: None,
+            message,
+            blocking: false,
+        }
+    }
+
+    fn on(platform: &str, message: String) -> Self {
+        Self {
+            platform: Some(platform.to_string()),
+            message,
+            blocking: false,
+        }
+    }
+}
+
+/// The checks that need nothing but the post: the profile's content `rules`, images without alt text, text over
+/// each platform's limit, links posted without a word about them and hashtags in capitals.  `limits` gives the
+/// limits of an account.
+pub fn check(
+    text: &str,
+    options: &PostOptions,
+    platforms: &[String],
+    limits: impl Fn(&str) -> PlatformLimits,
+    rules: &ContentRules,
+) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+
+    // Each platform gets its own text, but a rule broken everywhere is listed once
+    let broken: Vec<Vec<String>> = platforms
+        .iter()
+        .map(|platform| rules.violations(&post_text(platform, text, options)))
+        .collect();
+    let blocking = rules.enforcement == Enforcement::Block;
+    for (index, (platform, violations)) in platforms.iter().zip(&broken).enumerate() {
+        let everywhere = |message: &String| broken.iter().all(|other| other.contains(message));
+        for message in violations {
+            if !everywhere(message) {
+                warnings.push(Warning {
+                    blocking,
+                    ..Warning::on(platform, message.clone())
+                });
+            } else if index == 0 {
+                warnings.push(Warning {
+                    blocking,
+                    ..Warning::everywhere(message.clone())
+                });
+            }
+        }
+    }
+
+    let images = options
+        .images
+        .iter()
+        .chain(options.thread.iter().flat_map(|segment| &segment.images));
+    for image in images.filter(|image| image.alt.trim().is_empty()) {
+        warnings.push(Warning::everywhere(format!(
+            "{} has no alt text, so screen reader users won't know what it shows",
+            image.path
+        )));
+    }
+
+    for platform in platforms {
+        let max_chars = limits(platform).max_chars;
+        for (index, part) in publish::thread_parts(platform, text, options).iter().enumerate() {
+            let length = limits::count(&part.text);
+            if length > max_chars {
+                let which = if index == 0 {
+                    "The post".to_string()
+                } else {
+                    format!("Post {} of the thread", index + 1)
+                };
+                warnings.push(Warning::on(
+                    platform,
+                    format!(
+                        "{} is {} characters, over {}'s limit of {} ({})",
+                        which,
+                        length,
+                        platform,
+                        max_chars,
+                        limits::over_limit_choice(platform).label().to_lowercase()
+                    ),
+                ));
+            }
+        }
+    }
+
+    let texts: Vec<&str> = std::iter::once(text)
+        .chain(options.thread.iter().map(|segment| segment.text.as_str()))
+        .collect();
+    for part in &texts {
+        if let Some(url) = bare_url(part) {
+            warnings.push(Warning::everywhere(format!(
+                "{} is shared without a word about it; say why it's worth opening",
+                url
+            )));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    for (_, tag) in texts.iter().flat_map(|part| entities::hashtags(part)) {
+        // Short acronyms like #AI are read the same either way
+        let letters = tag.chars().filter(|c| c.is_alphabetic()).count();
+        let shouting = letters > 3 && !tag.chars().any(char::is_lowercase);
+        if shouting && seen.insert(tag) {
+            warnings.push(Warning::everywhere(format!(
+                "#{} is in capitals, which screen readers spell out letter by letter; try #{}",
+                tag,
+                camel_case(tag)
+            )));
+        }
+    }
+
+    warnings
+}
+
+/// Everything `platform` gets of the post, all posts of a thread together.
+fn post_text(platform: &str, text: &str, options: &PostOptions) -> String {
+    publish::thread_parts(platform, text, options)
+        .into_iter()
+        .map(|part| part.text)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The first link in `text` when the rest of it is too short to say anything about the links.
+fn bare_url(text: &str) -> Option<String> {
+    let urls = links::find_urls(text);
+    let (_, first) = urls.first()?;
+    let mut rest = text.to_string();
+    for (range, _) in urls.iter().rev() {
+        rest.replace_range(range.clone(), " ");
+    }
+    let words = rest
+        .split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .count();
+    (words < MIN_WORDS_AROUND_LINK).then(|| first.clone())
+}
+
+/// `tag` with only the first letter of each `_`-separated word in capitals, like `RustLang` for `RUST_LANG`.
+fn camel_case(tag: &str) -> String {
+    tag.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect()
+            })
+        })
+        .collect()
+}
+
+/// Mentions in the post that aren't accounts on the platforms they're sent to, looked up the way the composer
+/// suggests handles.  Only `platforms` that can look handles up are checked, so pass the authorized ones.
+pub async fn unknown_mentions(
+    text: &str,
+    options: &PostOptions,
+    platforms: &[String],
+    bluesky_token: Option<&str>,
+) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    for platform in platforms {
+        let base = limits::base_platform(platform);
+        if !matches!(base, "Twitter" | "Bluesky" | "Mastodon") {
+            continue;
+        }
+        let mut checked = HashSet::new();
+        for part in publish::thread_parts(platform, text, options) {
+            for range in entities::mentions(&part.text) {
+                let handle = part.text[range.start + 1..range.end].to_lowercase();
+                if !checked.insert(handle.clone()) {
+                    continue;
+                }
+                if !mention_exists(platform, base, &handle, bluesky_token).await {
+                    warnings.push(Warning::on(
+                        platform,
+                        format!(
+                            "@{} couldn't be found on {}, so it won't reach anyone there",
+                            handle, platform
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+    warnings
+}
+
+/// Whether `handle` (without `@`) is an account `platform` would link.  Handles from other networks never are:
+/// `user@instance` outside Mastodon, or a Bluesky handle without a domain.
+async fn mention_exists(platform: &str, base: &str, handle: &str, bluesky_token: Option<&str>) -> bool {
+    let is_fediverse = handle.contains('@');
+    match base {
+        "Twitter" if is_fediverse => return false,
+        "Bluesky" if is_fediverse || !handle.contains('.') => return false,
+        _ => {}
+    }
+    let suggestions = autocomplete::mentions(handle, &[platform.to_string()], bluesky_token).await;
+    suggestions.iter().any(|suggestion| {
+        let found = suggestion.handle.trim_start_matches('@').to_lowercase();
+        // A local Mastodon account is found with its instance's domain added
+        found == handle || (base == "Mastodon" && !is_fediverse && found.split('@').next() == Some(handle))
+    })
+}
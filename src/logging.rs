@@ -0,0 +1,118 @@
+use chrono::{DateTime, Local};
+use std::collections::VecDeque;
+use std::fs::OpenOptions;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, EnvFilter};
+
+const DEFAULT_FILTER: &str = "warn,multique=info";
+const BUFFER_CAPACITY: usize = 500;
+
+/// A log event as shown in the in-app log panel.
+#[derive(Clone)]
+pub struct LogRecord {
+    pub time: DateTime<Local>,
+    pub level: Level,
+    pub message: String,
+}
+
+/// Ring buffer of the most recent log events, shared between the tracing layer and the UI.
+#[derive(Clone, Default)]
+pub struct LogBuffer(Arc<Mutex<VecDeque<LogRecord>>>);
+
+impl LogBuffer {
+    fn push(&self, record: LogRecord) {
+        let mut records = self.0.lock().unwrap();
+        if records.len() == BUFFER_CAPACITY {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    pub fn snapshot(&self) -> Vec<LogRecord> {
+        self.0.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        self.0.lock().unwrap().clear();
+    }
+}
+
+/// Tracing layer that copies every event into a `LogBuffer`.
+struct BufferLayer {
+    buffer: LogBuffer,
+}
+
+impl<S: Subscriber> Layer<S> for BufferLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        let mut message = visitor.message;
+        if !visitor.fields.is_empty() {
+            message = format!("{} {}", message, visitor.fields.join(" "));
+        }
+
+        self.buffer.push(LogRecord {
+            time: Local::now(),
+            level: *event.metadata().level(),
+            message,
+        });
+    }
+}
+
+/// Flattens an event into its message plus `key=value` pairs for the remaining fields.
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: Vec<String>,
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields.push(format!("{}={}", field.name(), value));
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.fields.push(format!("{}={:?}", field.name(), value));
+        }
+    }
+}
+
+/// Installs the global tracing subscriber and returns the buffer backing the in-app log panel.
+///
+/// The filter is read from `MULTIQUE_LOG` using `RUST_LOG` syntax (e.g. `debug` or `multique=trace`) and defaults to
+/// info-level events from this crate only.  When `MULTIQUE_LOG_FILE` is set, events are also appended to that file.
+pub fn init() -> LogBuffer {
+    let filter = EnvFilter::try_from_env("MULTIQUE_LOG").unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+    let buffer = LogBuffer::default();
+
+    let file_layer = std::env::var("MULTIQUE_LOG_FILE").ok().and_then(|path| {
+        match OpenOptions::new().create(true).append(true).open(&path) {
+            Ok(file) => Some(fmt::layer().with_ansi(false).with_writer(Mutex::new(file))),
+            Err(err) => {
+                eprintln!("Failed to open log file {}: {:?}", path, err);
+                None
+            }
+        }
+    });
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(std::io::stderr)) // stderr, so CLI output on stdout stays parseable
+        .with(file_layer)
+        .with(BufferLayer { buffer: buffer.clone() })
+        .init();
+
+    buffer
+}
@@ -0,0 +1,77 @@
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+
+use crate::settings::Settings;
+
+/// Port of SMTP with TLS from the start; other ports upgrade with STARTTLS.
+const SMTPS_PORT: u16 = 465;
+
+/// How the Email platform delivers posts.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum Service {
+    /// To `newsletter_to` through the SMTP server in settings.
+    #[default]
+    Smtp,
+    /// As a Buttondown newsletter to its subscribers.
+    Buttondown,
+    /// To `newsletter_to` (e.g. a mailing list) through Mailgun's API.
+    Mailgun,
+}
+
+impl Service {
+    pub const ALL: [Service; 3] = [Service::Smtp, Service::Buttondown, Service::Mailgun];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Service::Smtp => "SMTP",
+            Service::Buttondown => "Buttondown",
+            Service::Mailgun => "Mailgun",
+        }
+    }
+}
+
+/// Sends a plain-text email through the SMTP server in settings.  `from` falls back to `smtp_username` when empty,
+/// and `to` may list several addresses, comma separated.  Returns the server's reply.
+pub async fn send(settings: &Settings, from: &str, to: &str, subject: &str, body: String) -> Result<String, String> {
+    let from = match from.trim() {
+        "" => settings.smtp_username.trim(),
+        from => from,
+    };
+    let mut message = Message::builder().from(parse_address(from, "Sender")?);
+    for address in to.split(',').map(str::trim).filter(|address| !address.is_empty()) {
+        message = message.to(parse_address(address, "Recipient")?);
+    }
+    let message = message
+        .subject(subject)
+        .header(ContentType::TEXT_PLAIN)
+        .body(body)
+        .map_err(|err| err.to_string())?;
+
+    let host = settings.smtp_host.trim();
+    let builder = if settings.smtp_port == SMTPS_PORT {
+        AsyncSmtpTransport::<Tokio1Executor>::relay(host)
+    } else {
+        AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+    }
+    .map_err(|err| err.to_string())?
+    .port(settings.smtp_port);
+    let builder = match settings.smtp_username.trim() {
+        "" => builder,
+        username => builder.credentials(Credentials::new(username.to_string(), settings.smtp_password.clone())),
+    };
+    builder
+        .build()
+        .send(message)
+        .await
+        .map(|response| response.message().map(String::as_str).collect::<Vec<_>>().join(" "))
+        .map_err(|err| err.to_string())
+}
+
+fn parse_address(address: &str, role: &str) -> Result<Mailbox, String> {
+    address
+        .parse()
+        .map_err(|err| format!("{} \"{}\": {}", role, address, err))
+}
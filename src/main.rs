@@ -1,195 +1,914 @@
+use clap::Parser;
 use dotenv::dotenv;
 use eframe::egui;
 use std::collections::HashMap;
+use std::process::ExitCode;
 use std::sync::Arc;
 use tokio::runtime::Runtime;
 use tokio::sync::Mutex;
+use tracing::{error, info, warn};
 
+mod activitypub;
+mod alerts;
+mod api;
+mod archive;
+mod audit;
+mod autocomplete;
+mod backup;
+#[cfg(feature = "bluesky")]
 mod bluesky;
+mod budget;
+mod cli;
+mod config_bundle;
+mod connectivity;
+mod cron;
+mod crypto;
+mod daemon;
+#[cfg(feature = "demo")]
+mod demo;
+mod drafts;
+#[cfg(feature = "email")]
+mod email;
+mod engagement;
+mod entities;
+mod expiry;
+mod feeds;
+mod github;
+#[cfg(feature = "hashnode")]
+mod hashnode;
+mod history;
+mod html;
+mod http;
+mod import;
+mod inbox;
+mod layout;
+mod limits;
+#[cfg(feature = "linkedin")]
 mod linkedin;
+mod links;
+mod lint;
+mod logging;
+mod mail;
+mod markdown;
+#[cfg(feature = "mastodon")]
 mod mastodon;
+mod media;
+#[cfg(feature = "medium")]
+mod medium;
+#[cfg(feature = "mqtt")]
+mod mqtt;
+mod outbox;
+#[cfg(feature = "plugins")]
+mod plugins;
 mod posts;
+mod presets;
+mod profanity;
+mod profiles;
+mod publish;
+mod queues;
+mod ratelimit;
+mod recovery;
+mod report;
+mod rules;
+mod scheduler;
+#[cfg(feature = "scripting")]
+mod scripting;
+mod settings;
+mod shared;
+mod stats;
+#[cfg(feature = "mastodon")]
+mod streaming;
+mod sync;
+mod theme;
+mod threads;
+mod timezone;
+mod tokens;
+mod transforms;
+#[cfg(feature = "twitter")]
 mod twitter;
+mod webhooks;
+
+/// How often the clipboard is checked for a URL while the composer is empty
+const CLIPBOARD_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Pause in typing after which the mention being typed is looked up
+const MENTION_LOOKUP_DELAY: std::time::Duration = std::time::Duration::from_millis(400);
+
+/// Days a post is kept when "Delete after" is first ticked
+const DEFAULT_EXPIRY_DAYS: u32 = 7;
+
+/// How long the trending hashtags fetched from Mastodon are offered before they're fetched again
+#[cfg(feature = "mastodon")]
+const TRENDING_TAGS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
 
 struct PostApp {
     state: Arc<Mutex<posts::AppState>>,
     rt: Arc<Runtime>,
     platform_checkboxes: HashMap<&'static str, bool>, // Added checkboxes state
+    log_buffer: logging::LogBuffer,
+    log_level: tracing::Level,          // Most verbose level shown in the log panel
+    settings_draft: settings::Settings, // Edited in the settings section until saved
+    last_autosave: std::time::Instant,
+    autosaved_text: String,                 // Composer text as of the last autosave
+    saved_selection: (String, Vec<String>), // Profile and checked platforms as last saved to its config
+    presets: Vec<presets::Preset>,          // Named platform selections applied in one click
+    new_preset_name: String,
+    editing_preset: Option<usize>, // Preset whose hashtags, signature and visibility are being edited
+    new_profile_name: String,
+    new_banned: String,      // Word or phrase being added to the profile's content rules
+    new_disclaimer: String,  // Disclaimer being added to the profile's content rules
+    config_path: String,     // File used by settings export/import
+    sync_passphrase: String, // Never saved; prefilled from SYNC_PASSPHRASE
+    sync_conflict: bool,     // The last sync found changes on both sides
+    shown_unread: usize,     // Unread notifications counted in the window title
+    applied_appearance: Option<(theme::Theme, bool)>, // Theme and reduced motion as last applied
+    backup_path: String,
+    backup_passphrase: String,
+    archive_dir: String, // Folder "Export archive" writes to
+    history_filter: history::HistoryFilter,
+    schedule_input: String, // When to schedule the composed post, in the configured time zone
+    schedule_repeat: Option<scheduler::Frequency>,
+    schedule_count: u32,    // Occurrences of a recurring post; 0 for no limit
+    schedule_until: String, // Optional end of a recurring post
+    selected_queue: Option<String>,
+    new_queue_name: String,
+    #[cfg(feature = "mastodon")]
+    new_mastodon_instance: String, // Instance of a Mastodon account being added
+    new_slot: (chrono::Weekday, String), // Day and HH:MM of a slot being added to a queue
+    new_image: media::ImageAttachment,   // Image being attached to the composed post
+    composers: Vec<Composer>,            // Extra composer windows
+    per_platform: bool,                  // The composer shows a tab per platform
+    platform_tab: Option<&'static str>,
+    compare: bool, // The composer shows two platforms' previews side by side
+    compared: [Option<&'static str>; 2],
+    clipboard_url: Option<String>, // A lone URL on the clipboard, offered as the start of a post
+    last_clipboard_check: std::time::Instant,
+    mention_query: String, // Mention being typed, looked up once typing pauses
+    mention_typed_at: std::time::Instant,
+    mention_searched: bool,
+    #[cfg(feature = "mastodon")]
+    trending_fetched_at: Option<std::time::Instant>,
+    feed_url: String, // RSS or Atom feed whose newest item can be imported into the composer
+    thread_new_images: Vec<media::ImageAttachment>, // Image being attached to each further post of a thread
+    drafts: Vec<drafts::Draft>, // Posts and threads saved to finish later
+    next_composer_id: u64,
+    report_period: report::Period,
+    inbox_reply: Option<QuickReply>,     // Reply being written to a mention in the inbox
+    schedule_edit: Option<ScheduleEdit>, // Scheduled post being edited in the schedule list
 }
 
-impl PostApp {
-    fn new() -> Self {
-        let state = Arc::new(Mutex::new(posts::AppState::default()));
-        let rt = Arc::new(Runtime::new().unwrap());
+/// A copy of a scheduled post being edited, saved back over it in the schedule
+struct ScheduleEdit {
+    post: scheduler::ScheduledPost,
+    time: String, // In the configured time zone, as the schedule field takes it
+    new_image: media::ImageAttachment,
+}
 
-        let platform_checkboxes = HashMap::from([
-            ("Twitter", true),
-            ("Bluesky", true),
-            ("Mastodon", true),
-            ("LinkedIn", false),
-        ]);
+/// A short reply to a notification in the inbox, sent straight back to the platform it came from
+struct QuickReply {
+    platform: String,
+    status_id: String,
+    visibility: posts::Visibility,
+    text: String,
+}
 
-        // Load Bluesky tokens and validate
-        if let Some(tokens) = bluesky::load_tokens() {
-            let mut state_guard = futures::executor::block_on(state.lock());
-            state_guard.bluesky_token = Some(tokens.access_jwt.clone());
-            state_guard.did = Some(tokens.did.clone());
-            state_guard.bluesky_authorized = true; // Assume authorized for now
-
-            // Validate and refresh token asynchronously
-            let state_clone = Arc::clone(&state);
-            rt.spawn(async move {
-                if let Some(new_tokens) = bluesky::refresh_access_token(&tokens.refresh_jwt).await {
-                    let mut state = state_clone.lock().await;
-                    state.bluesky_token = Some(new_tokens.access_jwt);
-                    state.did = Some(new_tokens.did);
-                    println!("Bluesky token refreshed successfully.");
-                } else {
-                    println!("Bluesky token refresh failed. Attempting reauthorization...");
-                    if let Some(new_tokens) = bluesky::reauthorize_bluesky().await {
-                        let mut state = state_clone.lock().await;
-                        state.bluesky_token = Some(new_tokens.access_jwt);
-                        state.did = Some(new_tokens.did);
-                        println!("Bluesky reauthorized successfully.");
-                    } else {
-                        let mut state = state_clone.lock().await;
-                        state.bluesky_authorized = false;
-                        println!("Failed to refresh or reauthorize Bluesky.");
-                    }
-                }
-            });
-        }
+/// Publishes a post from a composer to `platforms`, or holds it in the outbox when offline.  Returns whether it went
+/// out everywhere or was held, so the composer can be cleared.
+async fn send(
+    state: &Arc<Mutex<posts::AppState>>,
+    text: &str,
+    platforms: &[String],
+    idempotency_key: &str,
+    options: &posts::PostOptions,
+) -> bool {
+    let offline = state.lock().await.offline;
+    if offline {
+        let held = outbox::push(text, platforms, idempotency_key, options);
+        info!("Offline; post held in the outbox");
+        let mut state = state.lock().await;
+        state.outbox_count = held;
+        state.toast("Offline — post held in the outbox", true);
+        return true;
+    }
+    match publish::publish(
+        state,
+        text,
+        platforms,
+        idempotency_key,
+        options,
+        &shared::current_user(),
+    )
+    .await
+    {
+        Some(entry) => entry.results.iter().all(|r| r.success),
+        None => false,
+    }
+}
 
-        // Load tokens for other platforms
-        if twitter::load_bearer_token().is_some() {
-            let mut state_guard = futures::executor::block_on(state.lock());
-            state_guard.twitter_authorized = true;
+/// A composer in a window of its own, with its own text, platforms and attachments
+struct Composer {
+    id: u64,
+    text: String,
+    idempotency_key: String,
+    options: posts::PostOptions,
+    platform_checkboxes: HashMap<&'static str, bool>,
+    new_image: media::ImageAttachment,
+    posting: bool,
+    outcome: Arc<std::sync::Mutex<Option<bool>>>, // Set by the posting task: whether every platform succeeded
+    open: bool,
+}
+
+impl Composer {
+    fn new(id: u64) -> Self {
+        Self {
+            id,
+            text: String::new(),
+            idempotency_key: history::new_idempotency_key(),
+            options: posts::PostOptions::default(),
+            platform_checkboxes: default_platform_checkboxes(),
+            new_image: media::ImageAttachment {
+                path: String::new(),
+                alt: String::new(),
+            },
+            posting: false,
+            outcome: Arc::new(std::sync::Mutex::new(None)),
+            open: true,
         }
+    }
 
-        if linkedin::load_bearer_token().is_some() {
-            let mut state_guard = futures::executor::block_on(state.lock());
-            state_guard.linkedin_authorized = true;
+    /// Picks up the result of a post in flight, clearing the composer when every platform succeeded
+    fn poll(&mut self) {
+        let Some(all_succeeded) = self.outcome.lock().unwrap().take() else {
+            return;
+        };
+        self.posting = false;
+        if all_succeeded {
+            self.text.clear();
+            self.idempotency_key = history::new_idempotency_key();
+            self.options = posts::PostOptions::default();
         }
+    }
+
+    fn post(&mut self, app_state: &Arc<Mutex<posts::AppState>>, rt: &Runtime) {
+        self.posting = true;
+        let text = self.text.clone();
+        let idempotency_key = self.idempotency_key.clone();
+        let options = self.options.clone();
+        let platforms: Vec<String> = self
+            .platform_checkboxes
+            .iter()
+            .filter(|(_, checked)| **checked)
+            .map(|(platform, _)| platform.to_string())
+            .collect();
+        let task_state = Arc::clone(app_state);
+        let outcome = Arc::clone(&self.outcome);
+
+        rt.spawn(async move {
+            let all_succeeded = send(&task_state, &text, &platforms, &idempotency_key, &options).await;
+            *outcome.lock().unwrap() = Some(all_succeeded);
+        });
+    }
+
+    /// Draws the composer's window; closing it sets `open` to false
+    fn show(&mut self, ctx: &egui::Context, app_state: &Arc<Mutex<posts::AppState>>, rt: &Runtime) {
+        self.poll();
+        let viewport_id = egui::ViewportId::from_hash_of(("composer", self.id));
+        let builder = egui::ViewportBuilder::default()
+            .with_title(format!("Compose #{}", self.id))
+            .with_inner_size([480.0, 400.0]);
+
+        ctx.show_viewport_immediate(viewport_id, builder, |ctx, _class| {
+            if ctx.input(|i| i.viewport().close_requested()) {
+                self.open = false;
+            }
+            egui::CentralPanel::default().show(ctx, |ui| {
+                let mut platforms: Vec<&'static str> = {
+                    let state = futures::executor::block_on(app_state.lock());
+                    self.platform_checkboxes
+                        .keys()
+                        .copied()
+                        .filter(|platform| is_authorized(&state, platform))
+                        .collect()
+                };
+                platforms.sort();
+                ui.horizontal_wrapped(|ui| {
+                    ui.label("To:");
+                    for platform in platforms {
+                        if let Some(checked) = self.platform_checkboxes.get_mut(platform) {
+                            ui.checkbox(checked, platform);
+                        }
+                    }
+                });
+
+                let text = ui.add_enabled(
+                    !self.posting,
+                    egui::TextEdit::multiline(&mut self.text)
+                        .desired_width(f32::INFINITY)
+                        .layouter(&mut highlight_entities),
+                );
+                accessible(text, "Post text");
+                render_attachments(ui, &mut self.options.images, &mut self.new_image);
+                ui.checkbox(&mut self.options.link_previews, "🔗 Link preview card");
+
+                ui.horizontal(|ui| {
+                    let any_platform = self.platform_checkboxes.values().any(|checked| *checked);
+                    if ui
+                        .add_enabled(
+                            !self.posting && any_platform && !self.text.trim().is_empty(),
+                            egui::Button::new("📤 Post").fill(egui::Color32::DARK_GRAY),
+                        )
+                        .clicked()
+                    {
+                        self.post(app_state, rt);
+                    }
+                    if self.posting {
+                        theme::busy(ui, settings::get().reduced_motion);
+                        ui.label("Posting…");
+                        ui.ctx().request_repaint_after(std::time::Duration::from_millis(100));
+                    }
+                });
+            });
+        });
+    }
+}
+
+impl PostApp {
+    fn new(log_buffer: logging::LogBuffer) -> Self {
+        let state = Arc::new(Mutex::new(posts::AppState {
+            history: history::load_history(),
+            inbox: inbox::load_inbox(),
+            outbox_count: outbox::load_outbox().len(),
+            schedule: scheduler::load_schedule(),
+            queues: queues::load_queues(),
+            schedule_pause: scheduler::load_pause(),
+            engagement_samples: engagement::load_samples(),
+            compose_key: history::new_idempotency_key(),
+            ..Default::default()
+        }));
 
-        if mastodon::load_tokens().is_some() {
+        // Restore text left unsent by a crash or by closing the app mid-compose
+        let autosaved_text = recovery::load().unwrap_or_default();
+        if !autosaved_text.is_empty() {
             let mut state_guard = futures::executor::block_on(state.lock());
-            state_guard.mastodon_authorized = true;
+            state_guard.post_text = autosaved_text.clone();
+            state_guard.toast("Restored unsent text from your last session", true);
+            info!("Restored unsent composer text from the recovery file");
         }
+        let rt = Arc::new(Runtime::new().unwrap());
 
+        #[cfg(feature = "plugins")]
+        plugins::load();
+        let platform_checkboxes = default_platform_checkboxes();
+        load_authorizations(&state, &rt);
+        detect_limits(&state, &rt);
+
+        rt.spawn(connectivity::monitor(Arc::clone(&state)));
+        rt.spawn(scheduler::run(Arc::clone(&state)));
+        rt.spawn(github::watch(Arc::clone(&state)));
+        rt.spawn(engagement::track(Arc::clone(&state)));
+        rt.spawn(expiry::run(Arc::clone(&state)));
+        #[cfg(feature = "mqtt")]
+        rt.spawn(mqtt::listen(Arc::clone(&state)));
+        #[cfg(feature = "mastodon")]
+        rt.spawn(streaming::listen(Arc::clone(&state)));
+
+        let saved_selection = (profiles::active(), checked_platforms(&platform_checkboxes));
         Self {
             state,
             rt,
             platform_checkboxes,
+            log_buffer,
+            log_level: tracing::Level::INFO,
+            settings_draft: settings::get(),
+            last_autosave: std::time::Instant::now(),
+            autosaved_text,
+            saved_selection,
+            presets: presets::load_presets(),
+            new_preset_name: String::new(),
+            editing_preset: None,
+            new_profile_name: String::new(),
+            new_banned: String::new(),
+            new_disclaimer: String::new(),
+            config_path: "multique-config.json".to_string(),
+            sync_passphrase: std::env::var("SYNC_PASSPHRASE").unwrap_or_default(),
+            sync_conflict: false,
+            shown_unread: 0,
+            applied_appearance: None,
+            backup_path: "multique-credentials.backup".to_string(),
+            backup_passphrase: String::new(),
+            archive_dir: "multique-archive".to_string(),
+            history_filter: history::HistoryFilter::default(),
+            schedule_input: String::new(),
+            schedule_repeat: None,
+            schedule_count: 0,
+            schedule_until: String::new(),
+            selected_queue: None,
+            new_queue_name: String::new(),
+            #[cfg(feature = "mastodon")]
+            new_mastodon_instance: String::new(),
+            new_slot: (chrono::Weekday::Mon, String::new()),
+            new_image: media::ImageAttachment {
+                path: String::new(),
+                alt: String::new(),
+            },
+            composers: Vec::new(),
+            next_composer_id: 1,
+            report_period: report::Period::default(),
+            inbox_reply: None,
+            schedule_edit: None,
+            per_platform: false,
+            platform_tab: None,
+            compare: false,
+            compared: [None, None],
+            clipboard_url: None,
+            last_clipboard_check: std::time::Instant::now(),
+            mention_query: String::new(),
+            mention_typed_at: std::time::Instant::now(),
+            mention_searched: false,
+            #[cfg(feature = "mastodon")]
+            trending_fetched_at: None,
+            feed_url: String::new(),
+            thread_new_images: Vec::new(),
+            drafts: drafts::load_drafts(),
         }
     }
-}
 
-impl eframe::App for PostApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        let available_width = ctx.available_rect().width();
-        let main_section_width = available_width * 0.6;
-        let side_panel_width = available_width * 0.4;
-        let state_clone = Arc::clone(&self.state);
-        egui::SidePanel::right("note_panel").exact_width(side_panel_width).show(ctx, |ui| {
-            ui.heading(egui::RichText::new("📝 Instructions").color(egui::Color32::GREEN));
-            ui.label("1. Authorize the platforms you want to use.\n2. Check the boxes for the platforms you want to post to.\n3. Write your message and click 'Post.'\n\nKeep posts to under 5 every 15 minutes to avoid rate limiting.");
+    /// Platforms the composed post goes to: the accounts picked in the composer's chips, or else the checked ones
+    fn targets(&self, state: &posts::AppState) -> Vec<&'static str> {
+        self.platform_checkboxes
+            .iter()
+            .filter(|(platform, checked)| match &state.compose_targets {
+                Some(targets) => targets.iter().any(|target| target == *platform),
+                None => **checked,
+            })
+            .map(|(platform, _)| *platform)
+            .collect()
+    }
 
-            ui.add_space(20.0);
+    /// Activates another profile, reloading its accounts and default checkboxes
+    fn switch_profile(&mut self, profile: &str) {
+        profiles::switch(profile);
+        futures::executor::block_on(self.state.lock()).compose_targets = None;
+        self.settings_draft.active_profile = profile.to_string();
+        self.platform_checkboxes = default_platform_checkboxes();
+        load_authorizations(&self.state, &self.rt);
+        detect_limits(&self.state, &self.rt);
+        info!(profile, "Switched profile");
+    }
+
+    /// Buttons applying a preset (its platforms, and any hashtags, signature and visibility it carries), saving the
+    /// checked platforms as a new preset, and editing what a preset adds
+    fn render_presets(&mut self, ui: &mut egui::Ui) {
+        let mut apply = None;
+        let mut remove = None;
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Presets:");
+            for (i, preset) in self.presets.iter().enumerate() {
+                let button = ui
+                    .button(preset.name.as_str())
+                    .on_hover_text(preset.platforms.join(", "));
+                if button.clicked() {
+                    apply = Some(i);
+                }
+                button.context_menu(|ui| {
+                    if ui.button("✏ Edit preset").clicked() {
+                        self.editing_preset = Some(i);
+                        ui.close_menu();
+                    }
+                    if ui.button("🗑 Delete preset").clicked() {
+                        remove = Some(i);
+                        ui.close_menu();
+                    }
+                });
+            }
 
-            ui.heading(
-                egui::RichText::new("📋 It will post to:")
-                    .color(egui::Color32::GREEN),
+            accessible(
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.new_preset_name)
+                        .hint_text("New preset name")
+                        .desired_width(120.0),
+                ),
+                "New preset name",
             );
-            let state = futures::executor::block_on(state_clone.lock());
+            let name = self.new_preset_name.trim().to_string();
+            let valid = !name.is_empty() && !self.presets.iter().any(|preset| preset.name == name);
+            if ui
+                .add_enabled(valid, egui::Button::new("💾 Save checked as preset"))
+                .clicked()
+            {
+                self.presets.push(presets::Preset::new(
+                    &name,
+                    checked_platforms(&self.platform_checkboxes),
+                ));
+                presets::save_presets(&self.presets);
+                self.new_preset_name.clear();
+                info!(preset = %name, "Saved platform preset");
+            }
+        });
 
-            for (platform, checked) in &self.platform_checkboxes {
-                let is_authorized = match *platform {
-                    "Twitter" => state.twitter_authorized,
-                    "Bluesky" => state.bluesky_authorized,
-                    "Mastodon" => state.mastodon_authorized,
-                    "LinkedIn" => state.linkedin_authorized,
-                    _ => false,
-                };
+        if let Some(i) = self.editing_preset.filter(|i| *i < self.presets.len()) {
+            let mut done = false;
+            let mut preset = self.presets[i].clone();
+            ui.group(|ui| {
+                ui.label(format!("Preset \"{}\" also adds:", preset.name));
+                egui::Grid::new("preset_extras").num_columns(2).show(ui, |ui| {
+                    ui.label("Hashtags:");
+                    ui.add(egui::TextEdit::singleline(&mut preset.hashtags).hint_text("#rust #oss"));
+                    ui.end_row();
 
-                if *checked && is_authorized {
-                    ui.label(format!("- {}", platform));
-                }
+                    ui.label("Signature:");
+                    ui.add(egui::TextEdit::singleline(&mut preset.signature).hint_text("Line below the post"));
+                    ui.end_row();
+
+                    ui.label("Mastodon visibility:");
+                    egui::ComboBox::from_id_salt("preset_visibility")
+                        .selected_text(preset.visibility.map_or("Unchanged", |visibility| visibility.label()))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut preset.visibility, None, "Unchanged");
+                            for visibility in posts::Visibility::ALL {
+                                ui.selectable_value(&mut preset.visibility, Some(visibility), visibility.label());
+                            }
+                        });
+                    ui.end_row();
+                });
+                done = ui.button("Done").clicked();
+            });
+            if preset != self.presets[i] {
+                self.presets[i] = preset;
+                presets::save_presets(&self.presets);
+            }
+            if done {
+                self.editing_preset = None;
+            }
+        }
+
+        if let Some(i) = apply {
+            let preset = &self.presets[i];
+            for (platform, checked) in self.platform_checkboxes.iter_mut() {
+                *checked = preset.platforms.iter().any(|p| p == platform);
+            }
+            let mut state = futures::executor::block_on(self.state.lock());
+            state.compose_targets = None;
+            state.post_text = preset.decorate(&state.post_text);
+            if let Some(visibility) = preset.visibility {
+                state.compose_options.visibility = visibility;
             }
+            info!(preset = %preset.name, "Applied preset");
+        }
+        if let Some(i) = remove {
+            let preset = self.presets.remove(i);
+            presets::save_presets(&self.presets);
+            self.editing_preset = None;
+            info!(preset = %preset.name, "Deleted preset");
+        }
+    }
+
+    /// Which platforms new posts in the active profile start with, saved straight away to the profile's config
+    fn render_default_platforms(&mut self, ui: &mut egui::Ui) {
+        let mut config = profiles::load_config();
+        let mut custom = config.default_platforms.is_some();
+        ui.horizontal(|ui| {
+            ui.label(format!("Pre-checked platforms ({}):", profiles::active()));
+            ui.checkbox(&mut custom, "Choose").on_hover_text(
+                "Otherwise the platforms checked last are restored, or Twitter, Bluesky and Mastodon the first time",
+            );
         });
 
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.set_min_width(main_section_width);
-            ui.heading("🌟 Multique - Post to all the platforms!");
+        let mut selected = config
+            .default_platforms
+            .clone()
+            .unwrap_or_else(|| checked_platforms(&self.platform_checkboxes));
+        if custom {
+            let mut platforms: Vec<&str> = default_platform_checkboxes().into_keys().collect();
+            platforms.sort_unstable();
+            ui.horizontal_wrapped(|ui| {
+                for platform in platforms {
+                    let mut checked = selected.iter().any(|p| p == platform);
+                    if ui.checkbox(&mut checked, platform).changed() {
+                        if checked {
+                            selected.push(platform.to_string());
+                            selected.sort();
+                        } else {
+                            selected.retain(|p| p != platform);
+                        }
+                    }
+                }
+            });
+        }
 
-            ui.add_space(20.0);
-            // Platform Selection Section
-            ui.group(|ui| {
-                ui.set_min_width(400.0);
-                ui.label("Platforms:");
+        let defaults = custom.then_some(selected);
+        if defaults != config.default_platforms {
+            config.default_platforms = defaults;
+            profiles::save_config(&config);
+            info!(profile = %profiles::active(), "Changed the pre-checked platforms");
+        }
+    }
 
-                render_platform_checkbox(
-                    ui,
-                    "🐦 Twitter / X:",
-                    "Twitter",
-                    &mut self.platform_checkboxes,
-                    |state| state.twitter_authorized,
-                    || {
-                        let rt = Arc::clone(&self.rt);
-                        let state_clone = Arc::clone(&self.state);
-                        rt.spawn(async move {
-                            if let Some(auth_url) = twitter::generate_auth_url().await {
-                                println!("Authorize your app at: {}", auth_url);
+    /// Banned words and required disclaimers of the active profile, saved straight away to the profile's config
+    fn render_content_rules(&mut self, ui: &mut egui::Ui) {
+        let mut config = profiles::load_config();
+        let rules = &mut config.content_rules;
+        let before = rules.clone();
+        ui.horizontal(|ui| {
+            ui.label(format!("Content rules ({}):", profiles::active()));
+            egui::ComboBox::from_id_salt("rules_enforcement")
+                .selected_text(rules.enforcement.label())
+                .show_ui(ui, |ui| {
+                    for enforcement in rules::Enforcement::ALL {
+                        ui.selectable_value(&mut rules.enforcement, enforcement, enforcement.label());
+                    }
+                })
+                .response
+                .on_hover_text("Warn before posting, or refuse to post until the text follows the rules");
+        });
 
-                                println!("Enter the authorization code:");
-                                let mut input_code = String::new();
-                                std::io::stdin().read_line(&mut input_code).unwrap();
-                                let code = input_code.trim().to_string();
+        let lists = [
+            ("Banned words and phrases:", &mut rules.banned, &mut self.new_banned),
+            ("Required disclaimers:", &mut rules.required, &mut self.new_disclaimer),
+        ];
+        for (label, list, new_entry) in lists {
+            ui.label(label);
+            let mut removed = None;
+            ui.horizontal_wrapped(|ui| {
+                for (index, entry) in list.iter().enumerate() {
+                    ui.label(entry);
+                    if icon_button(ui, "✖", &format!("Remove {}", entry)).clicked() {
+                        removed = Some(index);
+                    }
+                }
+            });
+            if let Some(index) = removed {
+                list.remove(index);
+            }
+            ui.horizontal(|ui| {
+                let field = accessible(ui.text_edit_singleline(new_entry), label);
+                let entered = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                let entry = new_entry.trim().to_string();
+                let addable = !entry.is_empty() && !list.contains(&entry);
+                if (ui.add_enabled(addable, egui::Button::new("➕ Add")).clicked() || entered) && addable {
+                    list.push(entry);
+                    new_entry.clear();
+                }
+            });
+        }
 
-                                if twitter::authorize_twitter(state_clone.clone(), &code).await.is_some() {
-                                    let mut state = state_clone.lock().await;
-                                    state.twitter_authorized = true;
-                                }
-                            }
-                        });
+        if *rules != before {
+            profiles::save_config(&config);
+            info!(profile = %profiles::active(), "Changed the content rules");
+        }
+    }
+
+    /// Export/import of non-secret configuration, for setting up another machine
+    fn render_config_transfer(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Config file:");
+            ui.text_edit_singleline(&mut self.config_path);
+        });
+
+        ui.horizontal(|ui| {
+            let path = std::path::PathBuf::from(self.config_path.trim());
+            if ui
+                .button("📤 Export")
+                .on_hover_text("Saves settings, profile defaults and queues. Tokens are not included.")
+                .clicked()
+            {
+                let exported = config_bundle::export_to(&path);
+                futures::executor::block_on(self.state.lock()).toast(
+                    if exported {
+                        "Configuration exported"
+                    } else {
+                        "Failed to export configuration"
                     },
-                    state_clone.clone(),
+                    exported,
                 );
+            }
 
-                render_platform_checkbox(
-                    ui,
-                    "🐦 Bluesky:",
-                    "Bluesky",
-                    &mut self.platform_checkboxes,
-                    |state| state.bluesky_authorized,
-                    || {
-                        let rt = Arc::clone(&self.rt);
-                        let state_clone = Arc::clone(&self.state);
-                        rt.spawn(async move {
-                            if bluesky::authorize_bluesky(state_clone.clone()).await.is_some() {
-                                let mut state = state_clone.lock().await;
-                                state.bluesky_authorized = true;
-                            }
-                        });
+            if ui.button("📥 Import").clicked() {
+                let imported = config_bundle::import_from(&path);
+                if imported {
+                    futures::executor::block_on(self.state.lock()).queues = queues::load_queues();
+                    self.settings_draft = settings::get();
+                    self.platform_checkboxes = default_platform_checkboxes();
+                }
+                futures::executor::block_on(self.state.lock()).toast(
+                    if imported {
+                        "Configuration imported"
+                    } else {
+                        "Failed to import configuration"
                     },
-                    state_clone.clone(),
+                    imported,
                 );
+            }
+        });
+    }
+
+    /// Encrypted sync of configuration and tokens with other machines
+    fn render_sync(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Sync passphrase:");
+            ui.add(egui::TextEdit::singleline(&mut self.sync_passphrase).password(true));
+        });
+
+        let configured = !settings::get().sync_target.trim().is_empty() && !self.sync_passphrase.is_empty();
+        let mut outcome = None;
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(configured, egui::Button::new("🔄 Sync now"))
+                .on_disabled_hover_text("Set and save a sync target, then enter the passphrase")
+                .clicked()
+            {
+                outcome = Some(self.rt.block_on(sync::sync(&self.sync_passphrase)));
+            }
+            if self.sync_conflict {
+                ui.colored_label(egui::Color32::YELLOW, "Both machines changed:");
+                if ui.button("Keep this machine's").clicked() {
+                    outcome = Some(self.rt.block_on(sync::push(&self.sync_passphrase)));
+                }
+                if ui.button("Use the synced copy").clicked() {
+                    outcome = Some(self.rt.block_on(sync::pull(&self.sync_passphrase)));
+                }
+            }
+        });
+
+        let Some(outcome) = outcome else {
+            return;
+        };
+        self.sync_conflict = outcome == sync::SyncOutcome::Conflict;
+        if outcome == sync::SyncOutcome::Pulled {
+            // Keep the active profile; the pulled copy may have changed its defaults and tokens
+            self.settings_draft = settings::get();
+            self.platform_checkboxes = default_platform_checkboxes();
+            load_authorizations(&self.state, &self.rt);
+        }
+        let (message, success) = match outcome {
+            sync::SyncOutcome::UpToDate => ("Already in sync", true),
+            sync::SyncOutcome::Pushed => ("Uploaded configuration and tokens", true),
+            sync::SyncOutcome::Pulled => ("Downloaded configuration and tokens", true),
+            sync::SyncOutcome::Conflict => ("Sync conflict: choose which copy to keep", false),
+            sync::SyncOutcome::Failed => ("Sync failed; see the log for details", false),
+        };
+        futures::executor::block_on(self.state.lock()).toast(message, success);
+    }
+
+    /// Posts sent, engagement and failures per platform over the last week or month
+    fn render_report(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("📈 Report").show(ui, |ui| {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt("report_period")
+                    .selected_text(self.report_period.label())
+                    .show_ui(ui, |ui| {
+                        for period in report::Period::ALL {
+                            ui.selectable_value(&mut self.report_period, period, period.label());
+                        }
+                    });
+            });
+            let report = {
+                let state = futures::executor::block_on(self.state.lock());
+                report::build(&state.history, &state.engagement_samples, self.report_period)
+            };
+            ui.label(format!(
+                "{} post(s) sent since {}",
+                report.posts,
+                timezone::format(report.since)
+            ));
+            if !report.platforms.is_empty() {
+                egui::Grid::new("report").striped(true).show(ui, |ui| {
+                    ui.strong("Platform");
+                    ui.strong("Sent");
+                    ui.strong("Failed");
+                    ui.strong("Failure rate");
+                    ui.strong("Likes");
+                    ui.strong("Reposts");
+                    ui.end_row();
+                    for summary in &report.platforms {
+                        ui.label(summary.platform.as_str());
+                        ui.label(summary.sent.to_string());
+                        ui.label(summary.failed.to_string());
+                        ui.label(format!("{:.0}%", summary.failure_rate() * 100.0));
+                        ui.label(summary.likes.to_string());
+                        ui.label(summary.reposts.to_string());
+                        ui.end_row();
+                    }
+                });
+            }
+            if let Some(top) = &report.top_post {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(format!(
+                        "🏆 Top post ({} likes and reposts): {}",
+                        top.engagement,
+                        top.excerpt()
+                    ));
+                    if let Some(url) = &top.url {
+                        ui.hyperlink_to("🔗 Open", url);
+                    }
+                });
+            }
+            if ui
+                .button("📋 Copy as Markdown")
+                .on_hover_text("Also printed by `multique report`")
+                .clicked()
+            {
+                ui.ctx().copy_text(report.to_markdown());
+            }
+        });
+    }
+
+    /// Writes the post history to a folder of Markdown and JSON files, with copies of the images
+    fn render_archive_export(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            accessible(
+                ui.add(egui::TextEdit::singleline(&mut self.archive_dir).hint_text("Archive folder")),
+                "Archive folder",
+            );
+            if ui
+                .add_enabled(
+                    !self.archive_dir.trim().is_empty(),
+                    egui::Button::new("📦 Export archive"),
+                )
+                .on_hover_text(
+                    "One Markdown and one JSON file per post, with copies of the images, e.g. for a static site",
+                )
+                .clicked()
+            {
+                let (message, success) = match archive::export(std::path::Path::new(self.archive_dir.trim())) {
+                    Ok(count) => (
+                        format!("Exported {} post(s) to {}", count, self.archive_dir.trim()),
+                        true,
+                    ),
+                    Err(err) => (err, false),
+                };
+                futures::executor::block_on(self.state.lock()).toast(message, success);
+            }
+        });
+    }
+
+    /// Passphrase-encrypted backup and restore of every profile's platform tokens
+    fn render_credentials_backup(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Credentials backup:");
+            ui.text_edit_singleline(&mut self.backup_path);
+        });
+        ui.horizontal(|ui| {
+            ui.label("Backup passphrase:");
+            ui.add(egui::TextEdit::singleline(&mut self.backup_passphrase).password(true));
+        });
+
+        ui.horizontal(|ui| {
+            let path = std::path::PathBuf::from(self.backup_path.trim());
+            let ready = !self.backup_passphrase.is_empty() && !self.backup_path.trim().is_empty();
+
+            if ui
+                .add_enabled(ready, egui::Button::new("🔐 Backup credentials"))
+                .on_hover_text("Saves the tokens of all profiles, encrypted with the passphrase")
+                .clicked()
+            {
+                let (message, success) = match backup::backup_to(&path, &self.backup_passphrase) {
+                    Some(count) => (format!("Backed up {} token file(s)", count), true),
+                    None => ("Failed to back up credentials".to_string(), false),
+                };
+                futures::executor::block_on(self.state.lock()).toast(message, success);
+            }
+
+            if ui
+                .add_enabled(ready, egui::Button::new("♻ Restore credentials"))
+                .on_hover_text("Restores tokens from a backup, replacing current ones for the same platforms")
+                .clicked()
+            {
+                let restored = backup::restore_from(&path, &self.backup_passphrase);
+                if restored.is_some() {
+                    load_authorizations(&self.state, &self.rt);
+                }
+                let (message, success) = match restored {
+                    Some(count) => (format!("Restored {} token file(s)", count), true),
+                    None => ("Failed to restore credentials; check the passphrase".to_string(), false),
+                };
+                futures::executor::block_on(self.state.lock()).toast(message, success);
+            }
+        });
+    }
 
+    /// Mastodon accounts on other instances than the main one, each with its own checkbox, and a field to add one
+    #[cfg(feature = "mastodon")]
+    fn render_mastodon_accounts(&mut self, ui: &mut egui::Ui) {
+        let accounts = futures::executor::block_on(self.state.lock()).mastodon_accounts.clone();
+        for account in accounts {
+            let platform = account.platform_key();
+            let instance = account.instance.clone();
+            let account_software = account.software;
+            self.platform_checkboxes.entry(platform).or_insert(false);
+            ui.horizontal(|ui| {
                 render_platform_checkbox(
                     ui,
-                    "🐘 Mastodon:",
-                    "Mastodon",
+                    &format!("🐘 {}:", platform),
+                    platform,
                     &mut self.platform_checkboxes,
-                    |state| state.mastodon_authorized,
+                    |state| state.mastodon_account_authorized(platform),
                     || {
-                        let rt = Arc::clone(&self.rt);
                         let state_clone = Arc::clone(&self.state);
-                        rt.spawn(async move {
-                            let client_id =
-                                std::env::var("MASTODON_CLIENT_ID").expect("MASTODON_CLIENT_ID not set in .env");
-                            let client_secret = std::env::var("MASTODON_CLIENT_SECRET")
-                                .expect("MASTODON_CLIENT_SECRET not set in .env");
-
-                            let authorization_url = mastodon::generate_auth_url(&client_id).await;
+                        self.rt.spawn(async move {
+                            let base_url = account.base_url();
+                            let authorization_url =
+                                mastodon::generate_auth_url(&base_url, &account.client_id, account.software).await;
                             println!("Authorize your app at: {}", authorization_url);
 
                             println!("Enter the authorization code:");
@@ -197,157 +916,3459 @@ impl eframe::App for PostApp {
                             std::io::stdin().read_line(&mut input_code).unwrap();
                             let code = input_code.trim().to_string();
 
-                            if let Some(access_token) =
-                                mastodon::authorize_mastodon(&client_id, &client_secret, &code).await
-                            {
-                                mastodon::save_tokens(&access_token);
-                                let mut state = state_clone.lock().await;
-                                state.mastodon_authorized = true;
+                            let Some(access_token) = mastodon::authorize_mastodon(
+                                &base_url,
+                                &account.client_id,
+                                &account.client_secret,
+                                &code,
+                            )
+                            .await
+                            else {
+                                return;
+                            };
+                            let mut accounts = mastodon::load_accounts();
+                            if let Some(stored) = accounts.iter_mut().find(|a| a.instance == account.instance) {
+                                stored.access_token = Some(access_token);
                             }
+                            mastodon::save_accounts(&accounts);
+                            state_clone.lock().await.mastodon_accounts = accounts;
                         });
                     },
-                    state_clone.clone(),
+                    Arc::clone(&self.state),
                 );
+                let mut software = account_software;
+                egui::ComboBox::from_id_salt(("mastodon_software", platform))
+                    .selected_text(software.label())
+                    .show_ui(ui, |ui| {
+                        for option in activitypub::Software::ALL {
+                            ui.selectable_value(&mut software, option, option.label());
+                        }
+                    });
+                if software != account_software {
+                    let mut accounts = mastodon::load_accounts();
+                    if let Some(stored) = accounts.iter_mut().find(|a| a.instance == instance) {
+                        stored.software = software;
+                    }
+                    mastodon::save_accounts(&accounts);
+                    info!(%platform, ?software, "Changed instance software");
+                    futures::executor::block_on(self.state.lock()).mastodon_accounts = accounts;
+                }
+                if icon_button(ui, "✖", "Remove this account").clicked() {
+                    let mut accounts = mastodon::load_accounts();
+                    accounts.retain(|a| a.instance != instance);
+                    mastodon::save_accounts(&accounts);
+                    audit::record(audit::Action::Delete, Some(platform), "Removed account");
+                    self.platform_checkboxes.remove(platform);
+                    futures::executor::block_on(self.state.lock()).mastodon_accounts = accounts;
+                }
+            });
+        }
 
-                render_platform_checkbox(
-                    ui,
-                    "🔗 LinkedIn:",
-                    "LinkedIn",
-                    &mut self.platform_checkboxes,
-                    |state| state.linkedin_authorized,
-                    || {
-                        let rt = Arc::clone(&self.rt);
-                        let state_clone = Arc::clone(&self.state);
-                        rt.spawn(async move {
-                            if let Some(auth_url) = linkedin::generate_auth_url().await {
-                                println!("Authorize your app at: {}", auth_url);
+        ui.horizontal(|ui| {
+            accessible(
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.new_mastodon_instance)
+                        .hint_text("Another instance, e.g. hachyderm.io"),
+                ),
+                "Mastodon instance to add",
+            );
+            let instance = mastodon::normalize_instance(&self.new_mastodon_instance);
+            if ui
+                .add_enabled(!instance.is_empty(), egui::Button::new("➕ Add Mastodon account"))
+                .clicked()
+            {
+                self.new_mastodon_instance.clear();
+                let state_clone = Arc::clone(&self.state);
+                self.rt.spawn(async move {
+                    let mut accounts = mastodon::load_accounts();
+                    if accounts.iter().any(|a| a.instance == instance) {
+                        state_clone
+                            .lock()
+                            .await
+                            .toast(format!("{} is already added", instance), false);
+                        return;
+                    }
+                    let Some(account) = mastodon::register_app(&instance).await else {
+                        state_clone
+                            .lock()
+                            .await
+                            .toast(format!("Couldn't register with {}", instance), false);
+                        return;
+                    };
+                    let platform = account.platform();
+                    accounts.push(account);
+                    mastodon::save_accounts(&accounts);
+                    let mut state = state_clone.lock().await;
+                    state.mastodon_accounts = accounts;
+                    state.toast(format!("Added {}; press Authorize to sign in", platform), true);
+                });
+            }
+        });
+    }
 
-                                println!("Enter the authorization code:");
-                                let mut input_code = String::new();
-                                std::io::stdin().read_line(&mut input_code).unwrap();
-                                let code = input_code.trim().to_string();
+    /// Profile switcher shown above the platform list
+    fn render_profile_bar(&mut self, ui: &mut egui::Ui) {
+        let active = profiles::active();
+        let mut switch_to = None;
 
-                                if linkedin::authorize_linkedin(state_clone.clone(), &code).await.is_some() {
-                                    let mut state = state_clone.lock().await;
-                                    state.linkedin_authorized = true;
-                                }
-                            }
-                        });
-                    },
-                    state_clone.clone(),
+        ui.horizontal(|ui| {
+            ui.label("👤 Profile:");
+            egui::ComboBox::from_id_salt("profile")
+                .selected_text(active.as_str())
+                .show_ui(ui, |ui| {
+                    for profile in profiles::list() {
+                        if ui.selectable_label(profile == active, profile.as_str()).clicked() && profile != active {
+                            switch_to = Some(profile);
+                        }
+                    }
+                });
+
+            accessible(
+                ui.add(egui::TextEdit::singleline(&mut self.new_profile_name).hint_text("New profile name")),
+                "New profile name",
+            );
+            if ui.button("➕ Add").clicked() {
+                let name = self.new_profile_name.trim().to_string();
+                if profiles::create(&name) {
+                    self.new_profile_name.clear();
+                    switch_to = Some(name);
+                } else {
+                    warn!(profile = %name, "Invalid or duplicate profile name");
+                }
+            }
+
+            if ui
+                .button("💾 Save checkboxes as profile default")
+                .on_hover_text("New posts in this profile start with the current platform selection")
+                .clicked()
+            {
+                let mut config = profiles::load_config();
+                config.default_platforms = Some(checked_platforms(&self.platform_checkboxes));
+                profiles::save_config(&config);
+                info!(profile = %active, "Saved default platforms for profile");
+            }
+        });
+
+        if let Some(profile) = switch_to {
+            self.switch_profile(&profile);
+        }
+    }
+
+    /// Loads an earlier post into the composer, with the platforms it went to checked, for editing and re-sending
+    fn post_again(&mut self, entry: &history::HistoryEntry) {
+        let mut state = futures::executor::block_on(self.state.lock());
+        if state.posting {
+            state.toast("Wait for the current post to finish", false);
+            return;
+        }
+        state.post_text = entry.text.clone();
+        // A fresh key, otherwise the platforms it already went to would be skipped as duplicates
+        state.compose_key = history::new_idempotency_key();
+        state.post_progress.clear();
+        state.compose_targets = None;
+        for (platform, checked) in self.platform_checkboxes.iter_mut() {
+            *checked = entry.results.iter().any(|r| r.platform == *platform);
+        }
+        state.toast("Loaded post into the composer", true);
+        info!(entry_id = %entry.id, "Loaded history entry into the composer");
+    }
+
+    /// Schedules the composed post (once or repeating) from the schedule fields, clearing the composer on success
+    fn schedule_post(&mut self, state: &mut posts::AppState) {
+        let scheduled_at = match timezone::parse(&self.schedule_input) {
+            Ok(scheduled_at) if scheduled_at <= chrono::Utc::now() => {
+                state.toast("That time has already passed", false);
+                return;
+            }
+            Ok(scheduled_at) => scheduled_at,
+            Err(err) => {
+                state.toast(err, false);
+                return;
+            }
+        };
+
+        let recurrence = match self.schedule_repeat {
+            Some(frequency) => {
+                let until = match self.schedule_until.trim() {
+                    "" => None,
+                    until => match timezone::parse(until) {
+                        Ok(until) => Some(until),
+                        Err(err) => {
+                            state.toast(format!("End date: {}", err), false);
+                            return;
+                        }
+                    },
+                };
+                Some(scheduler::Recurrence {
+                    frequency,
+                    cron: None,
+                    until,
+                    remaining: (self.schedule_count > 0).then_some(self.schedule_count),
+                })
+            }
+            None => None,
+        };
+
+        let platforms: Vec<String> = self
+            .targets(state)
+            .iter()
+            .map(|platform| platform.to_string())
+            .collect();
+        scheduler::schedule(
+            &state.post_text,
+            &platforms,
+            scheduled_at,
+            &state.compose_key,
+            recurrence,
+            None,
+            &state.compose_options,
+        );
+        state.schedule = scheduler::load_schedule();
+        info!(scheduled_at = %scheduled_at, repeat = ?self.schedule_repeat, "Scheduled post");
+        state.toast(format!("Scheduled for {}", timezone::format(scheduled_at)), true);
+        state.post_text.clear();
+        state.compose_key = history::new_idempotency_key();
+        state.compose_options = posts::PostOptions::default();
+        state.compose_targets = None;
+        self.schedule_input.clear();
+        self.schedule_until.clear();
+    }
+
+    /// Checks the selected queue's platforms, if it has any
+    fn apply_queue_platforms(&mut self) {
+        let Some(queue) = queues::load_queues()
+            .into_iter()
+            .find(|q| Some(&q.name) == self.selected_queue.as_ref())
+        else {
+            return;
+        };
+        if queue.platforms.is_empty() {
+            return;
+        }
+        for (platform, checked) in self.platform_checkboxes.iter_mut() {
+            *checked = queue.platforms.iter().any(|p| p == platform);
+        }
+    }
+
+    /// Runs the checks before posting: the ones on the text right away, then the mention lookups.  The post goes out
+    /// once nothing was found, or after the warnings were dismissed or confirmed (see `render_lint_warnings`).
+    fn lint_post(&self, state: &mut posts::AppState) {
+        let text = state.post_text.clone();
+        let options = state.compose_options.clone();
+        let platforms: Vec<String> = self
+            .targets(state)
+            .iter()
+            .map(|platform| platform.to_string())
+            .collect();
+        let rules = profiles::load_config().content_rules;
+        let mut warnings = lint::check(&text, &options, &platforms, |platform| state.limits(platform), &rules);
+        let lookups: Vec<String> = platforms
+            .into_iter()
+            .filter(|platform| is_authorized(state, platform))
+            .collect();
+        let bluesky_token = state.bluesky_token.clone();
+        let offline = state.offline;
+        state.linting = true;
+        let task_state = Arc::clone(&self.state);
+        self.rt.spawn(async move {
+            // Offline, every lookup would fail and flag every mention
+            if !offline {
+                warnings.extend(lint::unknown_mentions(&text, &options, &lookups, bluesky_token.as_deref()).await);
+            }
+            let mut state = task_state.lock().await;
+            state.linting = false;
+            state.lint_warnings = Some(warnings);
+        });
+    }
+
+    /// The warnings found before posting, each dismissible, with the choice to post anyway or keep editing.  Posts
+    /// straight away when there are none left.  Broken content rules set to block can't be dismissed or posted past.
+    fn render_lint_warnings(&self, ui: &mut egui::Ui, state: &mut posts::AppState) {
+        let Some(warnings) = state.lint_warnings.as_mut() else {
+            return;
+        };
+        let mut confirmed = warnings.is_empty();
+        if !confirmed {
+            let mut cancelled = false;
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                ui.label(egui::RichText::new("⚠ Before posting").strong());
+                let mut dismissed = None;
+                for (index, warning) in warnings.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if warning.blocking {
+                            ui.colored_label(egui::Color32::LIGHT_RED, "⛔");
+                        } else if icon_button(ui, "✖", "Dismiss warning").clicked() {
+                            dismissed = Some(index);
+                        }
+                        match &warning.platform {
+                            Some(platform) => ui.label(format!("{}: {}", platform, warning.message)),
+                            None => ui.label(&warning.message),
+                        };
+                    });
+                }
+                if let Some(index) = dismissed {
+                    warnings.remove(index);
+                }
+                let blocked = warnings.iter().any(|warning| warning.blocking);
+                if blocked {
+                    ui.label("⛔ The profile's content rules block this post until it's changed.");
+                }
+                ui.horizontal(|ui| {
+                    confirmed =
+                        ui.add_enabled(!blocked, egui::Button::new("📤 Post anyway")).clicked() || warnings.is_empty();
+                    cancelled = ui.button("✏ Keep editing").clicked();
+                });
+            });
+            if cancelled {
+                state.lint_warnings = None;
+                return;
+            }
+        }
+        if confirmed {
+            state.lint_warnings = None;
+            self.send_post(state);
+        }
+    }
+
+    /// Sends the composed post to the selected platforms, or holds it in the outbox when offline
+    fn send_post(&self, state: &mut posts::AppState) {
+        // Mark the post in flight before spawning so the button is disabled on the very next frame
+        state.posting = true;
+        state.post_progress.clear();
+        let text = state.post_text.clone();
+        let idempotency_key = state.compose_key.clone();
+        let options = state.compose_options.clone();
+        let platforms: Vec<String> = self
+            .targets(state)
+            .iter()
+            .map(|platform| platform.to_string())
+            .collect();
+        let task_state = Arc::clone(&self.state);
+
+        self.rt.spawn(async move {
+            let all_succeeded = send(&task_state, &text, &platforms, &idempotency_key, &options).await;
+
+            let mut state = task_state.lock().await;
+            state.posting = false;
+            // Keep the text (and its idempotency key) after a failure so retrying only resends to the
+            // platforms that failed
+            if all_succeeded {
+                state.post_text.clear();
+                state.compose_key = history::new_idempotency_key();
+                state.compose_options = posts::PostOptions::default();
+                state.compose_targets = None;
+            }
+        });
+    }
+
+    /// Schedules the composed post for the selected queue's next free slot
+    fn add_to_queue(&mut self, state: &mut posts::AppState) {
+        let Some(queue) = queues::load_queues()
+            .into_iter()
+            .find(|q| Some(&q.name) == self.selected_queue.as_ref())
+        else {
+            return;
+        };
+        let Some(scheduled_at) = queues::next_free_slot(&queue) else {
+            state.toast(
+                format!("Queue \"{}\" has no free slots; add some under Scheduled", queue.name),
+                false,
+            );
+            return;
+        };
+
+        let platforms: Vec<String> = self
+            .targets(state)
+            .iter()
+            .map(|platform| platform.to_string())
+            .collect();
+        scheduler::schedule(
+            &state.post_text,
+            &platforms,
+            scheduled_at,
+            &state.compose_key,
+            None,
+            Some(&queue.name),
+            &state.compose_options,
+        );
+        state.schedule = scheduler::load_schedule();
+        info!(queue = %queue.name, scheduled_at = %scheduled_at, "Added post to queue");
+        state.toast(
+            format!("Queued in \"{}\" for {}", queue.name, timezone::format(scheduled_at)),
+            true,
+        );
+        state.post_text.clear();
+        state.compose_key = history::new_idempotency_key();
+        state.compose_options = posts::PostOptions::default();
+        state.compose_targets = None;
+    }
+
+    /// Management of queues: their slots and default platforms
+    fn render_queues(&mut self, ui: &mut egui::Ui) {
+        let mut queues = futures::executor::block_on(self.state.lock()).queues.clone();
+        let mut changed = false;
+        let mut toggled = false;
+        let mut delete = None;
+        let mut all_platforms: Vec<&str> = default_platform_checkboxes().into_keys().collect();
+        all_platforms.sort();
+
+        for (index, queue) in queues.iter_mut().enumerate() {
+            let title = if queue.paused {
+                egui::RichText::new(format!("📂 {} (⏸ paused)", queue.name)).color(egui::Color32::YELLOW)
+            } else {
+                egui::RichText::new(format!("📂 {}", queue.name))
+            };
+            egui::CollapsingHeader::new(title)
+                .id_salt(("queue", index))
+                .show(ui, |ui| {
+                    let (label, hover) = if queue.paused {
+                        ("▶ Resume queue", "Its posts due while paused go out on the next check")
+                    } else {
+                        ("⏸ Pause queue", "Hold this queue's posts, even when due, until resumed")
+                    };
+                    if ui.button(label).on_hover_text(hover).clicked() {
+                        queues::set_paused(&queue.name, !queue.paused);
+                        queue.paused = !queue.paused;
+                        toggled = true;
+                    }
+
+                    queue
+                        .slots
+                        .sort_by_key(|slot| (slot.weekday.num_days_from_monday(), slot.time));
+                    let mut remove_slot = None;
+                    for (slot_index, slot) in queue.slots.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("{} {}", slot.weekday, slot.time.format("%H:%M")));
+                            if icon_button(ui, "✖", "Remove this slot").clicked() {
+                                remove_slot = Some(slot_index);
+                            }
+                        });
+                    }
+                    if let Some(slot_index) = remove_slot {
+                        queue.slots.remove(slot_index);
+                        changed = true;
+                    }
+
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_salt(("queue_slot_day", index))
+                            .selected_text(self.new_slot.0.to_string())
+                            .show_ui(ui, |ui| {
+                                let week = std::iter::successors(Some(chrono::Weekday::Mon), |day| Some(day.succ()));
+                                for weekday in week.take(7) {
+                                    ui.selectable_value(&mut self.new_slot.0, weekday, weekday.to_string());
+                                }
+                            });
+                        accessible(
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.new_slot.1)
+                                    .hint_text("HH:MM")
+                                    .desired_width(60.0),
+                            ),
+                            "Slot time",
+                        );
+                        if ui.button("➕ Add slot").clicked() {
+                            match chrono::NaiveTime::parse_from_str(self.new_slot.1.trim(), "%H:%M") {
+                                Ok(time) => {
+                                    queue.slots.push(queues::Slot {
+                                        weekday: self.new_slot.0,
+                                        time,
+                                    });
+                                    self.new_slot.1.clear();
+                                    changed = true;
+                                }
+                                Err(_) => warn!(time = %self.new_slot.1, "Slot time must look like 09:30"),
+                            }
+                        }
+                    });
+
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label("Platforms:");
+                        for platform in &all_platforms {
+                            let mut checked = queue.platforms.iter().any(|p| p == platform);
+                            if ui.checkbox(&mut checked, *platform).changed() {
+                                if checked {
+                                    queue.platforms.push(platform.to_string());
+                                } else {
+                                    queue.platforms.retain(|p| p != platform);
+                                }
+                                changed = true;
+                            }
+                        }
+                    });
+
+                    if ui.button("🗑 Delete queue").clicked() {
+                        delete = Some(index);
+                    }
+                });
+        }
+
+        if let Some(index) = delete {
+            let removed = queues.remove(index);
+            audit::record(
+                audit::Action::Delete,
+                None,
+                &format!("Deleted queue \"{}\"", removed.name),
+            );
+            if self.selected_queue.as_ref() == Some(&removed.name) {
+                self.selected_queue = None;
+            }
+            changed = true;
+        }
+
+        ui.horizontal(|ui| {
+            accessible(
+                ui.add(egui::TextEdit::singleline(&mut self.new_queue_name).hint_text("New queue name")),
+                "New queue name",
+            );
+            let name = self.new_queue_name.trim().to_string();
+            let valid = !name.is_empty() && !queues.iter().any(|q| q.name == name);
+            if ui.add_enabled(valid, egui::Button::new("➕ Add queue")).clicked() {
+                queues.push(queues::Queue {
+                    name,
+                    slots: Vec::new(),
+                    platforms: Vec::new(),
+                    paused: false,
+                });
+                self.new_queue_name.clear();
+                changed = true;
+            }
+        });
+
+        if changed {
+            queues::save_queues(&queues);
+        }
+        if changed || toggled {
+            futures::executor::block_on(self.state.lock()).queues = queues;
+        }
+    }
+
+    /// Tabs for the selected platforms, each with the text posted there, its length, and the options only that
+    /// platform has.  A tab shows the composed text (after the platform's transforms) until it is edited.
+    fn render_platform_tabs(&mut self, ui: &mut egui::Ui, state: &mut posts::AppState, selected: &[&'static str]) {
+        if selected.is_empty() {
+            return;
+        }
+        let tab = match self.platform_tab {
+            Some(tab) if selected.contains(&tab) => tab,
+            _ => selected[0],
+        };
+        self.platform_tab = Some(tab);
+        ui.horizontal_wrapped(|ui| {
+            for platform in selected {
+                let label = if state.compose_options.texts.contains_key(*platform) {
+                    format!("{} ✏", platform)
+                } else {
+                    platform.to_string()
+                };
+                if ui.selectable_label(tab == *platform, label).clicked() {
+                    self.platform_tab = Some(*platform);
+                }
+            }
+        });
+
+        ui.group(|ui| {
+            let custom = state.compose_options.texts.get(tab).cloned();
+            let mut text = custom
+                .clone()
+                .unwrap_or_else(|| transforms::apply(tab, &state.post_text));
+            let response = ui.add_enabled(
+                !state.posting,
+                egui::TextEdit::multiline(&mut text).layouter(&mut highlight_entities),
+            );
+            if accessible(response, &format!("Text for {}", tab)).changed() {
+                state.compose_options.texts.insert(tab.to_string(), text.clone());
+            }
+            if custom.is_some()
+                && ui
+                    .small_button("↺ Use the composed text")
+                    .on_hover_text(format!("Post the same text on {} as everywhere else", tab))
+                    .clicked()
+            {
+                state.compose_options.texts.remove(tab);
+            }
+            render_length(
+                ui,
+                tab,
+                limits::count(&text),
+                state.limits(tab),
+                &mut self.settings_draft,
+            );
+
+            let options = &mut state.compose_options;
+            match limits::base_platform(tab) {
+                "Mastodon" => {
+                    ui.horizontal(|ui| {
+                        ui.label("Content warning:");
+                        ui.add(egui::TextEdit::singleline(&mut options.content_warning).hint_text("None"));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Visibility:");
+                        egui::ComboBox::from_id_salt("mastodon_visibility")
+                            .selected_text(options.visibility.label())
+                            .show_ui(ui, |ui| {
+                                for visibility in posts::Visibility::ALL {
+                                    ui.selectable_value(&mut options.visibility, visibility, visibility.label());
+                                }
+                            });
+                    });
+                }
+                "Twitter" => {
+                    ui.horizontal(|ui| {
+                        ui.label("Who can reply:");
+                        egui::ComboBox::from_id_salt("twitter_reply_settings")
+                            .selected_text(options.reply_settings.label())
+                            .show_ui(ui, |ui| {
+                                for settings in posts::ReplySettings::ALL {
+                                    ui.selectable_value(&mut options.reply_settings, settings, settings.label());
+                                }
+                            });
+                    });
+                }
+                "Bluesky" => render_link_previews(ui, options),
+                _ => {}
+            }
+        });
+    }
+
+    /// Previews of two of the selected platforms next to each other, so threading, truncation, dropped hashtags and
+    /// missing link cards stand out before posting
+    fn render_preview_comparison(&mut self, ui: &mut egui::Ui, state: &posts::AppState, selected: &[&'static str]) {
+        for (i, side) in self.compared.iter_mut().enumerate() {
+            if !side.is_some_and(|platform| selected.contains(&platform)) {
+                *side = selected.get(i).copied();
+            }
+        }
+        ui.columns(2, |columns| {
+            for (i, column) in columns.iter_mut().enumerate() {
+                egui::ComboBox::from_id_salt(("compare_platform", i))
+                    .selected_text(self.compared[i].unwrap_or_default())
+                    .show_ui(column, |ui| {
+                        for platform in selected {
+                            ui.selectable_value(&mut self.compared[i], Some(*platform), *platform);
+                        }
+                    });
+                let Some(platform) = self.compared[i] else {
+                    continue;
+                };
+                let preview = publish::preview(
+                    platform,
+                    &state.post_text,
+                    &state.compose_options,
+                    state.limits(platform).max_chars,
+                );
+                if preview.parts.is_empty() {
+                    column.colored_label(egui::Color32::YELLOW, "Skipped: the text is too long");
+                }
+                for (n, part) in preview.parts.iter().enumerate() {
+                    column.group(|ui| {
+                        if preview.parts.len() > 1 {
+                            ui.weak(format!("{}/{}", n + 1, preview.parts.len()));
+                        }
+                        ui.label(part);
+                    });
+                }
+                if preview.truncated {
+                    column.colored_label(egui::Color32::YELLOW, "✂ Shortened");
+                }
+                column.weak(format!(
+                    "# {} hashtag(s) · 🔗 {} · 🖼 {} image(s)",
+                    preview.hashtags,
+                    if preview.link_card { "link card" } else { "no link card" },
+                    preview.images
+                ));
+            }
+        });
+    }
+
+    /// With the composer empty and a URL on the clipboard, offers to start a post about the link: the URL goes in
+    /// at once, and the page's title is put above it when it has been fetched
+    fn offer_clipboard_url(&mut self, ui: &mut egui::Ui, state: &mut posts::AppState) {
+        if self.last_clipboard_check.elapsed() >= CLIPBOARD_CHECK_INTERVAL {
+            self.last_clipboard_check = std::time::Instant::now();
+            self.clipboard_url = clipboard_url();
+        }
+        ui.ctx().request_repaint_after(CLIPBOARD_CHECK_INTERVAL);
+        let Some(url) = self.clipboard_url.clone() else {
+            return;
+        };
+        if !ui
+            .button(format!("📋 Compose about {}", url))
+            .on_hover_text("Start a post with this link and its page title")
+            .clicked()
+        {
+            return;
+        }
+
+        state.post_text = url.clone();
+        let task_state = Arc::clone(&self.state);
+        self.rt.spawn(async move {
+            let Some(preview) = links::fetch_preview(&url).await else {
+                return;
+            };
+            let mut state = task_state.lock().await;
+            // Leave the text alone if it was edited while the page loaded
+            if state.post_text == url {
+                state.post_text = format!("{}\n\n{}", preview.title, url);
+            }
+        });
+    }
+
+    /// Handles for the mention being typed at the end of the composed text, from the selected platforms.  The lookup
+    /// waits for a pause in typing so every keystroke doesn't cost a request.
+    fn render_mention_suggestions(&mut self, ui: &mut egui::Ui, state: &mut posts::AppState) {
+        let Some(query) = autocomplete::typing(&state.post_text, '@').map(str::to_string) else {
+            self.mention_query.clear();
+            return;
+        };
+        if query != self.mention_query {
+            self.mention_query = query.clone();
+            self.mention_typed_at = std::time::Instant::now();
+            self.mention_searched = false;
+        }
+        if !self.mention_searched {
+            if self.mention_typed_at.elapsed() < MENTION_LOOKUP_DELAY {
+                ui.ctx().request_repaint_after(MENTION_LOOKUP_DELAY);
+                return;
+            }
+            self.mention_searched = true;
+            let platforms: Vec<String> = self
+                .targets(state)
+                .into_iter()
+                .filter(|platform| is_authorized(state, platform))
+                .map(str::to_string)
+                .collect();
+            let bluesky_token = state.bluesky_token.clone();
+            let task_state = Arc::clone(&self.state);
+            self.rt.spawn(async move {
+                let suggestions = autocomplete::mentions(&query, &platforms, bluesky_token.as_deref()).await;
+                task_state.lock().await.mention_suggestions = Some((query, suggestions));
+            });
+            return;
+        }
+
+        let Some((_, suggestions)) = state
+            .mention_suggestions
+            .as_ref()
+            .filter(|(found_for, _)| *found_for == query)
+        else {
+            return;
+        };
+        let mut picked = None;
+        ui.horizontal_wrapped(|ui| {
+            for suggestion in suggestions {
+                let label = format!("{} · {}", suggestion.handle, suggestion.platform);
+                if ui.small_button(label).on_hover_text(&suggestion.name).clicked() {
+                    picked = Some(suggestion.handle.clone());
+                }
+            }
+        });
+        if let Some(handle) = picked {
+            autocomplete::complete(&mut state.post_text, '@', &handle);
+            state.mention_suggestions = None;
+        }
+    }
+
+    /// Hashtags for the one being typed at the end of the composed text: ones used before, and optionally ones
+    /// trending on Mastodon (refreshed every `TRENDING_TAGS_INTERVAL`)
+    fn render_hashtag_suggestions(&mut self, ui: &mut egui::Ui, state: &mut posts::AppState) {
+        let Some(query) = autocomplete::typing(&state.post_text, '#').map(str::to_string) else {
+            return;
+        };
+        #[cfg(feature = "mastodon")]
+        if settings::get().trending_hashtags
+            && self
+                .trending_fetched_at
+                .map_or(true, |fetched| fetched.elapsed() >= TRENDING_TAGS_INTERVAL)
+        {
+            self.trending_fetched_at = Some(std::time::Instant::now());
+            let task_state = Arc::clone(&self.state);
+            self.rt.spawn(async move {
+                let tags = mastodon::fetch_trending_tags(&mastodon::api_base_url(), mastodon::software()).await;
+                task_state.lock().await.trending_tags = tags;
+            });
+        }
+        let trending = if settings::get().trending_hashtags {
+            state.trending_tags.as_slice()
+        } else {
+            &[]
+        };
+
+        let mut picked = None;
+        ui.horizontal_wrapped(|ui| {
+            for tag in autocomplete::hashtags(&query, &state.history, trending) {
+                if ui.small_button(format!("#{}", tag)).clicked() {
+                    picked = Some(tag);
+                }
+            }
+        });
+        if let Some(tag) = picked {
+            autocomplete::complete(&mut state.post_text, '#', &format!("#{}", tag));
+        }
+    }
+
+    /// Replaces the composed text with the newest item of the feed at `feed_url`, its HTML converted to plain text
+    fn import_feed_item(&mut self) {
+        let url = self.feed_url.trim().to_string();
+        let task_state = Arc::clone(&self.state);
+        self.rt.spawn(async move {
+            let item = feeds::latest_item(&url).await;
+            let mut state = task_state.lock().await;
+            match item {
+                Some(item) => {
+                    info!(url, title = %item.title, "Imported feed item");
+                    state.post_text = item.compose();
+                    state.compose_key = history::new_idempotency_key();
+                    state.post_progress.clear();
+                }
+                None => state.toast("Couldn't read an item from that feed", false),
+            }
+        });
+    }
+
+    /// The further posts of a thread written post by post, each with its own text, length and images, and controls to
+    /// reorder and remove them.  Moving the first of them up swaps it with the composed text.
+    fn render_thread(&mut self, ui: &mut egui::Ui, state: &mut posts::AppState, selected: &[&'static str]) {
+        enum Edit {
+            Up(usize),
+            Down(usize),
+            Remove(usize),
+        }
+
+        let count = state.compose_options.thread.len();
+        self.thread_new_images.resize_with(count, Default::default);
+        // The tightest limit among the selected platforms is the one each post has to fit
+        let limit = selected.iter().map(|platform| state.limits(platform).max_chars).min();
+        let numbering = limits::thread_numbering();
+        let mut edit = None;
+        for (i, segment) in state.compose_options.thread.iter_mut().enumerate() {
+            ui.group(|ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("🧵 Post {} of {}", i + 2, count + 1));
+                    if icon_button(ui, "⬆", "Move up").clicked() {
+                        edit = Some(Edit::Up(i));
+                    }
+                    let down = ui.add_enabled(i + 1 < count, egui::Button::new("⬇").small());
+                    if accessible(down, "Move down").on_hover_text("Move down").clicked() {
+                        edit = Some(Edit::Down(i));
+                    }
+                    if icon_button(ui, "✖", "Remove from the thread").clicked() {
+                        edit = Some(Edit::Remove(i));
+                    }
+                });
+                let text = ui.add_enabled(
+                    !state.posting,
+                    egui::TextEdit::multiline(&mut segment.text).layouter(&mut highlight_entities),
+                );
+                accessible(text, &format!("Post {} of the thread", i + 2));
+                if let Some(limit) = limit {
+                    // Counted with its number, which changes as posts are added or removed
+                    let length = limits::count(&numbering.apply(&segment.text, i + 2, count + 1));
+                    let color = if length > limit {
+                        egui::Color32::RED
+                    } else {
+                        ui.visuals().weak_text_color()
+                    };
+                    ui.colored_label(color, format!("{}/{}", length, limit));
+                }
+                render_attachments(ui, &mut segment.images, &mut self.thread_new_images[i]);
+            });
+        }
+
+        let thread = &mut state.compose_options.thread;
+        match edit {
+            Some(Edit::Up(0)) => {
+                std::mem::swap(&mut state.post_text, &mut thread[0].text);
+                std::mem::swap(&mut state.compose_options.images, &mut thread[0].images);
+            }
+            Some(Edit::Up(i)) => {
+                thread.swap(i - 1, i);
+                self.thread_new_images.swap(i - 1, i);
+            }
+            Some(Edit::Down(i)) => {
+                thread.swap(i, i + 1);
+                self.thread_new_images.swap(i, i + 1);
+            }
+            Some(Edit::Remove(i)) => {
+                thread.remove(i);
+                self.thread_new_images.remove(i);
+            }
+            None => {}
+        }
+
+        let label = if count == 0 {
+            "🧵 Start a thread"
+        } else {
+            "➕ Add a post"
+        };
+        ui.horizontal(|ui| {
+            if ui
+                .small_button(label)
+                .on_hover_text("Write the next post of a thread in its own box")
+                .clicked()
+            {
+                state.compose_options.thread.push(posts::ThreadSegment::default());
+            }
+            if count > 0 {
+                render_thread_numbering(ui, &mut self.settings_draft);
+            }
+        });
+    }
+
+    /// Saves the composer's post or thread, attachments included, as a draft, and lists the saved ones to resume.
+    /// Resuming a draft replaces what is in the composer and takes it off the list.
+    fn render_drafts(&mut self, ui: &mut egui::Ui, state: &mut posts::AppState) {
+        let empty = state.post_text.trim().is_empty() && state.compose_options.thread.is_empty();
+        if ui
+            .add_enabled(!state.posting && !empty, egui::Button::new("💾 Save draft"))
+            .on_hover_text("Put this post or thread aside, with its images, and clear the composer")
+            .clicked()
+        {
+            self.drafts.push(drafts::Draft {
+                saved_at: chrono::Utc::now(),
+                text: std::mem::take(&mut state.post_text),
+                options: std::mem::take(&mut state.compose_options),
+                targets: state.compose_targets.take(),
+            });
+            drafts::save_drafts(&self.drafts);
+            state.compose_key = history::new_idempotency_key();
+            state.post_progress.clear();
+            self.thread_new_images.clear();
+            info!(drafts = self.drafts.len(), "Saved draft");
+        }
+
+        let mut resumed = None;
+        let mut deleted = None;
+        for (i, draft) in self.drafts.iter().enumerate().rev() {
+            ui.horizontal(|ui| {
+                ui.label(format!("📝 {}", draft.summary()))
+                    .on_hover_text(format!("Saved {}", timezone::format(draft.saved_at)));
+                if ui
+                    .add_enabled(!state.posting, egui::Button::new("Resume"))
+                    .on_hover_text("Replaces what is in the composer")
+                    .clicked()
+                {
+                    resumed = Some(i);
+                }
+                if icon_button(ui, "✖", "Delete draft").clicked() {
+                    deleted = Some(i);
+                }
+            });
+        }
+        if let Some(i) = resumed {
+            let draft = self.drafts.remove(i);
+            drafts::save_drafts(&self.drafts);
+            self.per_platform = !draft.options.texts.is_empty();
+            self.thread_new_images.clear();
+            state.post_text = draft.text;
+            state.compose_options = draft.options;
+            state.compose_targets = draft.targets;
+            state.compose_key = history::new_idempotency_key();
+            state.post_progress.clear();
+        } else if let Some(i) = deleted {
+            self.drafts.remove(i);
+            drafts::save_drafts(&self.drafts);
+        }
+    }
+
+    /// Threads that stopped partway on a platform, with buttons to finish them from the first post that wasn't made
+    /// or to leave them as they are.
+    fn render_unfinished_threads(&self, ui: &mut egui::Ui, state: &mut posts::AppState) {
+        for thread in threads::load_unfinished() {
+            ui.horizontal(|ui| {
+                ui.colored_label(
+                    egui::Color32::YELLOW,
+                    format!(
+                        "🧵 Thread on {} stopped at post {} of {}",
+                        thread.platform,
+                        thread.next_post(),
+                        thread.parts.len()
+                    ),
+                )
+                .on_hover_text(format!("Stopped {}", timezone::format(thread.stopped_at)));
+                if ui
+                    .add_enabled(
+                        !state.posting,
+                        egui::Button::new(format!("▶ Resume from post {}", thread.next_post())),
+                    )
+                    .on_hover_text("Post the rest as replies to the posts already made")
+                    .clicked()
+                {
+                    state.posting = true;
+                    let task_state = Arc::clone(&self.state);
+                    let thread = thread.clone();
+                    self.rt.spawn(async move {
+                        publish::resume_thread(&task_state, thread).await;
+                        task_state.lock().await.posting = false;
+                    });
+                }
+                if ui
+                    .button("Discard")
+                    .on_hover_text("Leave the thread unfinished")
+                    .clicked()
+                {
+                    threads::remove(&thread.platform, &thread.idempotency_key);
+                    info!(platform = %thread.platform, "Discarded unfinished thread");
+                }
+            });
+        }
+    }
+
+    /// Instructions, where the post will go, and the posting caps and rate limits in use
+    fn render_note_panel(&mut self, ui: &mut egui::Ui, state: &Arc<Mutex<posts::AppState>>) {
+        if settings::get().show_instructions {
+            let mut dismissed = false;
+            egui::CollapsingHeader::new(egui::RichText::new("📝 Instructions").color(egui::Color32::GREEN))
+                .default_open(true)
+                .show(ui, |ui| {
+                    ui.label("1. Authorize the platforms you want to use.\n2. Check the boxes for the platforms you want to post to.\n3. Write your message and click 'Post.'\n\nKeep posts to under 5 every 15 minutes to avoid rate limiting.");
+                    dismissed = ui
+                        .small_button("Don't show again")
+                        .on_hover_text("They can be shown again under Settings")
+                        .clicked();
+                });
+            if dismissed {
+                // Saved straight away, like the over-limit choice
+                let mut saved = settings::get();
+                saved.show_instructions = false;
+                settings::update(saved);
+                self.settings_draft.show_instructions = false;
+                info!("Hid the instructions");
+            }
+
+            ui.add_space(20.0);
+        }
+
+        ui.heading(egui::RichText::new("📋 It will post to:").color(egui::Color32::GREEN));
+        let state = futures::executor::block_on(state.lock());
+
+        let mut targets = self.targets(&state);
+        targets.sort();
+        for platform in targets {
+            if is_authorized(&state, platform) {
+                ui.label(format!("- {}", platform));
+            }
+        }
+
+        // Usage of the platforms that have a posting cap
+        let caps = settings::get().posting_caps;
+        if !caps.is_empty() {
+            ui.add_space(20.0);
+            ui.heading(egui::RichText::new("📊 Posting caps:").color(egui::Color32::GREEN));
+            let mut capped: Vec<_> = caps.into_iter().collect();
+            capped.sort_by(|a, b| a.0.cmp(&b.0));
+            for (platform, cap) in capped {
+                let (last_hour, last_day) = budget::usage(&state.history, &platform);
+                let mut parts = Vec::new();
+                if let Some(per_hour) = cap.per_hour {
+                    parts.push(format!("{}/{} this hour", last_hour, per_hour));
+                }
+                if let Some(per_day) = cap.per_day {
+                    parts.push(format!("{}/{} in 24h", last_day, per_day));
+                }
+                let full =
+                    cap.per_hour.is_some_and(|cap| last_hour >= cap) || cap.per_day.is_some_and(|cap| last_day >= cap);
+                let color = if full {
+                    egui::Color32::RED
+                } else {
+                    ui.visuals().text_color()
+                };
+                ui.colored_label(color, format!("{}: {}", platform, parts.join(", ")));
+            }
+        }
+
+        // Request budgets the platforms reported with their last response
+        let budgets = ratelimit::all();
+        if !budgets.is_empty() {
+            ui.add_space(20.0);
+            ui.heading(egui::RichText::new("⏳ Rate limits:").color(egui::Color32::GREEN));
+            for (platform, budget) in budgets {
+                let quota = match budget.limit {
+                    Some(limit) => format!("{}/{}", budget.remaining, limit),
+                    None => budget.remaining.to_string(),
+                };
+                let color = if budget.remaining == 0 {
+                    egui::Color32::RED
+                } else {
+                    ui.visuals().text_color()
+                };
+                ui.colored_label(
+                    color,
+                    format!(
+                        "{}: {} left, resets {}",
+                        platform,
+                        quota,
+                        timezone::format(budget.reset)
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Applies the theme and motion settings when they change
+    fn update_theme(&mut self, ctx: &egui::Context) {
+        let settings = settings::get();
+        let appearance = (settings.theme, settings.reduced_motion);
+        if self.applied_appearance != Some(appearance) {
+            theme::apply(ctx, appearance.0, appearance.1);
+            self.applied_appearance = Some(appearance);
+        }
+    }
+
+    /// Shows the unread notification count in the window title, so it can be noticed in the taskbar
+    fn update_title(&mut self, ctx: &egui::Context) {
+        let unread = inbox::unread(&futures::executor::block_on(self.state.lock()).inbox);
+        if unread == self.shown_unread {
+            return;
+        }
+        self.shown_unread = unread;
+        let title = if unread > 0 {
+            format!("Multique ({} unread)", unread)
+        } else {
+            "Multique".to_string()
+        };
+        ctx.send_viewport_cmd(egui::ViewportCommand::Title(title));
+    }
+
+    /// Periodically writes the composer text to the recovery file so it survives a crash
+    fn autosave(&mut self) {
+        if self.last_autosave.elapsed() < recovery::AUTOSAVE_INTERVAL {
+            return;
+        }
+        self.last_autosave = std::time::Instant::now();
+
+        let text = futures::executor::block_on(self.state.lock()).post_text.clone();
+        if text != self.autosaved_text {
+            recovery::save(&text);
+            self.autosaved_text = text;
+        }
+        self.save_platform_selection();
+    }
+
+    /// Remembers the checked platforms in the active profile's config when they change, to restore them next time
+    fn save_platform_selection(&mut self) {
+        let selection = (profiles::active(), checked_platforms(&self.platform_checkboxes));
+        if selection == self.saved_selection {
+            return;
+        }
+        // Right after a profile switch the checkboxes are that profile's own; there's nothing new to save
+        if selection.0 == self.saved_selection.0 {
+            let mut config = profiles::load_config();
+            config.last_platforms = Some(selection.1.clone());
+            profiles::save_config(&config);
+        }
+        self.saved_selection = selection;
+    }
+}
+
+impl eframe::App for PostApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let available_width = ctx.available_rect().width();
+        let state_clone = Arc::clone(&self.state);
+        self.autosave();
+        self.update_theme(ctx);
+        ctx.request_repaint_after(recovery::AUTOSAVE_INTERVAL); // So the last keystrokes get saved without more input
+        let (offline, outbox_count) = {
+            let state = futures::executor::block_on(state_clone.lock());
+            (state.offline, state.outbox_count)
+        };
+        if offline {
+            egui::TopBottomPanel::top("offline_banner").show(ctx, |ui| {
+                ui.colored_label(
+                    egui::Color32::RED,
+                    format!(
+                        "⚠ Offline — {} post(s) held in the outbox. They'll be sent automatically when connectivity returns.",
+                        outbox_count
+                    ),
+                );
+            });
+            ctx.request_repaint_after(std::time::Duration::from_secs(1));
+        }
+        render_toasts(ctx, &state_clone);
+        self.update_title(ctx);
+        // A post still in flight when its window closes carries on; only its result goes unseen
+        for composer in &mut self.composers {
+            composer.show(ctx, &self.state, &self.rt);
+        }
+        self.composers.retain(|composer| composer.open);
+        egui::TopBottomPanel::bottom("log_panel")
+            .resizable(true)
+            .show(ctx, |ui| {
+                render_log_panel(ui, &self.log_buffer, &mut self.log_level);
+            });
+        let (position, share) = {
+            let settings = settings::get();
+            (
+                settings.notes_panel,
+                settings.notes_panel_share.clamp(layout::MIN_SHARE, layout::MAX_SHARE),
+            )
+        };
+        match position {
+            layout::PanelPosition::Right => {
+                egui::SidePanel::right("note_panel")
+                    .exact_width(available_width * share)
+                    .show(ctx, |ui| self.render_note_panel(ui, &state_clone));
+            }
+            layout::PanelPosition::Left => {
+                egui::SidePanel::left("note_panel")
+                    .exact_width(available_width * share)
+                    .show(ctx, |ui| self.render_note_panel(ui, &state_clone));
+            }
+            layout::PanelPosition::Bottom => {
+                egui::TopBottomPanel::bottom("note_panel")
+                    .exact_height(ctx.available_rect().height() * share)
+                    .show(ctx, |ui| {
+                        egui::ScrollArea::vertical().show(ui, |ui| self.render_note_panel(ui, &state_clone));
+                    });
+            }
+            layout::PanelPosition::Hidden => {}
+        }
+        let main_section_width = if position.is_beside() {
+            available_width * (1.0 - share)
+        } else {
+            available_width
+        };
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.set_min_width(main_section_width);
+            ui.heading("🌟 Multique - Post to all the platforms!");
+
+            ui.add_space(20.0);
+            self.render_profile_bar(ui);
+
+            // Platform Selection Section
+            ui.group(|ui| {
+                ui.set_min_width(400.0);
+                ui.label("Platforms:");
+                self.render_presets(ui);
+
+                #[cfg(feature = "twitter")]
+                render_platform_checkbox(
+                    ui,
+                    "🐦 Twitter / X:",
+                    "Twitter",
+                    &mut self.platform_checkboxes,
+                    |state| state.twitter_authorized,
+                    || {
+                        let rt = Arc::clone(&self.rt);
+                        let state_clone = Arc::clone(&self.state);
+                        rt.spawn(async move {
+                            if let Some(auth_url) = twitter::generate_auth_url().await {
+                                println!("Authorize your app at: {}", auth_url);
+
+                                println!("Enter the authorization code:");
+                                let mut input_code = String::new();
+                                std::io::stdin().read_line(&mut input_code).unwrap();
+                                let code = input_code.trim().to_string();
+
+                                if twitter::authorize_twitter(state_clone.clone(), &code).await.is_some() {
+                                    let mut state = state_clone.lock().await;
+                                    state.twitter_authorized = true;
+                                }
+                            }
+                        });
+                    },
+                    state_clone.clone(),
+                );
+
+                #[cfg(feature = "bluesky")]
+                render_platform_checkbox(
+                    ui,
+                    "🐦 Bluesky:",
+                    "Bluesky",
+                    &mut self.platform_checkboxes,
+                    |state| state.bluesky_authorized,
+                    || {
+                        let rt = Arc::clone(&self.rt);
+                        let state_clone = Arc::clone(&self.state);
+                        rt.spawn(async move {
+                            if bluesky::authorize_bluesky(state_clone.clone()).await.is_some() {
+                                let mut state = state_clone.lock().await;
+                                state.bluesky_authorized = true;
+                            }
+                        });
+                    },
+                    state_clone.clone(),
+                );
+
+                #[cfg(feature = "mastodon")]
+                render_platform_checkbox(
+                    ui,
+                    "🐘 Mastodon:",
+                    "Mastodon",
+                    &mut self.platform_checkboxes,
+                    |state| state.mastodon_authorized,
+                    || {
+                        let rt = Arc::clone(&self.rt);
+                        let state_clone = Arc::clone(&self.state);
+                        rt.spawn(async move {
+                            let client_id =
+                                std::env::var("MASTODON_CLIENT_ID").expect("MASTODON_CLIENT_ID not set in .env");
+                            let client_secret = std::env::var("MASTODON_CLIENT_SECRET")
+                                .expect("MASTODON_CLIENT_SECRET not set in .env");
+
+                            let base_url = mastodon::api_base_url();
+                            let authorization_url =
+                                mastodon::generate_auth_url(&base_url, &client_id, mastodon::software()).await;
+                            println!("Authorize your app at: {}", authorization_url);
+
+                            println!("Enter the authorization code:");
+                            let mut input_code = String::new();
+                            std::io::stdin().read_line(&mut input_code).unwrap();
+                            let code = input_code.trim().to_string();
+
+                            if let Some(access_token) =
+                                mastodon::authorize_mastodon(&base_url, &client_id, &client_secret, &code).await
+                            {
+                                mastodon::save_tokens(&access_token);
+                                let mut state = state_clone.lock().await;
+                                state.mastodon_authorized = true;
+                            }
+                        });
+                    },
+                    state_clone.clone(),
+                );
+
+                #[cfg(feature = "mastodon")]
+                self.render_mastodon_accounts(ui);
+
+                #[cfg(feature = "linkedin")]
+                render_platform_checkbox(
+                    ui,
+                    "🔗 LinkedIn:",
+                    "LinkedIn",
+                    &mut self.platform_checkboxes,
+                    |state| state.linkedin_authorized,
+                    || {
+                        let rt = Arc::clone(&self.rt);
+                        let state_clone = Arc::clone(&self.state);
+                        rt.spawn(async move {
+                            if let Some(auth_url) = linkedin::generate_auth_url().await {
+                                println!("Authorize your app at: {}", auth_url);
+
+                                println!("Enter the authorization code:");
+                                let mut input_code = String::new();
+                                std::io::stdin().read_line(&mut input_code).unwrap();
+                                let code = input_code.trim().to_string();
+
+                                if linkedin::authorize_linkedin(state_clone.clone(), &code).await.is_some() {
+                                    let mut state = state_clone.lock().await;
+                                    state.linkedin_authorized = true;
+                                }
+                            }
+                        });
+                    },
+                    state_clone.clone(),
+                );
+
+                #[cfg(feature = "medium")]
+                render_platform_checkbox(
+                    ui,
+                    "Ⓜ Medium:",
+                    "Medium",
+                    &mut self.platform_checkboxes,
+                    |state| state.medium_authorized,
+                    || {
+                        let state_clone = Arc::clone(&self.state);
+                        self.rt.spawn(async move {
+                            println!("Create an integration token under Medium's Settings → Security and apps");
+                            println!("Enter the integration token:");
+                            let mut input_token = String::new();
+                            std::io::stdin().read_line(&mut input_token).unwrap();
+
+                            let authorized = medium::authorize_medium(&input_token).await;
+                            let mut state = state_clone.lock().await;
+                            state.medium_authorized = authorized;
+                            if !authorized {
+                                state.toast("Medium didn't accept the integration token", false);
+                            }
+                        });
+                    },
+                    state_clone.clone(),
+                );
+
+                #[cfg(feature = "hashnode")]
+                render_platform_checkbox(
+                    ui,
+                    "Ⓗ Hashnode:",
+                    "Hashnode",
+                    &mut self.platform_checkboxes,
+                    |state| state.hashnode_authorized,
+                    || {
+                        let state_clone = Arc::clone(&self.state);
+                        self.rt.spawn(async move {
+                            println!("Create a personal access token under Hashnode's Account settings → Developer");
+                            println!("Enter the access token:");
+                            let mut input_token = String::new();
+                            std::io::stdin().read_line(&mut input_token).unwrap();
+
+                            let authorized = hashnode::authorize_hashnode(&input_token).await;
+                            let mut state = state_clone.lock().await;
+                            state.hashnode_authorized = authorized;
+                            if !authorized {
+                                state.toast("Hashnode didn't accept the token, or it has no publication", false);
+                            }
+                        });
+                    },
+                    state_clone.clone(),
+                );
+
+                // Email is set up under Settings rather than authorized
+                #[cfg(feature = "email")]
+                render_platform_checkbox(
+                    ui,
+                    "✉ Email:",
+                    "Email",
+                    &mut self.platform_checkboxes,
+                    |_| email::is_configured(),
+                    || {
+                        let state_clone = Arc::clone(&self.state);
+                        self.rt.spawn(async move {
+                            state_clone
+                                .lock()
+                                .await
+                                .toast("Set up the Email platform under Settings", false);
+                        });
+                    },
+                    state_clone.clone(),
+                );
+
+                // The demo platform needs no credentials, so it is always authorized
+                #[cfg(feature = "demo")]
+                render_platform_checkbox(
+                    ui,
+                    "🧪 Demo:",
+                    "Demo",
+                    &mut self.platform_checkboxes,
+                    |_| true,
+                    || {},
+                    state_clone.clone(),
+                );
+
+                #[cfg(feature = "plugins")]
+                for plugin in plugins::all() {
+                    render_platform_checkbox(
+                        ui,
+                        &format!("🧩 {}:", plugin.name),
+                        plugin.name,
+                        &mut self.platform_checkboxes,
+                        |state| state.plugins_authorized.contains(plugin.name),
+                        || {
+                            let state_clone = Arc::clone(&self.state);
+                            self.rt.spawn(async move {
+                                let Some(auth_url) = plugin.auth_url().await else {
+                                    return;
+                                };
+                                println!("Authorize your app at: {}", auth_url);
+
+                                println!("Enter the authorization code:");
+                                let mut input_code = String::new();
+                                std::io::stdin().read_line(&mut input_code).unwrap();
+                                let code = input_code.trim().to_string();
+
+                                if plugin.authorize(&code).await {
+                                    let mut state = state_clone.lock().await;
+                                    state.plugins_authorized.insert(plugin.name.to_string());
+                                }
+                            });
+                        },
+                        state_clone.clone(),
+                    );
+                }
+            });
+
+            ui.add_space(20.0);
+
+            // Compose and Post Section
+            ui.group(|ui| {
+                ui.set_min_width(400.0);
+                ui.horizontal(|ui| {
+                    ui.label("Compose your message:");
+                    if ui
+                        .small_button("🗗 New window")
+                        .on_hover_text("Compose another post in a separate window")
+                        .clicked()
+                    {
+                        self.composers.push(Composer::new(self.next_composer_id));
+                        self.next_composer_id += 1;
+                    }
+                });
+                let mut state = futures::executor::block_on(state_clone.lock());
+                let posting = state.posting;
+
+                if state.post_text.is_empty() {
+                    self.offer_clipboard_url(ui, &mut state);
+                }
+
+                ui.horizontal(|ui| {
+                    accessible(
+                        ui.add(egui::TextEdit::singleline(&mut self.feed_url).hint_text("Blog feed URL (RSS or Atom)")),
+                        "Blog feed URL",
+                    );
+                    if ui
+                        .add_enabled(
+                            !posting && !self.feed_url.trim().is_empty(),
+                            egui::Button::new("📰 Import"),
+                        )
+                        .on_hover_text("Start a post from the feed's newest item, converted to plain text")
+                        .clicked()
+                    {
+                        self.import_feed_item();
+                    }
+                });
+
+                // Release announcements drafted by the GitHub watcher
+                let mut used_draft = None;
+                let mut dismissed_draft = None;
+                for (i, draft) in state.release_drafts.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("📦 New release of {}", draft.repo));
+                        if ui.add_enabled(!posting, egui::Button::new("Use")).clicked() {
+                            used_draft = Some(i);
+                        }
+                        if ui.button("Dismiss").clicked() {
+                            dismissed_draft = Some(i);
+                        }
+                    });
+                }
+                if let Some(i) = used_draft {
+                    let draft = state.release_drafts.remove(i);
+                    state.post_text = draft.text;
+                    state.compose_key = history::new_idempotency_key();
+                    state.post_progress.clear();
+                } else if let Some(i) = dismissed_draft {
+                    state.release_drafts.remove(i);
+                }
+
+                self.render_drafts(ui, &mut state);
+                self.render_unfinished_threads(ui, &mut state);
+
+                // With several accounts, chips pick the ones this post goes to without touching the checkboxes
+                let mut accounts: Vec<&'static str> = self
+                    .platform_checkboxes
+                    .keys()
+                    .copied()
+                    .filter(|platform| is_authorized(&state, platform))
+                    .collect();
+                accounts.sort();
+                if accounts.len() > 1 {
+                    let targets = self.targets(&state);
+                    let overridden = state.compose_targets.is_some();
+                    let mut toggled = None;
+                    let mut reset = false;
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label("To:");
+                        for platform in &accounts {
+                            if ui.selectable_label(targets.contains(platform), *platform).clicked() {
+                                toggled = Some(*platform);
+                            }
+                        }
+                        if overridden && icon_button(ui, "↺", "Back to the checked platforms").clicked() {
+                            reset = true;
+                        }
+                    });
+                    if let Some(platform) = toggled {
+                        let mut picked: Vec<String> = targets.iter().map(|target| target.to_string()).collect();
+                        match picked.iter().position(|target| target == platform) {
+                            Some(i) => {
+                                picked.remove(i);
+                            }
+                            None => picked.push(platform.to_string()),
+                        }
+                        state.compose_targets = Some(picked);
+                    } else if reset {
+                        state.compose_targets = None;
+                    }
+                }
+
+                let text = ui.add_enabled(
+                    !posting,
+                    egui::TextEdit::multiline(&mut state.post_text).layouter(&mut highlight_entities),
+                );
+                accessible(text, "Post text");
+                self.render_mention_suggestions(ui, &mut state);
+                self.render_hashtag_suggestions(ui, &mut state);
+
+                let writing_stats = stats::analyze(&state.post_text);
+                ui.colored_label(
+                    ui.visuals().weak_text_color(),
+                    format!(
+                        "{} word(s) · {} sentence(s) · ~{} read",
+                        writing_stats.words,
+                        writing_stats.sentences,
+                        format_reading_time(writing_stats.reading_time_secs)
+                    ),
+                );
+
+                if ui
+                    .checkbox(&mut self.per_platform, "✏ Customize per platform")
+                    .on_hover_text("Edit the text and options for each platform in its own tab")
+                    .changed()
+                    && !self.per_platform
+                {
+                    // Turning it off goes back to the same post everywhere
+                    state.compose_options = posts::PostOptions {
+                        link_previews: state.compose_options.link_previews,
+                        images: std::mem::take(&mut state.compose_options.images),
+                        thread: std::mem::take(&mut state.compose_options.thread),
+                        ..Default::default()
+                    };
+                }
+
+                let mut selected = self.targets(&state);
+                selected.sort();
+                if self.per_platform {
+                    self.render_platform_tabs(ui, &mut state, &selected);
+                } else {
+                    // Character counter for each selected platform, against its (possibly server-detected) limit,
+                    // with the choice of what to do when the text doesn't fit
+                    let length = limits::count(&state.post_text);
+                    for platform in &selected {
+                        render_length(ui, platform, length, state.limits(platform), &mut self.settings_draft);
+                    }
+                }
+                if settings::get().profanity_filter {
+                    render_profanity(ui, &mut state);
+                }
+
+                if selected.len() > 1 {
+                    ui.checkbox(&mut self.compare, "⇆ Compare platforms")
+                        .on_hover_text("Show what two platforms will get side by side");
+                    if self.compare {
+                        self.render_preview_comparison(ui, &state, &selected);
+                    }
+                }
+
+                render_attachments(ui, &mut state.compose_options.images, &mut self.new_image);
+                self.render_thread(ui, &mut state, &selected);
+
+                if !self.per_platform {
+                    render_link_previews(ui, &mut state.compose_options);
+                }
+                render_expiry(ui, &mut state.compose_options);
+
+                let checking = state.linting || state.lint_warnings.is_some();
+                ui.horizontal(|ui| {
+                    let post_clicked = ui
+                        .add_enabled(
+                            !posting && !checking && !state.post_text.trim().is_empty(),
+                            egui::Button::new("📤 Post").fill(egui::Color32::DARK_GRAY),
+                        )
+                        .clicked();
+                    if posting {
+                        theme::busy(ui, settings::get().reduced_motion);
+                        ui.label("Posting…");
+                        ui.ctx().request_repaint_after(std::time::Duration::from_millis(100));
+                    } else if state.linting {
+                        theme::busy(ui, settings::get().reduced_motion);
+                        ui.label("Checking…");
+                        ui.ctx().request_repaint_after(std::time::Duration::from_millis(100));
+                    }
+
+                    if post_clicked {
+                        self.lint_post(&mut state);
+                    }
+                });
+                if !state.linting {
+                    self.render_lint_warnings(ui, &mut state);
+                }
+
+                let queue_clicked = ui
+                    .horizontal(|ui| {
+                        ui.label("Queue:");
+                        let before = self.selected_queue.clone();
+                        egui::ComboBox::from_id_salt("compose_queue")
+                            .selected_text(self.selected_queue.as_deref().unwrap_or("None"))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut self.selected_queue, None, "None");
+                                for queue in queues::load_queues() {
+                                    ui.selectable_value(&mut self.selected_queue, Some(queue.name.clone()), queue.name);
+                                }
+                            });
+                        if self.selected_queue != before {
+                            self.apply_queue_platforms();
+                        }
+                        ui.add_enabled(
+                            !posting && !state.post_text.trim().is_empty() && self.selected_queue.is_some(),
+                            egui::Button::new("📥 Add to queue"),
+                        )
+                        .on_hover_text("Schedule for the queue's next free slot")
+                        .clicked()
+                    })
+                    .inner;
+                if queue_clicked {
+                    self.add_to_queue(&mut state);
+                }
+
+                let schedule_clicked = ui
+                    .horizontal(|ui| {
+                        ui.label(format!("Schedule for ({}):", timezone::label()));
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.schedule_input)
+                                .hint_text("YYYY-MM-DD HH:MM")
+                                .desired_width(140.0),
+                        );
+                        egui::ComboBox::from_id_salt("schedule_repeat")
+                            .selected_text(self.schedule_repeat.map_or("Once", |f| f.label()))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut self.schedule_repeat, None, "Once");
+                                for frequency in scheduler::Frequency::ALL {
+                                    ui.selectable_value(&mut self.schedule_repeat, Some(frequency), frequency.label());
+                                }
+                            });
+                        ui.add_enabled(
+                            !posting && !state.post_text.trim().is_empty() && !self.schedule_input.trim().is_empty(),
+                            egui::Button::new("🕒 Schedule"),
+                        )
+                        .clicked()
+                    })
+                    .inner;
+                if self.schedule_repeat.is_some() {
+                    ui.horizontal(|ui| {
+                        ui.label("Occurrences:");
+                        ui.add(egui::DragValue::new(&mut self.schedule_count).range(0..=1000))
+                            .on_hover_text("0 repeats until the end date, or forever");
+                        ui.label("Until:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.schedule_until)
+                                .hint_text("YYYY-MM-DD HH:MM (optional)")
+                                .desired_width(180.0),
+                        );
+                    });
+                }
+                if schedule_clicked {
+                    self.schedule_post(&mut state);
+                }
+
+                for (platform, progress) in &state.post_progress {
+                    let (icon, color) = match progress {
+                        posts::PostProgress::Pending => ("⏳", ui.visuals().text_color()),
+                        posts::PostProgress::Succeeded => ("✅", egui::Color32::GREEN),
+                        posts::PostProgress::Failed => ("❌", egui::Color32::RED),
+                    };
+                    ui.colored_label(color, format!("{} {}", icon, platform));
+                }
+            });
+
+            ui.add_space(20.0);
+
+            // Inbox Section
+            ui.group(|ui| {
+                ui.set_min_width(400.0);
+                let unread = inbox::unread(&futures::executor::block_on(self.state.lock()).inbox);
+                let title = if unread > 0 {
+                    egui::RichText::new(format!("📥 Inbox ({})", unread)).color(egui::Color32::LIGHT_RED)
+                } else {
+                    egui::RichText::new("📥 Inbox")
+                };
+                egui::CollapsingHeader::new(title).id_salt("inbox").show(ui, |ui| {
+                    render_inbox(ui, &self.rt, &self.state, &mut self.inbox_reply);
+                });
+            });
+
+            ui.add_space(20.0);
+
+            // Scheduled Posts Section
+            ui.group(|ui| {
+                ui.set_min_width(400.0);
+                let pause = futures::executor::block_on(self.state.lock()).schedule_pause.clone();
+                let title = if pause.is_some() {
+                    egui::RichText::new("🕒 Scheduled (⏸ paused)").color(egui::Color32::YELLOW)
+                } else {
+                    egui::RichText::new("🕒 Scheduled")
+                };
+                egui::CollapsingHeader::new(title).id_salt("scheduled").show(ui, |ui| {
+                    render_pause(ui, &self.state, pause.as_ref());
+                    ui.separator();
+                    render_schedule(ui, &self.state, &mut self.schedule_edit);
+                    ui.separator();
+                    ui.label("Queues:");
+                    self.render_queues(ui);
+                });
+            });
+
+            ui.add_space(20.0);
+
+            // History Section
+            ui.group(|ui| {
+                ui.set_min_width(400.0);
+                ui.collapsing("📜 History", |ui| {
+                    if let Some(entry) = render_history(ui, &self.rt, &self.state, &mut self.history_filter) {
+                        self.post_again(&entry);
+                    }
+                    ui.separator();
+                    self.render_archive_export(ui);
+                    ui.separator();
+                    self.render_report(ui);
+                });
+            });
+
+            ui.add_space(20.0);
+
+            // Settings Section
+            ui.group(|ui| {
+                ui.set_min_width(400.0);
+                ui.collapsing("⚙ Settings", |ui| {
+                    if render_settings(ui, &mut self.settings_draft) {
+                        // The API base URLs may point at a different server now, and history may live elsewhere
+                        detect_limits(&self.state, &self.rt);
+                        futures::executor::block_on(self.state.lock()).history = history::load_history();
+                    }
+                    ui.separator();
+                    self.render_default_platforms(ui);
+                    ui.separator();
+                    self.render_content_rules(ui);
+                    ui.separator();
+                    self.render_config_transfer(ui);
+                    ui.separator();
+                    self.render_sync(ui);
+                    ui.separator();
+                    self.render_credentials_backup(ui);
+                });
+            });
+        });
+    }
+}
+
+/// Checkbox state for new posts: the active profile's defaults, falling back to the built-in ones
+fn default_platform_checkboxes() -> HashMap<&'static str, bool> {
+    let mut platform_checkboxes = HashMap::from([
+        #[cfg(feature = "twitter")]
+        ("Twitter", true),
+        #[cfg(feature = "bluesky")]
+        ("Bluesky", true),
+        #[cfg(feature = "mastodon")]
+        ("Mastodon", true),
+        #[cfg(feature = "linkedin")]
+        ("LinkedIn", false),
+        #[cfg(feature = "medium")]
+        ("Medium", false),
+        #[cfg(feature = "hashnode")]
+        ("Hashnode", false),
+        #[cfg(feature = "email")]
+        ("Email", false),
+        #[cfg(feature = "demo")]
+        ("Demo", false),
+    ]);
+    #[cfg(feature = "mastodon")]
+    platform_checkboxes.extend(
+        mastodon::load_accounts()
+            .iter()
+            .map(|account| (account.platform_key(), false)),
+    );
+    #[cfg(feature = "plugins")]
+    platform_checkboxes.extend(plugins::all().iter().map(|plugin| (plugin.name, false)));
+
+    // A default saved on purpose wins over whatever was checked last
+    let config = profiles::load_config();
+    if let Some(selected) = config.default_platforms.or(config.last_platforms) {
+        for (platform, checked) in platform_checkboxes.iter_mut() {
+            *checked = selected.iter().any(|p| p == platform);
+        }
+    }
+
+    platform_checkboxes
+}
+
+/// Names of the checked platforms, sorted
+fn checked_platforms(platform_checkboxes: &HashMap<&'static str, bool>) -> Vec<String> {
+    let mut checked: Vec<String> = platform_checkboxes
+        .iter()
+        .filter(|(_, checked)| **checked)
+        .map(|(platform, _)| platform.to_string())
+        .collect();
+    checked.sort();
+    checked
+}
+
+/// The clipboard's text when it is just one http(s) URL
+fn clipboard_url() -> Option<String> {
+    let text = arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .ok()?;
+    let text = text.trim();
+    match links::find_urls(text).as_slice() {
+        [(range, url)] if range.len() == text.len() => Some(url.clone()),
+        _ => None,
+    }
+}
+
+/// Refreshes the posting limits reported by the configured servers in the background
+fn detect_limits(state: &Arc<Mutex<posts::AppState>>, rt: &Runtime) {
+    let state = Arc::clone(state);
+    rt.spawn(async move {
+        let detected = limits::detect().await;
+        state.lock().await.detected_limits = detected;
+    });
+}
+
+/// Loads the active profile's tokens into the app state, replacing any previous authorization
+fn load_authorizations(state: &Arc<Mutex<posts::AppState>>, rt: &Runtime) {
+    {
+        let mut state_guard = futures::executor::block_on(state.lock());
+        state_guard.twitter_authorized = false;
+        state_guard.bluesky_authorized = false;
+        state_guard.mastodon_authorized = false;
+        state_guard.linkedin_authorized = false;
+        state_guard.medium_authorized = false;
+        state_guard.hashnode_authorized = false;
+        state_guard.bluesky_token = None;
+        state_guard.did = None;
+        state_guard.plugins_authorized.clear();
+        #[cfg(feature = "mastodon")]
+        {
+            state_guard.mastodon_accounts = mastodon::load_accounts();
+        }
+    }
+
+    // Load Bluesky tokens and validate
+    #[cfg(feature = "bluesky")]
+    if let Some(tokens) = bluesky::load_tokens() {
+        let mut state_guard = futures::executor::block_on(state.lock());
+        state_guard.bluesky_token = Some(tokens.access_jwt.clone());
+        state_guard.did = Some(tokens.did.clone());
+        state_guard.bluesky_authorized = true; // Assume authorized for now
+
+        // Validate and refresh token asynchronously
+        let state_clone = Arc::clone(state);
+        rt.spawn(async move {
+            if let Some(new_tokens) = bluesky::refresh_access_token(&tokens.refresh_jwt).await {
+                let mut state = state_clone.lock().await;
+                state.bluesky_token = Some(new_tokens.access_jwt);
+                state.did = Some(new_tokens.did);
+                info!(platform = "bluesky", "Bluesky token refreshed successfully");
+            } else {
+                warn!(
+                    platform = "bluesky",
+                    "Bluesky token refresh failed. Attempting reauthorization"
+                );
+                if let Some(new_tokens) = bluesky::reauthorize_bluesky().await {
+                    let mut state = state_clone.lock().await;
+                    state.bluesky_token = Some(new_tokens.access_jwt);
+                    state.did = Some(new_tokens.did);
+                    info!(platform = "bluesky", "Bluesky reauthorized successfully");
+                } else {
+                    let mut state = state_clone.lock().await;
+                    state.bluesky_authorized = false;
+                    error!(platform = "bluesky", "Failed to refresh or reauthorize Bluesky");
+                }
+            }
+        });
+    }
+
+    // Load tokens for other platforms
+    #[cfg(feature = "twitter")]
+    if twitter::load_bearer_token().is_some() {
+        let mut state_guard = futures::executor::block_on(state.lock());
+        state_guard.twitter_authorized = true;
+    }
+
+    #[cfg(feature = "linkedin")]
+    if linkedin::load_bearer_token().is_some() {
+        let mut state_guard = futures::executor::block_on(state.lock());
+        state_guard.linkedin_authorized = true;
+    }
+
+    #[cfg(feature = "medium")]
+    if medium::load_token().is_some() {
+        let mut state_guard = futures::executor::block_on(state.lock());
+        state_guard.medium_authorized = true;
+    }
+
+    #[cfg(feature = "hashnode")]
+    if hashnode::load_token().is_some() {
+        let mut state_guard = futures::executor::block_on(state.lock());
+        state_guard.hashnode_authorized = true;
+    }
+
+    #[cfg(feature = "mastodon")]
+    if mastodon::load_tokens().is_some() {
+        let mut state_guard = futures::executor::block_on(state.lock());
+        state_guard.mastodon_authorized = true;
+    }
+
+    #[cfg(feature = "plugins")]
+    for plugin in plugins::all() {
+        if plugin.load_token().is_some() {
+            let mut state_guard = futures::executor::block_on(state.lock());
+            state_guard.plugins_authorized.insert(plugin.name.to_string());
+        }
+    }
+}
+
+/// Whether the active profile has a token for `platform`
+fn is_authorized(state: &posts::AppState, platform: &str) -> bool {
+    match platform {
+        #[cfg(feature = "twitter")]
+        "Twitter" => state.twitter_authorized,
+        #[cfg(feature = "bluesky")]
+        "Bluesky" => state.bluesky_authorized,
+        #[cfg(feature = "mastodon")]
+        "Mastodon" => state.mastodon_authorized,
+        #[cfg(feature = "linkedin")]
+        "LinkedIn" => state.linkedin_authorized,
+        #[cfg(feature = "medium")]
+        "Medium" => state.medium_authorized,
+        #[cfg(feature = "hashnode")]
+        "Hashnode" => state.hashnode_authorized,
+        #[cfg(feature = "demo")]
+        "Demo" => true,
+        #[cfg(feature = "email")]
+        "Email" => email::is_configured(),
+        #[cfg(feature = "mastodon")]
+        name if name.starts_with(mastodon::ACCOUNT_PREFIX) => state.mastodon_account_authorized(name),
+        name => state.plugins_authorized.contains(name),
+    }
+}
+
+/// Helper function to render a platform's checkbox and authorization status
+fn render_platform_checkbox<F, G>(
+    ui: &mut egui::Ui,
+    label: &str,
+    platform_key: &'static str,
+    platform_checkboxes: &mut HashMap<&'static str, bool>,
+    is_authorized: F,
+    authorize_action: G,
+    state_clone: Arc<Mutex<posts::AppState>>,
+) where
+    F: FnOnce(&posts::AppState) -> bool,
+    G: FnOnce(),
+{
+    ui.horizontal(|ui| {
+        ui.label(label);
+
+        // Check authorization status
+        let state = futures::executor::block_on(state_clone.lock());
+        if is_authorized(&state) {
+            ui.colored_label(egui::Color32::GREEN, "Authorized ✅");
+            // Show checkbox only if authorized
+            if let Some(checked) = platform_checkboxes.get_mut(platform_key) {
+                accessible(ui.checkbox(checked, ""), &format!("Post to {}", platform_key));
+            }
+        } else {
+            ui.colored_label(egui::Color32::RED, "Not Authorized ❌");
+            // Show the "Authorize" button for unauthorized platforms
+            if ui.button("Authorize").clicked() {
+                authorize_action();
+            }
+        }
+    });
+}
+
+/// Names a widget for screen readers (through AccessKit) when its visible text doesn't say what it is: an icon-only
+/// button, or a text field with only hint text or no label beside it
+fn accessible(response: egui::Response, name: &str) -> egui::Response {
+    response
+        .ctx
+        .accesskit_node_builder(response.id, |node| node.set_name(name));
+    response
+}
+
+/// A small button showing only an icon, described by `name` on hover and to screen readers
+fn icon_button(ui: &mut egui::Ui, icon: &str, name: &str) -> egui::Response {
+    accessible(ui.small_button(icon), name).on_hover_text(name)
+}
+
+/// Length of the text for `platform` against its (possibly server-detected) limit, with the choice of what to do when
+/// the text doesn't fit
+fn render_length(
+    ui: &mut egui::Ui,
+    platform: &str,
+    length: usize,
+    platform_limits: limits::PlatformLimits,
+    draft: &mut settings::Settings,
+) {
+    ui.horizontal(|ui| {
+        render_length_ring(ui, length, platform_limits.max_chars);
+        let over = length > platform_limits.max_chars;
+        let color = if over {
+            egui::Color32::RED
+        } else {
+            ui.visuals().weak_text_color()
+        };
+        let remaining = if over {
+            format!("{} over", length - platform_limits.max_chars)
+        } else {
+            format!("{} left", platform_limits.max_chars - length)
+        };
+        ui.colored_label(
+            color,
+            format!("{} {}/{} ({})", platform, length, platform_limits.max_chars, remaining),
+        )
+        .on_hover_text(if platform_limits.detected {
+            "Limit reported by the server"
+        } else {
+            "Default limit"
+        });
+        if over {
+            render_over_limit_choice(ui, platform, draft);
+        }
+    });
+}
+
+/// Lays out composer text with its links, mentions and hashtags colored, showing what will become clickable
+fn highlight_entities(ui: &egui::Ui, text: &str, wrap_width: f32) -> Arc<egui::Galley> {
+    let font_id = egui::TextStyle::Body.resolve(ui.style());
+    let visuals = ui.visuals();
+    let color = |kind: entities::Kind| match (kind, visuals.dark_mode) {
+        (entities::Kind::Link, _) => visuals.hyperlink_color,
+        (entities::Kind::Mention, true) => egui::Color32::from_rgb(120, 180, 255),
+        (entities::Kind::Mention, false) => egui::Color32::from_rgb(20, 90, 200),
+        (entities::Kind::Hashtag, true) => egui::Color32::from_rgb(120, 210, 140),
+        (entities::Kind::Hashtag, false) => egui::Color32::from_rgb(20, 130, 60),
+    };
+    let plain = egui::TextFormat::simple(font_id.clone(), visuals.text_color());
+
+    let mut job = egui::text::LayoutJob::default();
+    let mut end = 0;
+    for (range, kind) in entities::find(text) {
+        job.append(&text[end..range.start], 0.0, plain.clone());
+        job.append(
+            &text[range.clone()],
+            0.0,
+            egui::TextFormat::simple(font_id.clone(), color(kind)),
+        );
+        end = range.end;
+    }
+    job.append(&text[end..], 0.0, plain);
+    job.wrap.max_width = wrap_width;
+    ui.fonts(|fonts| fonts.layout_job(job))
+}
+
+/// Share of the limit from which the length ring turns amber.
+const RING_WARNING_SHARE: f32 = 0.9;
+
+/// A ring that fills as the text approaches `max_chars`: amber near the limit, a full red ring over it
+fn render_length_ring(ui: &mut egui::Ui, length: usize, max_chars: usize) {
+    let share = length as f32 / max_chars.max(1) as f32;
+    let color = if share > 1.0 {
+        egui::Color32::RED
+    } else if share >= RING_WARNING_SHARE {
+        egui::Color32::from_rgb(255, 170, 0)
+    } else {
+        ui.visuals().selection.bg_fill
+    };
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(18.0, 18.0), egui::Sense::hover());
+    let center = rect.center();
+    let radius = rect.width() / 2.0 - 2.0;
+    let painter = ui.painter();
+    painter.circle_stroke(
+        center,
+        radius,
+        egui::Stroke::new(2.0, ui.visuals().widgets.inactive.bg_fill),
+    );
+
+    // Clockwise from the top, like the counter on Twitter
+    let segments = 32;
+    let filled = (share.min(1.0) * segments as f32).round() as usize;
+    if filled > 0 {
+        let points = (0..=filled)
+            .map(|i| {
+                let angle = -std::f32::consts::FRAC_PI_2 + std::f32::consts::TAU * i as f32 / segments as f32;
+                center + radius * egui::vec2(angle.cos(), angle.sin())
+            })
+            .collect();
+        painter.add(egui::Shape::line(points, egui::Stroke::new(2.5, color)));
+    }
+    accessible(response, &format!("{} of {} characters", length, max_chars));
+}
+
+fn render_link_previews(ui: &mut egui::Ui, options: &mut posts::PostOptions) {
+    ui.checkbox(&mut options.link_previews, "🔗 Link preview card")
+        .on_hover_text("Untick for a plain link on Bluesky.  Twitter and Mastodon always make cards themselves.");
+}
+
+/// Whether the post is deleted again after a number of days, on the platforms that can delete it
+fn render_expiry(ui: &mut egui::Ui, options: &mut posts::PostOptions) {
+    ui.horizontal(|ui| {
+        let mut expires = options.expire_after_days.is_some();
+        if ui
+            .checkbox(&mut expires, "⏳ Delete after")
+            .on_hover_text("Deleted from Twitter, Bluesky and Mastodon when the time comes; other platforms keep it")
+            .changed()
+        {
+            options.expire_after_days = expires.then_some(DEFAULT_EXPIRY_DAYS);
+        }
+        if let Some(days) = options.expire_after_days.as_mut() {
+            accessible(
+                ui.add(egui::DragValue::new(days).range(1..=365)),
+                "Days before the post is deleted",
+            );
+            ui.label("days");
+        }
+    });
+}
+
+/// Lists a post's images with remove buttons, and fields to attach another
+fn render_attachments(
+    ui: &mut egui::Ui,
+    images: &mut Vec<media::ImageAttachment>,
+    new_image: &mut media::ImageAttachment,
+) {
+    let mut removed = None;
+    for (i, image) in images.iter().enumerate() {
+        ui.horizontal(|ui| {
+            ui.label(format!("🖼 {}", image.path));
+            if image.alt.is_empty() {
+                ui.colored_label(egui::Color32::YELLOW, "no alt text");
+            }
+            if icon_button(ui, "✖", "Remove image").clicked() {
+                removed = Some(i);
+            }
+        });
+    }
+    if let Some(i) = removed {
+        images.remove(i);
+    }
+    ui.horizontal(|ui| {
+        accessible(
+            ui.add(egui::TextEdit::singleline(&mut new_image.path).hint_text("Image path")),
+            "Image path",
+        );
+        accessible(
+            ui.add(egui::TextEdit::singleline(&mut new_image.alt).hint_text("Alt text")),
+            "Alt text",
+        );
+        let valid = media::mime_type(new_image.path.trim()).is_some();
+        if ui
+            .add_enabled(valid, egui::Button::new("🖼 Attach"))
+            .on_hover_text("PNG, JPEG, GIF or WebP.  Only Bluesky uploads images so far.")
+            .clicked()
+        {
+            images.push(media::ImageAttachment {
+                path: new_image.path.trim().to_string(),
+                alt: new_image.alt.trim().to_string(),
+            });
+            new_image.path.clear();
+            new_image.alt.clear();
+        }
+    });
+}
+
+/// Shows pending toasts stacked in the bottom-right corner, green for success and red for errors
+fn render_toasts(ctx: &egui::Context, state: &Arc<Mutex<posts::AppState>>) {
+    let mut state = futures::executor::block_on(state.lock());
+    state.expire_toasts();
+    if state.toasts.is_empty() {
+        return;
+    }
+
+    egui::Area::new(egui::Id::new("toasts"))
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-10.0, -10.0))
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            for toast in &state.toasts {
+                let fill = if toast.success {
+                    egui::Color32::DARK_GREEN
+                } else {
+                    egui::Color32::DARK_RED
+                };
+                egui::Frame::none()
+                    .fill(fill)
+                    .rounding(4.0)
+                    .inner_margin(8.0)
+                    .show(ui, |ui| {
+                        ui.colored_label(egui::Color32::WHITE, toast.message.as_str());
+                    });
+                ui.add_space(4.0);
+            }
+        });
+
+    // Keep repainting so toasts disappear on time even without user input
+    ctx.request_repaint_after(std::time::Duration::from_millis(250));
+}
+
+/// "45s" or "2m 10s"
+fn format_reading_time(secs: usize) -> String {
+    if secs < 60 {
+        format!("{}s", secs)
+    } else {
+        format!("{}m {}s", secs / 60, secs % 60)
+    }
+}
+
+/// Picks what happens to text over `platform`'s limit.  The choice is saved right away, not through the settings form.
+fn render_over_limit_choice(ui: &mut egui::Ui, platform: &str, draft: &mut settings::Settings) {
+    let current = limits::over_limit_choice(platform);
+    let mut choice = current;
+    egui::ComboBox::from_id_salt(("over_limit", platform))
+        .selected_text(choice.label())
+        .show_ui(ui, |ui| {
+            for option in limits::OverLimit::ALL {
+                if option == limits::OverLimit::Thread && !limits::supports_threads(platform) {
+                    continue;
+                }
+                ui.selectable_value(&mut choice, option, option.label());
+            }
+        });
+
+    if choice != current {
+        let mut saved = settings::get();
+        saved.over_limit.insert(platform.to_string(), choice);
+        settings::update(saved);
+        draft.over_limit.insert(platform.to_string(), choice);
+        info!(platform, choice = choice.label(), "Changed over-limit handling");
+    }
+}
+
+/// Picks how the posts of a thread are numbered, saved straight away like the over-limit choice
+fn render_thread_numbering(ui: &mut egui::Ui, draft: &mut settings::Settings) {
+    let current = limits::thread_numbering();
+    let mut numbering = current;
+    egui::ComboBox::from_id_salt("thread_numbering")
+        .selected_text(numbering.label())
+        .show_ui(ui, |ui| {
+            for option in limits::ThreadNumbering::ALL {
+                ui.selectable_value(&mut numbering, option, option.label());
+            }
+        })
+        .response
+        .on_hover_text("How the posts of a thread are numbered");
+
+    if numbering != current {
+        let mut saved = settings::get();
+        saved.thread_numbering = numbering;
+        settings::update(saved);
+        draft.thread_numbering = numbering;
+        info!(numbering = numbering.label(), "Changed thread numbering");
+    }
+}
+
+/// Editable application settings; changes apply to requests made after saving.  Returns true when saved.
+/// Named bearer tokens for the daemon's API.  New tokens and revocations take effect once settings are saved.
+fn render_api_tokens(ui: &mut egui::Ui, draft: &mut settings::Settings) {
+    ui.label("API tokens (without any, the API accepts every request):");
+    let mut revoked = None;
+    egui::Grid::new("api_tokens_grid").num_columns(4).show(ui, |ui| {
+        for (i, token) in draft.api_tokens.iter_mut().enumerate() {
+            accessible(
+                ui.add(egui::TextEdit::singleline(&mut token.name).desired_width(120.0)),
+                "API token name",
+            );
+            egui::ComboBox::from_id_salt(("api_token_scope", i))
+                .selected_text(token.scope.label())
+                .show_ui(ui, |ui| {
+                    for scope in api::Scope::ALL {
+                        ui.selectable_value(&mut token.scope, scope, scope.label());
+                    }
+                });
+            ui.label(format!("Created {}", timezone::format(token.created_at)));
+            ui.horizontal(|ui| {
+                if ui.button("📋 Copy").clicked() {
+                    ui.ctx().copy_text(token.token.clone());
+                }
+                if ui.button("Revoke").clicked() {
+                    revoked = Some(i);
+                }
+            });
+            ui.end_row();
+        }
+    });
+    if let Some(i) = revoked {
+        let token = draft.api_tokens.remove(i);
+        info!(name = %token.name, "Revoked API token");
+    }
+    ui.horizontal(|ui| {
+        for scope in api::Scope::ALL {
+            if ui
+                .button(format!("➕ New token ({})", scope.label().to_lowercase()))
+                .clicked()
+            {
+                let name = format!("token {}", draft.api_tokens.len() + 1);
+                draft.api_tokens.push(api::ApiToken::new(&name, scope));
+                info!(%name, ?scope, "Created API token");
+            }
+        }
+    });
+}
+
+fn render_settings(ui: &mut egui::Ui, draft: &mut settings::Settings) -> bool {
+    let mut saved = false;
+    egui::Grid::new("settings_grid").num_columns(2).show(ui, |ui| {
+        ui.label("Request timeout (s):");
+        ui.add(egui::DragValue::new(&mut draft.request_timeout_secs).range(1..=600));
+        ui.end_row();
+
+        ui.label("Connect timeout (s):");
+        ui.add(egui::DragValue::new(&mut draft.connect_timeout_secs).range(1..=120));
+        ui.end_row();
+
+        ui.label("Retries on connection failure:");
+        ui.add(egui::DragValue::new(&mut draft.max_retries).range(0..=10));
+        ui.end_row();
+
+        ui.label("User-Agent:");
+        ui.text_edit_singleline(&mut draft.user_agent);
+        ui.end_row();
+
+        ui.label("Theme:");
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("theme")
+                .selected_text(draft.theme.label())
+                .show_ui(ui, |ui| {
+                    for theme in theme::Theme::ALL {
+                        ui.selectable_value(&mut draft.theme, theme, theme.label());
+                    }
+                });
+            ui.checkbox(&mut draft.reduced_motion, "Reduce motion")
+                .on_hover_text("No animated spinners; sections open and close at once");
+        });
+        ui.end_row();
+
+        ui.label("Instructions panel:");
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("notes_panel")
+                .selected_text(draft.notes_panel.label())
+                .show_ui(ui, |ui| {
+                    for position in layout::PanelPosition::ALL {
+                        ui.selectable_value(&mut draft.notes_panel, position, position.label());
+                    }
+                });
+            ui.checkbox(&mut draft.show_instructions, "Show instructions");
+            if draft.notes_panel != layout::PanelPosition::Hidden {
+                ui.add(
+                    egui::Slider::new(&mut draft.notes_panel_share, layout::MIN_SHARE..=layout::MAX_SHARE)
+                        .custom_formatter(|share, _| format!("{:.0}%", share * 100.0))
+                        .text("of the window"),
+                );
+            }
+        });
+        ui.end_row();
+
+        #[cfg(feature = "twitter")]
+        {
+            ui.label("Twitter long posts:");
+            egui::ComboBox::from_id_salt("twitter_long_posts")
+                .selected_text(match draft.twitter_long_posts {
+                    None => "Detect from subscription",
+                    Some(true) => "Yes (up to 25,000 characters)",
+                    Some(false) => "No (280 characters)",
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut draft.twitter_long_posts, None, "Detect from subscription");
+                    ui.selectable_value(
+                        &mut draft.twitter_long_posts,
+                        Some(true),
+                        "Yes (up to 25,000 characters)",
+                    );
+                    ui.selectable_value(&mut draft.twitter_long_posts, Some(false), "No (280 characters)");
+                });
+            ui.end_row();
+        }
+
+        #[cfg(feature = "mastodon")]
+        {
+            ui.label("Mastodon instance runs:");
+            egui::ComboBox::from_id_salt("mastodon_software")
+                .selected_text(draft.mastodon_software.label())
+                .show_ui(ui, |ui| {
+                    for option in activitypub::Software::ALL {
+                        ui.selectable_value(&mut draft.mastodon_software, option, option.label());
+                    }
+                });
+            ui.end_row();
+        }
+
+        ui.label("Time zone:");
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut draft.time_zone).hint_text("System zone, or e.g. Europe/Berlin"));
+            if !timezone::is_valid(&draft.time_zone) {
+                ui.colored_label(egui::Color32::RED, "Unknown time zone");
+            }
+        });
+        ui.end_row();
+
+        ui.label("Require approval:");
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut draft.require_approval, "Scheduled posts wait for approval");
+            ui.label("Self-approval after (min):");
+            ui.add(egui::DragValue::new(&mut draft.approval_cooldown_mins).range(0..=10080));
+        });
+        ui.end_row();
+
+        ui.label("Shared data folder:");
+        ui.add(
+            egui::TextEdit::singleline(&mut draft.shared_data_dir)
+                .hint_text("Network path for team history, schedule and queues"),
+        );
+        ui.end_row();
+
+        ui.label("Sync target:");
+        ui.add(egui::TextEdit::singleline(&mut draft.sync_target).hint_text("File path or WebDAV URL"));
+        ui.end_row();
+
+        ui.label("Webhooks:");
+        ui.add(
+            egui::TextEdit::singleline(&mut draft.webhook_urls)
+                .hint_text("URLs told about every post, comma separated"),
+        )
+        .on_hover_text("Each gets a JSON POST per platform when a post succeeds or fails");
+        ui.end_row();
+
+        ui.label("Alert email:");
+        ui.horizontal(|ui| {
+            accessible(
+                ui.add(
+                    egui::TextEdit::singleline(&mut draft.alert_email_to).hint_text("To, when a scheduled post fails"),
+                ),
+                "Alert email to",
+            );
+            accessible(
+                ui.add(egui::TextEdit::singleline(&mut draft.alert_email_from).hint_text("From (default: login)")),
+                "Alert email from",
+            );
+        });
+        ui.end_row();
+
+        ui.label("SMTP server:");
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut draft.smtp_host).hint_text("smtp.example.com"));
+            ui.add(egui::DragValue::new(&mut draft.smtp_port).range(1..=65535));
+        });
+        ui.end_row();
+
+        ui.label("SMTP login:");
+        ui.horizontal(|ui| {
+            accessible(
+                ui.add(egui::TextEdit::singleline(&mut draft.smtp_username).hint_text("Username")),
+                "SMTP username",
+            );
+            accessible(
+                ui.add(
+                    egui::TextEdit::singleline(&mut draft.smtp_password)
+                        .password(true)
+                        .hint_text("Password"),
+                ),
+                "SMTP password",
+            );
+        });
+        ui.end_row();
+
+        #[cfg(feature = "email")]
+        {
+            ui.label("Email platform:");
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt("newsletter_service")
+                    .selected_text(draft.newsletter_service.label())
+                    .show_ui(ui, |ui| {
+                        for option in mail::Service::ALL {
+                            ui.selectable_value(&mut draft.newsletter_service, option, option.label());
+                        }
+                    });
+                if draft.newsletter_service != mail::Service::Buttondown {
+                    accessible(
+                        ui.add(
+                            egui::TextEdit::singleline(&mut draft.newsletter_to).hint_text("To, e.g. a list address"),
+                        ),
+                        "Newsletter to",
+                    );
+                    accessible(
+                        ui.add(egui::TextEdit::singleline(&mut draft.newsletter_from).hint_text("From")),
+                        "Newsletter from",
+                    );
+                }
+            });
+            ui.end_row();
+
+            if draft.newsletter_service != mail::Service::Smtp {
+                ui.label("Newsletter API:");
+                ui.horizontal(|ui| {
+                    accessible(
+                        ui.add(
+                            egui::TextEdit::singleline(&mut draft.newsletter_api_key)
+                                .password(true)
+                                .hint_text("API key"),
+                        ),
+                        "Newsletter API key",
+                    );
+                    if draft.newsletter_service == mail::Service::Mailgun {
+                        accessible(
+                            ui.add(egui::TextEdit::singleline(&mut draft.mailgun_domain).hint_text("mg.example.com")),
+                            "Mailgun domain",
+                        );
+                    }
+                });
+                ui.end_row();
+            }
+        }
+
+        #[cfg(feature = "hashnode")]
+        {
+            ui.label("Hashnode publication:");
+            ui.add(
+                egui::TextEdit::singleline(&mut draft.hashnode_host)
+                    .hint_text("blog.example.com (default: your first publication)"),
+            );
+            ui.end_row();
+        }
+
+        ui.label("Local API (daemon):");
+        ui.add(egui::TextEdit::singleline(&mut draft.api_address).hint_text("127.0.0.1:8787"));
+        ui.end_row();
+
+        #[cfg(feature = "mqtt")]
+        {
+            ui.label("MQTT trigger:");
+            ui.horizontal(|ui| {
+                accessible(
+                    ui.add(egui::TextEdit::singleline(&mut draft.mqtt_broker).hint_text("broker.local:1883")),
+                    "MQTT broker",
+                );
+                accessible(
+                    ui.add(egui::TextEdit::singleline(&mut draft.mqtt_topic).hint_text("Topic, e.g. stream/live")),
+                    "MQTT topic",
                 );
             });
+            ui.end_row();
 
-            ui.add_space(20.0);
+            ui.label("MQTT login:");
+            ui.horizontal(|ui| {
+                accessible(
+                    ui.add(egui::TextEdit::singleline(&mut draft.mqtt_username).hint_text("Username (optional)")),
+                    "MQTT username",
+                );
+                accessible(
+                    ui.add(
+                        egui::TextEdit::singleline(&mut draft.mqtt_password)
+                            .password(true)
+                            .hint_text("Password"),
+                    ),
+                    "MQTT password",
+                );
+            });
+            ui.end_row();
 
-            // Compose and Post Section
-            ui.group(|ui| {
-                ui.set_min_width(400.0);
-                ui.label("Compose your message:");
-                {
-                    let mut state = futures::executor::block_on(state_clone.lock());
-                    ui.text_edit_multiline(&mut state.post_text);
+            ui.label("MQTT post:");
+            ui.add(
+                egui::TextEdit::multiline(&mut draft.mqtt_template)
+                    .desired_rows(2)
+                    .hint_text("{message}, or {field} of a JSON message"),
+            );
+            ui.end_row();
+
+            ui.label("Post MQTT messages to:");
+            ui.horizontal(|ui| {
+                for platform in ["Twitter", "Bluesky", "Mastodon", "LinkedIn"] {
+                    let mut checked = draft.mqtt_platforms.iter().any(|p| p == platform);
+                    if ui.checkbox(&mut checked, platform).changed() {
+                        if checked {
+                            draft.mqtt_platforms.push(platform.to_string());
+                        } else {
+                            draft.mqtt_platforms.retain(|p| p != platform);
+                        }
+                    }
+                }
+            });
+            ui.end_row();
+        }
+
+        #[cfg(feature = "scripting")]
+        {
+            ui.label("Hook script:");
+            ui.add(egui::TextEdit::singleline(&mut draft.hook_script).hint_text("Rhai file with on_before_post"));
+            ui.end_row();
+        }
+
+        ui.label("Watch GitHub releases:");
+        ui.add(egui::TextEdit::singleline(&mut draft.github_repos).hint_text("owner/repo, owner/other-repo"));
+        ui.end_row();
+
+        ui.label("Release announcement:");
+        ui.add(
+            egui::TextEdit::multiline(&mut draft.release_template)
+                .desired_rows(3)
+                .hint_text("Placeholders: {repo} {tag} {name} {url}"),
+        );
+        ui.end_row();
+
+        ui.label("Announce releases:");
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut draft.release_auto_post, "Post automatically to");
+            for platform in ["Twitter", "Bluesky", "Mastodon", "LinkedIn"] {
+                let mut checked = draft.release_platforms.iter().any(|p| p == platform);
+                let response = ui.add_enabled(draft.release_auto_post, egui::Checkbox::new(&mut checked, platform));
+                if response.changed() {
+                    if checked {
+                        draft.release_platforms.push(platform.to_string());
+                    } else {
+                        draft.release_platforms.retain(|p| p != platform);
+                    }
                 }
+            }
+        });
+        ui.end_row();
 
+        for (platform, default_url) in [
+            ("twitter", "https://api.twitter.com"),
+            ("bluesky", "https://bsky.social"),
+            ("mastodon", "https://fosstodon.org"),
+            ("github", "https://api.github.com"),
+        ] {
+            ui.label(format!("{} API base URL:", platform));
+            let url = draft.api_base_urls.entry(platform.to_string()).or_default();
+            ui.add(egui::TextEdit::singleline(url).hint_text(default_url));
+            ui.end_row();
+        }
+    });
+
+    ui.add_space(10.0);
+    ui.label("Posting caps (0 for none), on top of the platforms' own rate limits:");
+    egui::Grid::new("posting_caps_grid").num_columns(3).show(ui, |ui| {
+        for platform in default_platform_checkboxes()
+            .keys()
+            .copied()
+            .collect::<std::collections::BTreeSet<_>>()
+        {
+            ui.label(platform);
+            let cap = draft.posting_caps.entry(platform.to_string()).or_default();
+            for (limit, unit) in [(&mut cap.per_hour, "per hour"), (&mut cap.per_day, "per day")] {
+                let mut value = limit.unwrap_or(0);
                 if ui
-                    .add(egui::Button::new("📤 Post").fill(egui::Color32::DARK_GRAY))
-                    .clicked()
+                    .add(
+                        egui::DragValue::new(&mut value)
+                            .range(0..=1000)
+                            .suffix(format!(" {}", unit)),
+                    )
+                    .changed()
                 {
-                    let state = Arc::clone(&self.state);
-                    let rt = Arc::clone(&self.rt);
-                    let selected_platforms = self.platform_checkboxes.clone();
+                    *limit = (value > 0).then_some(value);
+                }
+            }
+            ui.end_row();
+        }
+    });
 
-                    rt.spawn(async move {
-                        let mut state = state.lock().await;
-                        let text = state.post_text.clone();
+    ui.add_space(10.0);
+    render_api_tokens(ui, draft);
 
-                        // Post only to platforms that are authorized and selected
-                        if *selected_platforms.get("Twitter").unwrap_or(&false) && state.twitter_authorized {
-                            if let Some(bearer_token) = twitter::load_bearer_token() {
-                                if twitter::post_to_twitter(&bearer_token, &text).await {
-                                    println!("Posted to Twitter successfully!");
-                                } else {
-                                    println!("Failed to post to Twitter.");
-                                }
-                            }
+    ui.add_space(10.0);
+    ui.checkbox(
+        &mut draft.roll_back_threads,
+        "Delete the posted part of a thread when a later post fails (Twitter and Mastodon)",
+    )
+    .on_hover_text("Otherwise the thread stops at the failed post and the posts before it stay up");
+    #[cfg(feature = "medium")]
+    ui.checkbox(
+        &mut draft.medium_drafts,
+        "Save Medium stories as drafts instead of publishing them",
+    );
+    ui.checkbox(&mut draft.markdown, "Write posts in Markdown")
+        .on_hover_text("**bold**, *italic*, `code`, [links](https://example.com), # headings and - lists");
+    if draft.markdown {
+        egui::Grid::new("markdown_grid").num_columns(2).show(ui, |ui| {
+            for platform in default_platform_checkboxes()
+                .keys()
+                .copied()
+                .collect::<std::collections::BTreeSet<_>>()
+            {
+                ui.label(platform);
+                let rendering = draft.markdown_rendering.entry(platform.to_string()).or_default();
+                egui::ComboBox::from_id_salt(("markdown_rendering", platform))
+                    .selected_text(rendering.label())
+                    .show_ui(ui, |ui| {
+                        for option in markdown::Rendering::ALL {
+                            ui.selectable_value(rendering, option, option.label());
                         }
+                    });
+                ui.end_row();
+            }
+        });
+    }
 
-                        if *selected_platforms.get("Bluesky").unwrap_or(&false) && state.bluesky_authorized {
-                            if let Some(token) = state.bluesky_token.clone() {
-                                if let Some(user_did) = state.did.clone() {
-                                    if bluesky::post_to_bluesky(&token, &text, &user_did).await {
-                                        println!("Posted to Bluesky successfully!");
-                                    } else {
-                                        println!("Failed to post to Bluesky.");
-                                    }
-                                }
-                            }
+    ui.add_space(10.0);
+    #[cfg(feature = "mastodon")]
+    ui.checkbox(
+        &mut draft.trending_hashtags,
+        "Suggest hashtags trending on Mastodon while typing a tag",
+    );
+    ui.checkbox(
+        &mut draft.profanity_filter,
+        "Flag profanity in the composer, offering to mask it or swap in milder words",
+    );
+    ui.label("Transforms before posting:");
+    ui.checkbox(
+        &mut draft.camel_case_hashtags,
+        "CamelCase hashtags on every platform (#rustlang → #RustLang), for screen readers",
+    );
+    ui.checkbox(
+        &mut draft.expand_emoji_shortcodes,
+        "Expand emoji shortcodes on every platform (:tada: → 🎉)",
+    );
+    egui::Grid::new("transforms_grid").num_columns(2).show(ui, |ui| {
+        for platform in default_platform_checkboxes()
+            .keys()
+            .copied()
+            .collect::<std::collections::BTreeSet<_>>()
+        {
+            ui.label(platform);
+            ui.horizontal(|ui| {
+                let enabled = draft.transforms.entry(platform.to_string()).or_default();
+                for transform in transforms::Transform::ALL {
+                    let mut checked = enabled.contains(&transform);
+                    if ui.checkbox(&mut checked, transform.label()).changed() {
+                        if checked {
+                            enabled.push(transform);
+                        } else {
+                            enabled.retain(|t| *t != transform);
                         }
+                    }
+                }
+            });
+            ui.end_row();
+        }
+    });
 
-                        if *selected_platforms.get("Mastodon").unwrap_or(&false) && state.mastodon_authorized {
-                            if let Some(token_data) = mastodon::load_tokens() {
-                                if mastodon::post_to_mastodon(&token_data.access_token, &text).await {
-                                    println!("Posted to Mastodon successfully!");
-                                } else {
-                                    println!("Failed to post to Mastodon.");
-                                }
-                            }
-                        }
+    ui.horizontal(|ui| {
+        // Blank overrides are equivalent to missing ones
+        draft.api_base_urls.retain(|_, url| !url.trim().is_empty());
+        draft.transforms.retain(|_, enabled| !enabled.is_empty());
+        draft
+            .markdown_rendering
+            .retain(|_, rendering| *rendering != markdown::Rendering::default());
+        draft
+            .posting_caps
+            .retain(|_, cap| *cap != budget::PostingCap::default());
+        let changed = *draft != settings::get();
+        if ui.add_enabled(changed, egui::Button::new("💾 Save")).clicked() {
+            settings::update(draft.clone());
+            info!("Settings saved");
+            saved = true;
+        }
+        if ui.add_enabled(changed, egui::Button::new("Revert")).clicked() {
+            *draft = settings::get();
+        }
+    });
+    saved
+}
 
-                        if *selected_platforms.get("LinkedIn").unwrap_or(&false) && state.linkedin_authorized {
-                            if let Some(linkedin_token) = linkedin::load_bearer_token() {
-                                if linkedin::post_to_linkedin(&linkedin_token, &text).await {
-                                    println!("Posted to LinkedIn successfully!");
-                                } else {
-                                    println!("Failed to post to LinkedIn.");
-                                }
-                            }
-                        }
+/// How many words of the post the profanity filter flags, with buttons to mask them or swap in milder ones
+fn render_profanity(ui: &mut egui::Ui, state: &mut posts::AppState) {
+    let flagged: usize = std::iter::once(&state.post_text)
+        .chain(state.compose_options.texts.values())
+        .chain(state.compose_options.thread.iter().map(|segment| &segment.text))
+        .map(|text| profanity::find(text).len())
+        .sum();
+    if flagged == 0 {
+        return;
+    }
+    ui.horizontal(|ui| {
+        ui.colored_label(
+            egui::Color32::from_rgb(255, 170, 0),
+            format!("⚠ {} word(s) flagged as profanity", flagged),
+        );
+        let fix = if ui
+            .button("✱ Mask")
+            .on_hover_text("Keep the first letter: s***")
+            .clicked()
+        {
+            Some(profanity::Fix::Mask)
+        } else if ui
+            .button("↻ Replace")
+            .on_hover_text("Swap in milder words where there are some, and mask the rest")
+            .clicked()
+        {
+            Some(profanity::Fix::Replace)
+        } else {
+            None
+        };
+        if let Some(fix) = fix {
+            let changed = profanity::fix_post(&mut state.post_text, &mut state.compose_options, fix);
+            info!(changed, ?fix, "Fixed profanity in the composer");
+        }
+    });
+}
+
+/// Collapsible view of recent log events with a level filter, for users who can't see stdout
+fn render_log_panel(ui: &mut egui::Ui, log_buffer: &logging::LogBuffer, log_level: &mut tracing::Level) {
+    egui::CollapsingHeader::new("🪵 Logs").show(ui, |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Show:");
+            egui::ComboBox::from_id_salt("log_level")
+                .selected_text(log_level.as_str())
+                .show_ui(ui, |ui| {
+                    for level in [
+                        tracing::Level::ERROR,
+                        tracing::Level::WARN,
+                        tracing::Level::INFO,
+                        tracing::Level::DEBUG,
+                        tracing::Level::TRACE,
+                    ] {
+                        ui.selectable_value(log_level, level, level.as_str());
+                    }
+                });
+            if ui.button("Clear").clicked() {
+                log_buffer.clear();
+            }
+        });
+
+        egui::ScrollArea::vertical()
+            .max_height(200.0)
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for record in log_buffer.snapshot().iter().filter(|r| r.level <= *log_level) {
+                    let color = match record.level {
+                        tracing::Level::ERROR => egui::Color32::RED,
+                        tracing::Level::WARN => egui::Color32::YELLOW,
+                        tracing::Level::INFO => ui.visuals().text_color(),
+                        _ => egui::Color32::GRAY,
+                    };
+                    ui.colored_label(
+                        color,
+                        format!(
+                            "{} {:5} {}",
+                            record.time.format("%H:%M:%S"),
+                            record.level.as_str(),
+                            record.message
+                        ),
+                    );
+                }
+            });
+
+        // Background tasks log without user input, so keep polling while the panel is open
+        ui.ctx().request_repaint_after(std::time::Duration::from_secs(1));
+    });
+}
+
+/// Lists past posts, newest first, with a per-post engagement comparison across platforms.  Returns the entry whose
+/// "Post again" button was clicked, if any
+fn render_history(
+    ui: &mut egui::Ui,
+    rt: &Arc<Runtime>,
+    state: &Arc<Mutex<posts::AppState>>,
+    filter: &mut history::HistoryFilter,
+) -> Option<history::HistoryEntry> {
+    let state_guard = futures::executor::block_on(state.lock());
+    if state_guard.history.is_empty() {
+        ui.label("Nothing posted yet.");
+        return None;
+    }
+    let mut post_again = None;
 
-                        state.post_text.clear(); // Clear input after posting
+    render_history_filter(ui, filter);
+    let matching: Vec<&history::HistoryEntry> = state_guard
+        .history
+        .iter()
+        .rev()
+        .filter(|entry| filter.matches(entry))
+        .collect();
+    if filter.is_active() {
+        ui.label(format!(
+            "{} of {} post(s) match",
+            matching.len(),
+            state_guard.history.len()
+        ));
+    }
+
+    egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+        for entry in matching {
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label(
+                    entry
+                        .posted_at
+                        .with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string(),
+                );
+                if let Some(author) = &entry.author {
+                    ui.label(format!("by {}", author));
+                }
+                match entry.expires_at {
+                    Some(_) if entry.expired => {
+                        ui.label("⌛ Deleted after expiry");
+                    }
+                    Some(expires_at) => {
+                        ui.label(format!("⏳ Deletes {}", timezone::format(expires_at)));
+                    }
+                    None => {}
+                }
+                if ui
+                    .button("🔁 Post again")
+                    .on_hover_text("Load this post into the composer")
+                    .clicked()
+                {
+                    post_again = Some(entry.clone());
+                }
+                if ui.button("📊 Compare").clicked() {
+                    let entry = entry.clone();
+                    let bluesky_token = state_guard.bluesky_token.clone();
+                    let state_clone = Arc::clone(state);
+                    rt.spawn(async move {
+                        let rows = engagement::compare(&entry, bluesky_token).await;
+                        let mut state = state_clone.lock().await;
+                        engagement::record(&mut state.engagement_samples, &entry.id, &rows);
+                        state.comparisons.insert(entry.id.clone(), rows);
                     });
                 }
             });
+            ui.label(entry.text.as_str());
+
+            if let Some(rows) = state_guard.comparisons.get(&entry.id) {
+                render_comparison(ui, &entry.id, rows);
+                if let Some(samples) = state_guard.engagement_samples.get(&entry.id) {
+                    render_engagement_chart(ui, &entry, samples);
+                }
+            } else {
+                let platforms: Vec<String> = entry
+                    .results
+                    .iter()
+                    .map(|r| format!("{} {}", r.platform, if r.success { "✅" } else { "❌" }))
+                    .collect();
+                ui.label(platforms.join("  "));
+            }
+        }
+    });
+    post_again
+}
+
+/// Mentions and other notifications received on the accounts, newest first
+fn render_inbox(
+    ui: &mut egui::Ui,
+    rt: &Arc<Runtime>,
+    state_arc: &Arc<Mutex<posts::AppState>>,
+    reply: &mut Option<QuickReply>,
+) {
+    let mut state = futures::executor::block_on(state_arc.lock());
+    if state.inbox.is_empty() {
+        ui.label("No notifications yet.");
+        return;
+    }
+    if ui.button("🗑 Clear").clicked() {
+        state.inbox.clear();
+        inbox::save_inbox(&state.inbox);
+        return;
+    }
+
+    for notification in &state.inbox {
+        ui.separator();
+        ui.horizontal(|ui| {
+            let from = egui::RichText::new(format!("{} {}", notification.icon(), notification.from));
+            ui.label(if notification.read { from } else { from.strong() })
+                .on_hover_text(notification.kind.as_str());
+            ui.label(timezone::format(notification.received_at));
+            ui.label(notification.platform.as_str());
+            if let Some(url) = &notification.url {
+                ui.hyperlink_to("🔗 Open", url);
+            }
+            if let Some(status_id) = notification.status_id.as_ref().filter(|_| notification.can_reply()) {
+                if ui.button("↩ Reply").clicked() {
+                    *reply = Some(QuickReply {
+                        platform: notification.platform.clone(),
+                        status_id: status_id.clone(),
+                        visibility: notification.visibility,
+                        // Mastodon only notifies the author of a reply that mentions them
+                        text: format!("{} ", notification.from),
+                    });
+                }
+            }
         });
+        if !notification.text.is_empty() {
+            ui.label(notification.text.as_str());
+        }
+        let replying = reply.as_ref().is_some_and(|r| {
+            r.platform == notification.platform && Some(&r.status_id) == notification.status_id.as_ref()
+        });
+        if replying {
+            let mut send = false;
+            let mut cancel = false;
+            if let Some(draft) = reply.as_mut() {
+                accessible(
+                    ui.add(egui::TextEdit::multiline(&mut draft.text).desired_rows(2)),
+                    &format!("Reply to {}", notification.from),
+                );
+                ui.horizontal(|ui| {
+                    ui.label(format!("Visibility: {}", draft.visibility.label()));
+                    send = ui
+                        .add_enabled(!draft.text.trim().is_empty(), egui::Button::new("📤 Send reply"))
+                        .clicked();
+                    cancel = ui.button("Cancel").clicked();
+                });
+            }
+            if send {
+                if let Some(draft) = reply.take() {
+                    let state = Arc::clone(state_arc);
+                    rt.spawn(async move {
+                        let result =
+                            publish::reply(&draft.platform, &draft.text, &draft.status_id, draft.visibility).await;
+                        let mut state = state.lock().await;
+                        match result {
+                            Ok(_) => state.toast(format!("Replied on {}", draft.platform), true),
+                            Err(err) => state.toast(format!("Reply on {} failed: {:?}", draft.platform, err), false),
+                        }
+                    });
+                }
+            } else if cancel {
+                *reply = None;
+            }
+        }
     }
+    // Shown once; they're no longer new when the inbox is next opened
+    inbox::mark_read(&mut state.inbox);
 }
 
-/// Helper function to render a platform's checkbox and authorization status
-fn render_platform_checkbox<F, G>(
-    ui: &mut egui::Ui,
-    label: &str,
-    platform_key: &'static str,
-    platform_checkboxes: &mut HashMap<&'static str, bool>,
-    is_authorized: F,
-    authorize_action: G,
-    state_clone: Arc<Mutex<posts::AppState>>,
-) where
-    F: FnOnce(&posts::AppState) -> bool,
-    G: FnOnce(),
-{
-    ui.horizontal(|ui| {
-        ui.label(label);
+/// Whether scheduled posts are paused, with the switch to pause or resume all of them
+fn render_pause(ui: &mut egui::Ui, state: &Arc<Mutex<posts::AppState>>, pause: Option<&scheduler::Pause>) {
+    ui.horizontal(|ui| match pause {
+        Some(pause) => {
+            ui.colored_label(
+                egui::Color32::YELLOW,
+                format!(
+                    "⏸ Nothing goes out: paused by {} since {}",
+                    pause.paused_by,
+                    timezone::format(pause.paused_at)
+                ),
+            );
+            if ui
+                .button("▶ Resume")
+                .on_hover_text("Posts that came due while paused go out on the next check")
+                .clicked()
+            {
+                scheduler::resume();
+                futures::executor::block_on(state.lock()).schedule_pause = None;
+            }
+        }
+        None => {
+            if ui
+                .button("⏸ Pause all")
+                .on_hover_text("Hold every scheduled and queued post, e.g. during an incident or a holiday")
+                .clicked()
+            {
+                let pause = scheduler::pause();
+                futures::executor::block_on(state.lock()).schedule_pause = Some(pause);
+            }
+        }
+    });
+}
 
-        // Check authorization status
-        let state = futures::executor::block_on(state_clone.lock());
-        if is_authorized(&state) {
-            ui.colored_label(egui::Color32::GREEN, "Authorized ✅");
-            // Show checkbox only if authorized
-            if let Some(checked) = platform_checkboxes.get_mut(platform_key) {
-                ui.checkbox(checked, "");
+/// Upcoming scheduled posts, soonest first, shown in the configured time zone.  The one in `editing` is shown as a
+/// form instead.
+fn render_schedule(ui: &mut egui::Ui, state: &Arc<Mutex<posts::AppState>>, editing: &mut Option<ScheduleEdit>) {
+    let (posts, paused_queues) = {
+        let state = futures::executor::block_on(state.lock());
+        let paused_queues: Vec<String> = state
+            .queues
+            .iter()
+            .filter(|queue| queue.paused)
+            .map(|queue| queue.name.clone())
+            .collect();
+        (state.schedule.clone(), paused_queues)
+    };
+    if posts.is_empty() {
+        ui.label("Nothing scheduled.");
+        *editing = None;
+        return;
+    }
+
+    let mut changed = false;
+    for post in posts {
+        ui.separator();
+        if let Some(edit) = editing.as_mut().filter(|edit| edit.post.id == post.id) {
+            if let Some(saved) = render_schedule_edit(ui, edit) {
+                if saved && !scheduler::update(&edit.post) {
+                    futures::executor::block_on(state.lock())
+                        .toast("The post is no longer scheduled; it may have gone out already", false);
+                } else if saved {
+                    info!(id = %post.id, "Edited scheduled post");
+                    changed = true;
+                }
+                *editing = None;
             }
-        } else {
-            ui.colored_label(egui::Color32::RED, "Not Authorized ❌");
-            // Show the "Authorize" button for unauthorized platforms
-            if ui.button("Authorize").clicked() {
-                authorize_action();
+            continue;
+        }
+        ui.horizontal(|ui| {
+            ui.label(timezone::format(post.scheduled_at))
+                .on_hover_text(post.scheduled_at.format("%Y-%m-%d %H:%M UTC").to_string());
+            ui.label(post.platforms.join(", "));
+            if let Some(author) = &post.author {
+                ui.label(format!("by {}", author));
+            }
+            if let Some(queue) = &post.queue {
+                ui.label(format!("📂 {}", queue));
+                if paused_queues.contains(queue) {
+                    ui.colored_label(egui::Color32::YELLOW, "⏸ paused");
+                }
+            }
+            if let Some(recurrence) = &post.recurrence {
+                let mut repeats = match &recurrence.cron {
+                    Some(expression) => format!("🔁 {}", expression),
+                    None => format!("🔁 {}", recurrence.frequency.label()),
+                };
+                if let Some(remaining) = recurrence.remaining {
+                    repeats.push_str(&format!(", {} left", remaining));
+                }
+                if let Some(until) = recurrence.until {
+                    repeats.push_str(&format!(", until {}", timezone::format(until)));
+                }
+                ui.label(repeats);
+            }
+            match &post.approval {
+                Some(approval) if approval.is_approved() => {
+                    ui.colored_label(egui::Color32::GREEN, "✔ Approved")
+                        .on_hover_text(format!("By {}", approval.approved_by.as_deref().unwrap_or("unknown")));
+                }
+                Some(approval) => {
+                    ui.colored_label(egui::Color32::YELLOW, "⏳ Awaiting approval")
+                        .on_hover_text(format!("Scheduled by {}", approval.requested_by));
+                    if ui.button("✔ Approve").clicked() {
+                        match scheduler::approve(&post.id) {
+                            Ok(()) => changed = true,
+                            Err(err) => futures::executor::block_on(state.lock()).toast(err, false),
+                        }
+                    }
+                }
+                None => {}
+            }
+            if ui.button("✏ Edit").clicked() {
+                *editing = Some(ScheduleEdit {
+                    time: timezone::to_wall_clock(post.scheduled_at)
+                        .format(timezone::INPUT_FORMAT)
+                        .to_string(),
+                    post: post.clone(),
+                    new_image: media::ImageAttachment::default(),
+                });
+            }
+            if ui.button("🗑 Cancel").clicked() {
+                scheduler::cancel(&post.id);
+                info!(id = %post.id, "Cancelled scheduled post");
+                changed = true;
+            }
+        });
+        ui.label(post.text.as_str());
+    }
+    if changed {
+        futures::executor::block_on(state.lock()).schedule = scheduler::load_schedule();
+    }
+}
+
+/// Form for a scheduled post's text, attachments, platforms and time.  Returns `Some(true)` once saved with a valid
+/// time (written into `edit.post`), `Some(false)` when discarded, and `None` while still editing.
+fn render_schedule_edit(ui: &mut egui::Ui, edit: &mut ScheduleEdit) -> Option<bool> {
+    let post = &mut edit.post;
+    accessible(
+        ui.add(
+            egui::TextEdit::multiline(&mut post.text)
+                .desired_rows(3)
+                .layouter(&mut highlight_entities),
+        ),
+        "Scheduled post text",
+    );
+    render_attachments(ui, &mut post.options.images, &mut edit.new_image);
+
+    let mut platforms: Vec<&str> = default_platform_checkboxes().into_keys().collect();
+    platforms.sort_unstable();
+    ui.horizontal_wrapped(|ui| {
+        for platform in platforms {
+            let mut checked = post.platforms.iter().any(|p| p == platform);
+            if ui.checkbox(&mut checked, platform).changed() {
+                if checked {
+                    post.platforms.push(platform.to_string());
+                } else {
+                    post.platforms.retain(|p| p != platform);
+                }
+            }
+        }
+    });
+
+    let parsed = timezone::parse(&edit.time);
+    ui.horizontal(|ui| {
+        ui.label(format!("At ({}):", timezone::label()));
+        accessible(
+            ui.add(
+                egui::TextEdit::singleline(&mut edit.time)
+                    .hint_text("YYYY-MM-DD HH:MM")
+                    .desired_width(140.0),
+            ),
+            "Scheduled time",
+        );
+        match &parsed {
+            Ok(at) if *at <= chrono::Utc::now() => {
+                ui.colored_label(egui::Color32::LIGHT_RED, "That time has already passed");
+            }
+            Ok(_) => {}
+            Err(err) => {
+                ui.colored_label(egui::Color32::LIGHT_RED, err);
+            }
+        }
+    });
+    if post.approval.as_ref().is_some_and(|approval| approval.is_approved()) {
+        ui.label("Saving changes means the post has to be approved again.");
+    }
+
+    let valid = parsed.as_ref().is_ok_and(|at| *at > chrono::Utc::now())
+        && !post.platforms.is_empty()
+        && !post.text.trim().is_empty();
+    ui.horizontal(|ui| {
+        if ui.add_enabled(valid, egui::Button::new("💾 Save")).clicked() {
+            if let Ok(at) = parsed {
+                post.scheduled_at = at;
             }
+            return Some(true);
+        }
+        ui.button("Discard").clicked().then_some(false)
+    })
+    .inner
+}
+
+/// Search box and filters above the history list
+fn render_history_filter(ui: &mut egui::Ui, filter: &mut history::HistoryFilter) {
+    ui.horizontal(|ui| {
+        ui.label("🔍");
+        accessible(
+            ui.add(egui::TextEdit::singleline(&mut filter.query).hint_text("Search posts")),
+            "Search posts",
+        );
+        if filter.is_active() && ui.button("Clear").clicked() {
+            *filter = history::HistoryFilter::default();
         }
     });
+
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_salt("history_platform")
+            .selected_text(filter.platform.as_deref().unwrap_or("All platforms"))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut filter.platform, None, "All platforms");
+                let mut platforms: Vec<&str> = default_platform_checkboxes().into_keys().collect();
+                platforms.sort();
+                for platform in platforms {
+                    ui.selectable_value(&mut filter.platform, Some(platform.to_string()), platform);
+                }
+            });
+
+        egui::ComboBox::from_id_salt("history_outcome")
+            .selected_text(match filter.outcome {
+                history::OutcomeFilter::Any => "Any outcome",
+                history::OutcomeFilter::Succeeded => "Succeeded",
+                history::OutcomeFilter::Failed => "Failed",
+            })
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut filter.outcome, history::OutcomeFilter::Any, "Any outcome");
+                ui.selectable_value(&mut filter.outcome, history::OutcomeFilter::Succeeded, "Succeeded");
+                ui.selectable_value(&mut filter.outcome, history::OutcomeFilter::Failed, "Failed");
+            });
+
+        ui.label("From:");
+        ui.add(
+            egui::TextEdit::singleline(&mut filter.from)
+                .hint_text("YYYY-MM-DD")
+                .desired_width(90.0),
+        );
+        ui.label("To:");
+        ui.add(
+            egui::TextEdit::singleline(&mut filter.to)
+                .hint_text("YYYY-MM-DD")
+                .desired_width(90.0),
+        );
+    });
+}
+
+/// Renders a side-by-side table of how a post performed on each platform
+fn render_comparison(ui: &mut egui::Ui, entry_id: &str, rows: &[engagement::ComparisonRow]) {
+    fn metric(value: Option<u64>) -> String {
+        value.map(|v| v.to_string()).unwrap_or_else(|| "—".to_string())
+    }
+
+    egui::Grid::new(format!("comparison_{}", entry_id))
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong("Platform");
+            ui.strong("Impressions");
+            ui.strong("Likes");
+            ui.strong("Reposts");
+            ui.strong("Replies");
+            ui.end_row();
+
+            for row in rows {
+                ui.label(row.platform.as_str());
+                match &row.engagement {
+                    Some(engagement) => {
+                        ui.label(metric(engagement.impressions));
+                        ui.label(metric(engagement.likes));
+                        ui.label(metric(engagement.reposts));
+                        ui.label(metric(engagement.replies));
+                    }
+                    None => {
+                        ui.label("unavailable");
+                    }
+                }
+                ui.end_row();
+            }
+        });
 }
 
-fn main() -> Result<(), eframe::Error> {
+/// Plots each platform's likes (solid) and reposts (dashed) over the days after posting, once there are samples
+/// enough to draw a line
+fn render_engagement_chart(ui: &mut egui::Ui, entry: &history::HistoryEntry, samples: &[engagement::Sample]) {
+    let mut platforms: Vec<&str> = samples.iter().map(|sample| sample.platform.as_str()).collect();
+    platforms.sort_unstable();
+    platforms.dedup();
+    let days_after = |sample: &engagement::Sample| (sample.at - entry.posted_at).num_minutes() as f64 / (24.0 * 60.0);
+    let series = |platform: &str, metric: fn(&engagement::Sample) -> Option<u64>| -> Vec<[f64; 2]> {
+        samples
+            .iter()
+            .filter(|sample| sample.platform == platform)
+            .filter_map(|sample| Some([days_after(sample), metric(sample)? as f64]))
+            .collect()
+    };
+    if platforms
+        .iter()
+        .all(|platform| series(platform, |s| s.likes).len() < 2 && series(platform, |s| s.reposts).len() < 2)
+    {
+        return;
+    }
+
+    egui_plot::Plot::new(format!("engagement_{}", entry.id))
+        .legend(egui_plot::Legend::default())
+        .height(180.0)
+        .x_axis_label("Days after posting")
+        .include_x(0.0)
+        .include_y(0.0)
+        .allow_scroll(false)
+        .show(ui, |plot_ui| {
+            for platform in platforms {
+                let likes = series(platform, |s| s.likes);
+                let reposts = series(platform, |s| s.reposts);
+                if likes.len() > 1 {
+                    plot_ui.line(egui_plot::Line::new(likes).name(format!("{} likes", platform)));
+                }
+                if reposts.len() > 1 {
+                    plot_ui.line(
+                        egui_plot::Line::new(reposts)
+                            .name(format!("{} reposts", platform))
+                            .style(egui_plot::LineStyle::dashed_loose()),
+                    );
+                }
+            }
+        });
+}
+
+fn main() -> ExitCode {
     dotenv().ok();
+    let log_buffer = logging::init();
+    // Settings, tokens, history and the schedule are kept relative to the working directory
+    if let Ok(dir) = std::env::var("MULTIQUE_DATA_DIR") {
+        if let Err(err) = std::env::set_current_dir(&dir) {
+            error!(%dir, error = ?err, "Failed to use MULTIQUE_DATA_DIR");
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let cli = cli::Cli::parse();
+    if cli.daemon {
+        return daemon::run();
+    }
+    if let Some(command) = cli.command {
+        return cli::run(command);
+    }
+
     let options = eframe::NativeOptions::default();
-    eframe::run_native("Multique", options, Box::new(|_cc| Ok(Box::new(PostApp::new()))))
+    match eframe::run_native(
+        "Multique",
+        options,
+        Box::new(|_cc| Ok(Box::new(PostApp::new(log_buffer)))),
+    ) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            error!(error = ?err, "Failed to start the app");
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,293 @@
+use serde::{Deserialize, Serialize};
+
+/// How text written in Markdown is turned into what a platform is sent.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum Rendering {
+    /// Formatting dropped; links become "text (url)".  For Twitter, Bluesky and Mastodon.
+    #[default]
+    Plain,
+    /// Bold, italics and code kept as Unicode letter styles (𝗯𝗼𝗹𝗱, 𝘪𝘵𝘢𝘭𝘪𝘤), for platforms without formatting such as
+    /// LinkedIn.  Screen readers may read styled letters badly, so use it sparingly.
+    Unicode,
+    /// HTML, for blog targets such as WordPress, Ghost or Dev.to.
+    Html,
+}
+
+impl Rendering {
+    pub const ALL: [Rendering; 3] = [Rendering::Plain, Rendering::Unicode, Rendering::Html];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Rendering::Plain => "Plain text",
+            Rendering::Unicode => "Unicode bold/italics",
+            Rendering::Html => "HTML",
+        }
+    }
+}
+
+/// The first non-empty line without heading marks, shortened to `max_chars` with an ellipsis, as the title of a
+/// story or the subject of an email.  `None` when the text is blank.
+pub fn title(text: &str, max_chars: usize) -> Option<String> {
+    let first_line = text
+        .lines()
+        .map(|line| line.trim().trim_start_matches('#').trim())
+        .find(|line| !line.is_empty())?;
+    if first_line.chars().count() <= max_chars {
+        return Some(first_line.to_string());
+    }
+    let kept: String = first_line.chars().take(max_chars.saturating_sub(1)).collect();
+    Some(format!("{}…", kept.trim_end()))
+}
+
+/// A run of text with the same formatting.
+#[derive(Default, Clone, PartialEq, Debug)]
+struct Span {
+    text: String,
+    bold: bool,
+    italic: bool,
+    code: bool,
+    link: Option<String>,
+}
+
+enum Block<'a> {
+    Heading(&'a str),
+    Item(&'a str),
+    Line(&'a str),
+    Blank,
+}
+
+/// Renders lightweight Markdown: `**bold**`, `*italic*` or `_italic_`, `` `code` ``, `[links](url)`, `# headings`
+/// and `-` or `*` lists.  Anything else is kept as text, so plain text passes through unchanged.
+pub fn render(text: &str, rendering: Rendering) -> String {
+    match rendering {
+        Rendering::Html => to_html(text),
+        _ => text
+            .lines()
+            .map(|line| match block(line) {
+                Block::Heading(heading) => inline(heading, rendering, true),
+                Block::Item(item) => format!("• {}", inline(item, rendering, false)),
+                Block::Line(line) => inline(line, rendering, false),
+                Block::Blank => String::new(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+fn block(line: &str) -> Block<'_> {
+    let trimmed = line.trim_start();
+    if trimmed.is_empty() {
+        return Block::Blank;
+    }
+    let hashes = trimmed.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&hashes) && trimmed[hashes..].starts_with(' ') {
+        return Block::Heading(trimmed[hashes..].trim());
+    }
+    match trimmed.strip_prefix("- ").or_else(|| trimmed.strip_prefix("* ")) {
+        Some(item) => Block::Item(item),
+        None => Block::Line(line),
+    }
+}
+
+/// One line of text in a style without markup.  Headings are bold where that can be shown.
+fn inline(text: &str, rendering: Rendering, heading: bool) -> String {
+    spans(text)
+        .into_iter()
+        .map(|span| {
+            let styled = match rendering {
+                Rendering::Unicode => style(&span.text, span.bold || heading, span.italic, span.code),
+                _ => span.text.clone(),
+            };
+            match span.link {
+                Some(url) if url != span.text => format!("{} ({})", styled, url),
+                _ => styled,
+            }
+        })
+        .collect()
+}
+
+fn to_html(text: &str) -> String {
+    let mut html = String::new();
+    let mut paragraph: Vec<String> = Vec::new();
+    let mut in_list = false;
+    let flush = |html: &mut String, paragraph: &mut Vec<String>| {
+        if !paragraph.is_empty() {
+            html.push_str(&format!("<p>{}</p>\n", paragraph.join("<br>\n")));
+            paragraph.clear();
+        }
+    };
+    for line in text.lines() {
+        let block = block(line);
+        if in_list && !matches!(block, Block::Item(_)) {
+            html.push_str("</ul>\n");
+            in_list = false;
+        }
+        match block {
+            Block::Heading(heading) => {
+                flush(&mut html, &mut paragraph);
+                let level = line.trim_start().chars().take_while(|c| *c == '#').count();
+                html.push_str(&format!("<h{0}>{1}</h{0}>\n", level, html_inline(heading)));
+            }
+            Block::Item(item) => {
+                flush(&mut html, &mut paragraph);
+                if !in_list {
+                    html.push_str("<ul>\n");
+                    in_list = true;
+                }
+                html.push_str(&format!("<li>{}</li>\n", html_inline(item)));
+            }
+            Block::Line(line) => paragraph.push(html_inline(line.trim())),
+            Block::Blank => flush(&mut html, &mut paragraph),
+        }
+    }
+    if in_list {
+        html.push_str("</ul>\n");
+    }
+    flush(&mut html, &mut paragraph);
+    html.trim_end().to_string()
+}
+
+fn html_inline(text: &str) -> String {
+    spans(text)
+        .into_iter()
+        .map(|span| {
+            let mut html = escape_html(&span.text);
+            if span.code {
+                html = format!("<code>{}</code>", html);
+            }
+            if span.italic {
+                html = format!("<em>{}</em>", html);
+            }
+            if span.bold {
+                html = format!("<strong>{}</strong>", html);
+            }
+            match span.link {
+                Some(url) => format!("<a href=\"{}\">{}</a>", escape_html(&url), html),
+                None => html,
+            }
+        })
+        .collect()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Splits a line into runs of formatting.  A marker only opens when it is followed by text and closed later in the
+/// line, and `_` only at word boundaries, so `2 * 3 * 4` and `snake_case_names` stay as they are.
+fn spans(text: &str) -> Vec<Span> {
+    let chars: Vec<char> = text.chars().collect();
+    let rest = |i: usize| chars[i..].iter().collect::<String>();
+    let mut spans: Vec<Span> = Vec::new();
+    let mut current = Span::default();
+    let (mut bold, mut italic) = (false, false);
+    let push = |spans: &mut Vec<Span>, current: &mut Span, bold: bool, italic: bool| {
+        if !current.text.is_empty() {
+            spans.push(std::mem::take(current));
+        }
+        current.bold = bold;
+        current.italic = italic;
+    };
+
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let before = i.checked_sub(1).map(|j| chars[j]);
+
+        if c == '\\' && next.is_some_and(|n| n.is_ascii_punctuation()) {
+            current.text.push(chars[i + 1]);
+            i += 2;
+            continue;
+        }
+        if c == '`' {
+            if let Some(len) = chars[i + 1..].iter().position(|c| *c == '`') {
+                push(&mut spans, &mut current, bold, italic);
+                current.code = true;
+                current.text = chars[i + 1..i + 1 + len].iter().collect();
+                push(&mut spans, &mut current, bold, italic);
+                i += len + 2;
+                continue;
+            }
+        }
+        if c == '[' {
+            if let Some((label, url, len)) = link(&rest(i + 1)) {
+                push(&mut spans, &mut current, bold, italic);
+                current.text = label;
+                current.link = Some(url);
+                push(&mut spans, &mut current, bold, italic);
+                i += len + 1;
+                continue;
+            }
+        }
+        if c == '*' || c == '_' {
+            let double = next == Some(c);
+            let marker_len = if double { 2 } else { 1 };
+            let marker = c.to_string().repeat(marker_len);
+            let after = chars.get(i + marker_len).copied();
+            let open = if double { bold } else { italic };
+            let boundary_ok = |side: Option<char>| c == '*' || side.map_or(true, |s| !s.is_alphanumeric());
+            let toggles = if open {
+                before.is_some_and(|b| !b.is_whitespace()) && boundary_ok(after)
+            } else {
+                after.is_some_and(|a| !a.is_whitespace())
+                    && boundary_ok(before)
+                    && rest(i + marker_len).contains(marker.as_str())
+            };
+            if toggles {
+                if double {
+                    bold = !bold;
+                } else {
+                    italic = !italic;
+                }
+                push(&mut spans, &mut current, bold, italic);
+                i += marker_len;
+                continue;
+            }
+        }
+        current.text.push(c);
+        i += 1;
+    }
+    push(&mut spans, &mut current, bold, italic);
+    spans
+}
+
+/// The label and URL of a `label](url)` link (the opening bracket already read), and how many characters it took.
+fn link(text: &str) -> Option<(String, String, usize)> {
+    let close = text.find("](")?;
+    let label = &text[..close];
+    let url_start = close + 2;
+    let url_len = text[url_start..].find(')')?;
+    let url = &text[url_start..url_start + url_len];
+    if label.is_empty() || url.is_empty() || url.contains(char::is_whitespace) {
+        return None;
+    }
+    let len = text[..url_start + url_len + 1].chars().count();
+    Some((label.to_string(), url.to_string(), len))
+}
+
+/// Rewrites ASCII letters (and digits, where a style has them) in the Mathematical Alphanumeric Symbols block.
+fn style(text: &str, bold: bool, italic: bool, code: bool) -> String {
+    // First code points of A, a and 0 in each sans-serif style
+    let (upper, lower, digit) = match (code, bold, italic) {
+        (true, _, _) => (0x1D670, 0x1D68A, Some(0x1D7F6)),
+        (false, true, true) => (0x1D63C, 0x1D656, Some(0x1D7EC)),
+        (false, true, false) => (0x1D5D4, 0x1D5EE, Some(0x1D7EC)),
+        (false, false, true) => (0x1D608, 0x1D622, None),
+        (false, false, false) => return text.to_string(),
+    };
+    text.chars()
+        .map(|c| {
+            let styled = match c {
+                'A'..='Z' => Some(upper + (c as u32 - 'A' as u32)),
+                'a'..='z' => Some(lower + (c as u32 - 'a' as u32)),
+                '0'..='9' => digit.map(|digit| digit + (c as u32 - '0' as u32)),
+                _ => None,
+            };
+            styled.and_then(char::from_u32).unwrap_or(c)
+        })
+        .collect()
+}
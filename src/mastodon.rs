@@ -1,11 +1,52 @@
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::fs;
-use std::path::Path;
+use std::sync::Mutex;
+use std::time::Instant;
+use tracing::{error, info, warn};
+
+use crate::activitypub::Software;
+use crate::audit;
+use crate::engagement::Engagement;
+use crate::http;
+use crate::limits::{self, PlatformLimits};
+use crate::posts::{PostError, PostOptions, Visibility};
+use crate::ratelimit;
+use crate::settings;
+use crate::tokens;
 
 const TOKEN_FILE: &str = "mastodon_tokens.json";
-const API_BASE_URL: &str = "https://fosstodon.org";
-const OAUTH_BASE_URL: &str = "https://fosstodon.org/oauth";
+// Named like the other token files so backups and sync include it
+const ACCOUNTS_FILE: &str = "mastodon_accounts_tokens.json";
+/// Upgrades of the stored `TokenData` and `Account` list written by older versions; see `tokens::load`.
+const TOKEN_MIGRATIONS: &[tokens::Migration] = &[];
+const ACCOUNTS_MIGRATIONS: &[tokens::Migration] = &[];
+const DEFAULT_API_BASE_URL: &str = "https://fosstodon.org";
+const REDIRECT_URI: &str = "urn:ietf:wg:oauth:2.0:oob";
+
+/// The platform name of the account on the instance at `base_url`: "Mastodon" for the main one.
+fn platform_for(base_url: &str) -> String {
+    if base_url == api_base_url() {
+        "Mastodon".to_string()
+    } else {
+        format!("{}{}", ACCOUNT_PREFIX, normalize_instance(base_url))
+    }
+}
+
+/// Prefix of the platform names of additional accounts, e.g. "Mastodon@hachyderm.io".
+pub const ACCOUNT_PREFIX: &str = "Mastodon@";
+
+/// The instance of the main account, honoring the override in settings.
+pub fn api_base_url() -> String {
+    settings::api_base_url("mastodon", DEFAULT_API_BASE_URL)
+}
+
+/// What the main account's instance runs, as chosen in settings.
+pub fn software() -> Software {
+    settings::get().mastodon_software
+}
+
+fn oauth_base_url(base_url: &str) -> String {
+    format!("{}/oauth", base_url)
+}
 
 #[derive(Serialize, Deserialize)]
 pub struct TokenData {
@@ -16,29 +57,194 @@ pub fn save_tokens(access_token: &str) {
     let token_data = TokenData {
         access_token: access_token.to_string(),
     };
-    let json = serde_json::to_string(&token_data).expect("Failed to serialize token data");
-    fs::write(TOKEN_FILE, json).expect("Failed to write token file");
+    tokens::save(TOKEN_FILE, TOKEN_MIGRATIONS, &token_data);
 }
 
+/// The stored token, or `MASTODON_ACCESS_TOKEN` when none has been stored, so a container can post without
+/// authorizing in the app.
 pub fn load_tokens() -> Option<TokenData> {
-    if Path::new(TOKEN_FILE).exists() {
-        let json = fs::read_to_string(TOKEN_FILE).expect("Failed to read token file");
-        serde_json::from_str(&json).ok()
-    } else {
-        None
+    tokens::load(TOKEN_FILE, TOKEN_MIGRATIONS).or_else(|| {
+        std::env::var("MASTODON_ACCESS_TOKEN")
+            .ok()
+            .map(|access_token| TokenData { access_token })
+    })
+}
+
+/// A Mastodon account on another instance than the main one.  The app registers itself on the instance when the
+/// account is added, so it needs no client id in `.env`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Account {
+    pub instance: String, // Host name, e.g. hachyderm.io
+    pub client_id: String,
+    pub client_secret: String,
+    pub access_token: Option<String>,
+    /// Detected when the account is added; accounts added before that are taken to be on Mastodon.
+    #[serde(default)]
+    pub software: Software,
+}
+
+impl Account {
+    /// Name of the account in the platform list, history and settings.
+    pub fn platform(&self) -> String {
+        format!("{}{}", ACCOUNT_PREFIX, self.instance)
+    }
+
+    /// `platform()` as a `&'static str`, since the platform list keys its checkboxes by those.  Each name is leaked
+    /// once.
+    pub fn platform_key(&self) -> &'static str {
+        static NAMES: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+        let platform = self.platform();
+        let mut names = NAMES.lock().unwrap();
+        if let Some(name) = names.iter().find(|name| **name == platform) {
+            return name;
+        }
+        let name: &'static str = Box::leak(platform.into_boxed_str());
+        names.push(name);
+        name
+    }
+
+    pub fn base_url(&self) -> String {
+        format!("https://{}", self.instance)
     }
 }
 
-/// Generates the Mastodon OAuth 2.0 authorization URL.
-pub async fn generate_auth_url(client_id: &str) -> String {
+pub fn load_accounts() -> Vec<Account> {
+    tokens::load(ACCOUNTS_FILE, ACCOUNTS_MIGRATIONS).unwrap_or_default()
+}
+
+pub fn save_accounts(accounts: &[Account]) {
+    tokens::save(ACCOUNTS_FILE, ACCOUNTS_MIGRATIONS, &accounts);
+}
+
+/// The additional account posted to as `platform`, if there is one.
+pub fn find_account(platform: &str) -> Option<Account> {
+    let instance = platform.strip_prefix(ACCOUNT_PREFIX)?;
+    load_accounts().into_iter().find(|account| account.instance == instance)
+}
+
+/// The host name in what the user typed, e.g. "https://Hachyderm.io/" -> "hachyderm.io".
+pub fn normalize_instance(input: &str) -> String {
+    let input = input.trim();
+    let host = input
+        .strip_prefix("https://")
+        .or_else(|| input.strip_prefix("http://"))
+        .unwrap_or(input);
+    host.trim_end_matches('/').to_ascii_lowercase()
+}
+
+/// The software `base_url` runs, from the version in `/api/v1/instance`.  Mastodon when it can't be told.
+pub async fn detect_software(base_url: &str) -> Software {
+    #[derive(Deserialize)]
+    struct InstanceResponse {
+        version: String,
+    }
+
+    match http::send_idempotent(http::client().get(format!("{}/api/v1/instance", base_url))).await {
+        Ok(response) if response.status().is_success() => match response.json::<InstanceResponse>().await {
+            Ok(instance) => Software::from_version(&instance.version),
+            Err(_) => Software::Mastodon,
+        },
+        Ok(response) => {
+            warn!(
+                platform = "mastodon",
+                request = "instance_v1",
+                status = response.status().as_u16(),
+                "Failed to fetch instance version"
+            );
+            Software::Mastodon
+        }
+        Err(err) => {
+            error!(platform = "mastodon", request = "instance_v1", error = ?err, "Error fetching instance version");
+            Software::Mastodon
+        }
+    }
+}
+
+/// Registers the app on `instance` (`POST /api/v1/apps`) with the scopes its software understands, returning a new,
+/// not yet authorized account.
+pub async fn register_app(instance: &str) -> Option<Account> {
+    #[derive(Serialize)]
+    struct AppRequest<'a> {
+        client_name: &'a str,
+        redirect_uris: &'a str,
+        scopes: &'a str,
+        website: &'a str,
+    }
+
+    #[derive(Deserialize)]
+    struct AppResponse {
+        client_id: String,
+        client_secret: String,
+    }
+
+    let instance = normalize_instance(instance);
+    let software = detect_software(&format!("https://{}", instance)).await;
+    let account = Account {
+        instance,
+        client_id: String::new(),
+        client_secret: String::new(),
+        access_token: None,
+        software,
+    };
+    let app_request = AppRequest {
+        client_name: "Multique",
+        redirect_uris: REDIRECT_URI,
+        scopes: software.scopes(),
+        website: "https://github.com/hortonew/multique",
+    };
+
+    let started = Instant::now();
+    match http::send(
+        http::client()
+            .post(format!("{}/api/v1/apps", account.base_url()))
+            .form(&app_request),
+    )
+    .await
+    {
+        Ok(response) if response.status().is_success() => {
+            let app = response.json::<AppResponse>().await.ok()?;
+            info!(platform = "mastodon", instance = %account.instance, software = ?software, "Registered app");
+            Some(Account {
+                client_id: app.client_id,
+                client_secret: app.client_secret,
+                ..account
+            })
+        }
+        Ok(response) => {
+            let status = response.status();
+            let duration_ms = started.elapsed().as_millis() as u64;
+            let body = response.text().await.unwrap_or_default();
+            warn!(
+                platform = "mastodon",
+                request = "apps",
+                instance = %account.instance,
+                status = status.as_u16(),
+                duration_ms,
+                %body,
+                "Failed to register app"
+            );
+            None
+        }
+        Err(err) => {
+            error!(platform = "mastodon", request = "apps", instance = %account.instance, error = ?err, "Error registering app");
+            None
+        }
+    }
+}
+
+/// Generates the Mastodon OAuth 2.0 authorization URL, asking for the scopes `software` understands.
+pub async fn generate_auth_url(base_url: &str, client_id: &str, software: Software) -> String {
     format!(
-        "{}/authorize?response_type=code&client_id={}&redirect_uri=urn:ietf:wg:oauth:2.0:oob&scope=write:statuses",
-        OAUTH_BASE_URL, client_id
+        "{}/authorize?response_type=code&client_id={}&redirect_uri={}&scope={}",
+        oauth_base_url(base_url),
+        client_id,
+        REDIRECT_URI,
+        software.scopes().replace(' ', "+")
     )
 }
 
 /// Exchanges an authorization code for an access token.
-pub async fn authorize_mastodon(client_id: &str, client_secret: &str, code: &str) -> Option<String> {
+pub async fn authorize_mastodon(base_url: &str, client_id: &str, client_secret: &str, code: &str) -> Option<String> {
     #[derive(Serialize)]
     struct TokenRequest {
         grant_type: String,
@@ -53,76 +259,416 @@ pub async fn authorize_mastodon(client_id: &str, client_secret: &str, code: &str
         access_token: String,
     }
 
-    let client = Client::new();
+    let client = http::client();
     let token_request = TokenRequest {
         grant_type: "authorization_code".to_string(),
         client_id: client_id.to_string(),
         client_secret: client_secret.to_string(),
-        redirect_uri: "urn:ietf:wg:oauth:2.0:oob".to_string(),
+        redirect_uri: REDIRECT_URI.to_string(),
         code: code.to_string(),
     };
 
-    match client
-        .post(format!("{}/token", OAUTH_BASE_URL))
-        .form(&token_request)
-        .send()
-        .await
+    let started = Instant::now();
+    match http::send(
+        client
+            .post(format!("{}/token", oauth_base_url(base_url)))
+            .form(&token_request),
+    )
+    .await
     {
         Ok(response) => {
-            if response.status().is_success() {
+            let status = response.status();
+            let duration_ms = started.elapsed().as_millis() as u64;
+            if status.is_success() {
                 if let Ok(token_response) = response.json::<TokenResponse>().await {
+                    info!(
+                        platform = "mastodon",
+                        request = "token",
+                        status = status.as_u16(),
+                        duration_ms,
+                        "Authorized Mastodon"
+                    );
+                    audit::record(audit::Action::Authorize, Some(&platform_for(base_url)), "");
                     Some(token_response.access_token)
                 } else {
-                    println!("Failed to parse token response.");
+                    error!(
+                        platform = "mastodon",
+                        request = "token",
+                        "Failed to parse token response"
+                    );
                     None
                 }
             } else {
-                println!(
-                    "Failed to authorize Mastodon: {}",
-                    response.text().await.unwrap_or_default()
+                let body = response.text().await.unwrap_or_default();
+                warn!(
+                    platform = "mastodon",
+                    request = "token",
+                    status = status.as_u16(),
+                    duration_ms,
+                    %body,
+                    "Failed to authorize Mastodon"
                 );
                 None
             }
         }
         Err(err) => {
-            println!("Error sending request: {:?}", err);
+            error!(platform = "mastodon", request = "token", error = ?err, "Error sending request");
             None
         }
     }
 }
 
-/// Posts a status (toot) to Mastodon.
-pub async fn post_to_mastodon(token: &str, status: &str) -> bool {
+/// A status created by `post_to_mastodon`.
+#[derive(Deserialize)]
+pub struct PostedStatus {
+    pub id: String,
+    /// Public web address of the status.
+    pub url: Option<String>,
+}
+
+/// Posts a status (toot) to Mastodon, optionally as a reply to `in_reply_to`.  Mastodon ignores repeated requests
+/// carrying the same `idempotency_key`, returning the originally created status instead.
+pub async fn post_to_mastodon(
+    base_url: &str,
+    token: &str,
+    status: &str,
+    in_reply_to: Option<&str>,
+    idempotency_key: &str,
+    options: &PostOptions,
+) -> Result<PostedStatus, PostError> {
     #[derive(Serialize)]
     struct StatusPost {
         status: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        in_reply_to_id: Option<String>,
+        #[serde(skip_serializing_if = "String::is_empty")]
+        spoiler_text: String,
+        visibility: Visibility,
     }
 
-    let client = Client::new();
+    let client = http::client();
     let post_data = StatusPost {
         status: status.to_string(),
+        in_reply_to_id: in_reply_to.map(str::to_string),
+        spoiler_text: options.content_warning.trim().to_string(),
+        visibility: options.visibility,
     };
 
-    match client
-        .post(format!("{}/api/v1/statuses", API_BASE_URL))
-        .bearer_auth(token)
-        .json(&post_data)
-        .send()
-        .await
+    // The key makes a repeated request harmless, so timeouts can be retried too: if the first attempt did create the
+    // status, the retry gets that status back rather than a duplicate
+    let started = Instant::now();
+    match http::send_idempotent(
+        client
+            .post(format!("{}/api/v1/statuses", base_url))
+            .bearer_auth(token)
+            .header("Idempotency-Key", idempotency_key)
+            .json(&post_data),
+    )
+    .await
     {
         Ok(response) => {
+            ratelimit::record(&platform_for(base_url), response.headers());
             let status_code = response.status();
+            let duration_ms = started.elapsed().as_millis() as u64;
             let body = response.text().await.unwrap_or_default();
             if status_code.is_success() {
+                match serde_json::from_str::<PostedStatus>(&body) {
+                    Ok(status_response) => {
+                        info!(
+                            platform = "mastodon",
+                            request = "statuses",
+                            status = status_code.as_u16(),
+                            duration_ms,
+                            post_id = %status_response.id,
+                            "Posted status"
+                        );
+                        Ok(status_response)
+                    }
+                    Err(_) => {
+                        error!(
+                            platform = "mastodon",
+                            request = "statuses",
+                            "Posted, but failed to parse the status response"
+                        );
+                        Err(PostError::Rejected)
+                    }
+                }
+            } else {
+                warn!(
+                    platform = "mastodon",
+                    request = "statuses",
+                    status = status_code.as_u16(),
+                    duration_ms,
+                    %body,
+                    "Failed to post status"
+                );
+                Err(PostError::from_status(status_code))
+            }
+        }
+        Err(err) => {
+            error!(platform = "mastodon", request = "statuses", error = ?err, "Error posting to Mastodon");
+            Err(PostError::Network)
+        }
+    }
+}
+
+/// Deletes one of the account's statuses, as when a thread that failed partway is rolled back.  Returns whether it
+/// is gone.
+pub async fn delete_status(base_url: &str, token: &str, status_id: &str) -> bool {
+    let client = http::client();
+    let started = Instant::now();
+    match http::send_idempotent(
+        client
+            .delete(format!("{}/api/v1/statuses/{}", base_url, status_id))
+            .bearer_auth(token),
+    )
+    .await
+    {
+        Ok(response) => {
+            ratelimit::record(&platform_for(base_url), response.headers());
+            let status = response.status();
+            let duration_ms = started.elapsed().as_millis() as u64;
+            // Already gone counts as deleted, so a retried rollback doesn't report a failure
+            if status.is_success() || status == reqwest::StatusCode::NOT_FOUND {
+                info!(
+                    platform = "mastodon",
+                    request = "delete_status",
+                    status = status.as_u16(),
+                    duration_ms,
+                    post_id = status_id,
+                    "Deleted status"
+                );
                 true
             } else {
-                println!("Failure in post_to_mastodon: {}", body);
+                let body = response.text().await.unwrap_or_default();
+                warn!(
+                    platform = "mastodon",
+                    request = "delete_status",
+                    status = status.as_u16(),
+                    duration_ms,
+                    %body,
+                    "Failed to delete status"
+                );
                 false
             }
         }
         Err(err) => {
-            println!("Error posting to Mastodon: {:?}", err);
+            error!(platform = "mastodon", request = "delete_status", error = ?err, "Error deleting status");
             false
         }
     }
 }
+
+/// Fetches favourite/boost/reply counts for a status.  The request is unauthenticated because the stored token
+/// only carries the `write:statuses` scope.
+pub async fn fetch_engagement(base_url: &str, status_id: &str) -> Option<Engagement> {
+    #[derive(Deserialize)]
+    struct StatusResponse {
+        favourites_count: u64,
+        reblogs_count: u64,
+        replies_count: u64,
+    }
+
+    let client = http::client();
+    let started = Instant::now();
+    match http::send_idempotent(client.get(format!("{}/api/v1/statuses/{}", base_url, status_id))).await {
+        Ok(response) if response.status().is_success() => {
+            let status = response.json::<StatusResponse>().await.ok()?;
+            Some(Engagement {
+                impressions: None,
+                likes: Some(status.favourites_count),
+                reposts: Some(status.reblogs_count),
+                replies: Some(status.replies_count),
+            })
+        }
+        Ok(response) => {
+            let status = response.status();
+            let duration_ms = started.elapsed().as_millis() as u64;
+            let body = response.text().await.unwrap_or_default();
+            warn!(
+                platform = "mastodon",
+                request = "get_status",
+                status = status.as_u16(),
+                duration_ms,
+                %body,
+                "Failed to fetch status"
+            );
+            None
+        }
+        Err(err) => {
+            error!(platform = "mastodon", request = "get_status", error = ?err, "Error fetching status");
+            None
+        }
+    }
+}
+
+/// Accounts matching `query`, as (full handle, display name), from `/api/v2/search`.  Unauthenticated, like
+/// `fetch_engagement`, so only accounts the instance already knows are found.  Local accounts get the instance's
+/// domain, so the handle works when the post is read elsewhere.
+pub async fn search_accounts(base_url: &str, query: &str) -> Vec<(String, String)> {
+    #[derive(Deserialize)]
+    struct Account {
+        acct: String,
+        display_name: String,
+    }
+
+    #[derive(Deserialize)]
+    struct SearchResponse {
+        accounts: Vec<Account>,
+    }
+
+    let client = http::client();
+    let started = Instant::now();
+    match http::send_idempotent(client.get(format!("{}/api/v2/search", base_url)).query(&[
+        ("q", query),
+        ("type", "accounts"),
+        ("limit", "8"),
+    ]))
+    .await
+    {
+        Ok(response) if response.status().is_success() => {
+            let Ok(found) = response.json::<SearchResponse>().await else {
+                return Vec::new();
+            };
+            let domain = normalize_instance(base_url);
+            found
+                .accounts
+                .into_iter()
+                .map(|account| {
+                    let handle = if account.acct.contains('@') {
+                        account.acct
+                    } else {
+                        format!("{}@{}", account.acct, domain)
+                    };
+                    (handle, account.display_name)
+                })
+                .collect()
+        }
+        Ok(response) => {
+            let status = response.status();
+            let duration_ms = started.elapsed().as_millis() as u64;
+            warn!(
+                platform = "mastodon",
+                request = "search",
+                status = status.as_u16(),
+                duration_ms,
+                "Failed to search accounts"
+            );
+            Vec::new()
+        }
+        Err(err) => {
+            error!(platform = "mastodon", request = "search", error = ?err, "Error searching accounts");
+            Vec::new()
+        }
+    }
+}
+
+/// Names of the hashtags trending on the instance, from `/api/v1/trends/tags`.  Empty when the instance doesn't
+/// share its trends, or its software has none.
+pub async fn fetch_trending_tags(base_url: &str, software: Software) -> Vec<String> {
+    #[derive(Deserialize)]
+    struct Tag {
+        name: String,
+    }
+
+    if !software.has_trends() {
+        return Vec::new();
+    }
+
+    let client = http::client();
+    match http::send_idempotent(
+        client
+            .get(format!("{}/api/v1/trends/tags", base_url))
+            .query(&[("limit", "20")]),
+    )
+    .await
+    {
+        Ok(response) if response.status().is_success() => response
+            .json::<Vec<Tag>>()
+            .await
+            .map(|tags| tags.into_iter().map(|tag| tag.name).collect())
+            .unwrap_or_default(),
+        Ok(response) => {
+            warn!(
+                platform = "mastodon",
+                request = "trends_tags",
+                status = response.status().as_u16(),
+                "Failed to fetch trending tags"
+            );
+            Vec::new()
+        }
+        Err(err) => {
+            error!(platform = "mastodon", request = "trends_tags", error = ?err, "Error fetching trending tags");
+            Vec::new()
+        }
+    }
+}
+
+/// Reads the instance's status length and media limits from its instance endpoint (`Software::instance_path`).
+/// Servers without the `configuration` block advertise the length as a top-level `max_toot_chars` instead.
+pub async fn fetch_instance_limits(base_url: &str, software: Software) -> Option<PlatformLimits> {
+    #[derive(Deserialize)]
+    struct InstanceResponse {
+        max_toot_chars: Option<usize>,
+        configuration: Option<Configuration>,
+    }
+
+    #[derive(Deserialize)]
+    struct Configuration {
+        statuses: Option<StatusConfiguration>,
+        media_attachments: Option<MediaConfiguration>,
+    }
+
+    #[derive(Deserialize)]
+    struct StatusConfiguration {
+        max_characters: Option<usize>,
+        max_media_attachments: Option<usize>,
+    }
+
+    #[derive(Deserialize)]
+    struct MediaConfiguration {
+        image_size_limit: Option<u64>,
+        video_size_limit: Option<u64>,
+    }
+
+    let client = http::client();
+    let started = Instant::now();
+    match http::send_idempotent(client.get(format!("{}{}", base_url, software.instance_path()))).await {
+        Ok(response) if response.status().is_success() => {
+            let instance = response.json::<InstanceResponse>().await.ok()?;
+            let configuration = instance.configuration;
+            let statuses = configuration.as_ref().and_then(|c| c.statuses.as_ref());
+            let media = configuration.as_ref().and_then(|c| c.media_attachments.as_ref());
+            let defaults = limits::default_for("Mastodon");
+
+            Some(PlatformLimits {
+                max_chars: statuses
+                    .and_then(|s| s.max_characters)
+                    .or(instance.max_toot_chars)
+                    .unwrap_or(software.default_max_chars()),
+                max_media: statuses.and_then(|s| s.max_media_attachments).or(defaults.max_media),
+                max_image_bytes: media.and_then(|m| m.image_size_limit),
+                max_video_bytes: media.and_then(|m| m.video_size_limit),
+                detected: true,
+            })
+        }
+        Ok(response) => {
+            let status = response.status();
+            let duration_ms = started.elapsed().as_millis() as u64;
+            let body = response.text().await.unwrap_or_default();
+            warn!(
+                platform = "mastodon",
+                request = "instance",
+                status = status.as_u16(),
+                duration_ms,
+                %body,
+                "Failed to fetch instance info"
+            );
+            None
+        }
+        Err(err) => {
+            error!(platform = "mastodon", request = "instance", error = ?err, "Error fetching instance info");
+            None
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// An image attached to a post.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct ImageAttachment {
+    pub path: String,
+    /// Description for screen readers.
+    #[serde(default)]
+    pub alt: String,
+}
+
+/// An attachment read from disk, ready to upload.
+pub struct LoadedImage {
+    pub bytes: Vec<u8>,
+    pub mime_type: &'static str,
+    /// Pixel width and height, when the format could be read.
+    pub dimensions: Option<(usize, usize)>,
+}
+
+/// The MIME type of an image, from its file extension.
+pub fn mime_type(path: &str) -> Option<&'static str> {
+    let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
+}
+
+pub fn load(image: &ImageAttachment) -> Result<LoadedImage, String> {
+    let mime_type =
+        mime_type(&image.path).ok_or_else(|| format!("{} isn't a PNG, JPEG, GIF or WebP image", image.path))?;
+    let bytes = fs::read(&image.path).map_err(|err| format!("Failed to read {}: {}", image.path, err))?;
+    let dimensions = imagesize::blob_size(&bytes).ok().map(|size| (size.width, size.height));
+    Ok(LoadedImage {
+        bytes,
+        mime_type,
+        dimensions,
+    })
+}
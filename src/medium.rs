@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+use tracing::{error, info, warn};
+
+use crate::posts::PostError;
+use crate::{audit, http, markdown, settings, tokens};
+
+const TOKEN_FILE: &str = "medium_tokens.json";
+/// Upgrades of the stored `TokenData` written by older versions; see `tokens::load`.
+const TOKEN_MIGRATIONS: &[tokens::Migration] = &[];
+const DEFAULT_API_BASE_URL: &str = "https://api.medium.com";
+/// Longest title Medium shows in full.
+const MAX_TITLE_CHARS: usize = 100;
+
+fn api_base_url() -> String {
+    settings::api_base_url("medium", DEFAULT_API_BASE_URL)
+}
+
+/// An integration token, made under Medium's Settings → Security and apps.  It doesn't expire.
+#[derive(Serialize, Deserialize)]
+pub struct TokenData {
+    pub integration_token: String,
+}
+
+pub fn save_token(integration_token: &str) {
+    let token_data = TokenData {
+        integration_token: integration_token.to_string(),
+    };
+    tokens::save(TOKEN_FILE, TOKEN_MIGRATIONS, &token_data);
+}
+
+/// The stored token, or `MEDIUM_INTEGRATION_TOKEN` when none has been stored.
+pub fn load_token() -> Option<String> {
+    tokens::load::<TokenData>(TOKEN_FILE, TOKEN_MIGRATIONS)
+        .map(|token_data| token_data.integration_token)
+        .or_else(|| std::env::var("MEDIUM_INTEGRATION_TOKEN").ok())
+}
+
+#[derive(Deserialize)]
+struct Data<T> {
+    data: T,
+}
+
+/// The id of the token's user, from `/v1/me`.  `None` when Medium refuses the token.
+pub async fn user_id(token: &str) -> Option<String> {
+    #[derive(Deserialize)]
+    struct User {
+        id: String,
+    }
+
+    let started = Instant::now();
+    match http::send_idempotent(
+        http::client()
+            .get(format!("{}/v1/me", api_base_url()))
+            .bearer_auth(token),
+    )
+    .await
+    {
+        Ok(response) if response.status().is_success() => {
+            response.json::<Data<User>>().await.ok().map(|user| user.data.id)
+        }
+        Ok(response) => {
+            let status = response.status();
+            let duration_ms = started.elapsed().as_millis() as u64;
+            let body = response.text().await.unwrap_or_default();
+            warn!(
+                platform = "medium",
+                request = "me",
+                status = status.as_u16(),
+                duration_ms,
+                %body,
+                "Failed to fetch the Medium user"
+            );
+            None
+        }
+        Err(err) => {
+            error!(platform = "medium", request = "me", error = ?err, "Error fetching the Medium user");
+            None
+        }
+    }
+}
+
+/// Checks `integration_token` with Medium and stores it.  Returns whether it works.
+pub async fn authorize_medium(integration_token: &str) -> bool {
+    let integration_token = integration_token.trim();
+    if user_id(integration_token).await.is_none() {
+        return false;
+    }
+    save_token(integration_token);
+    audit::record(audit::Action::Authorize, Some("Medium"), "");
+    info!(platform = "medium", "Authorized Medium");
+    true
+}
+
+/// Publishes the post as a Medium story written in Markdown, or as a draft when `settings.medium_drafts` is on.
+/// Returns the story's id and address.
+pub async fn post_to_medium(token: &str, text: &str) -> Result<(String, String), PostError> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct StoryPost<'a> {
+        title: String,
+        content_format: &'a str,
+        content: &'a str,
+        publish_status: &'a str,
+    }
+
+    #[derive(Deserialize)]
+    struct Story {
+        id: String,
+        url: String,
+    }
+
+    let user_id = user_id(token).await.ok_or(PostError::Unauthorized)?;
+    let story = StoryPost {
+        title: markdown::title(text, MAX_TITLE_CHARS).unwrap_or_else(|| "Untitled".to_string()),
+        content_format: "markdown",
+        content: text,
+        publish_status: if settings::get().medium_drafts {
+            "draft"
+        } else {
+            "public"
+        },
+    };
+
+    let started = Instant::now();
+    match http::send(
+        http::client()
+            .post(format!("{}/v1/users/{}/posts", api_base_url(), user_id))
+            .bearer_auth(token)
+            .json(&story),
+    )
+    .await
+    {
+        Ok(response) => {
+            let status = response.status();
+            let duration_ms = started.elapsed().as_millis() as u64;
+            let body = response.text().await.unwrap_or_default();
+            if status.is_success() {
+                match serde_json::from_str::<Data<Story>>(&body) {
+                    Ok(created) => {
+                        info!(
+                            platform = "medium",
+                            request = "posts",
+                            status = status.as_u16(),
+                            duration_ms,
+                            post_id = %created.data.id,
+                            "Published story"
+                        );
+                        Ok((created.data.id, created.data.url))
+                    }
+                    Err(_) => {
+                        error!(
+                            platform = "medium",
+                            request = "posts",
+                            "Published, but failed to parse the story response"
+                        );
+                        Err(PostError::Rejected)
+                    }
+                }
+            } else {
+                warn!(
+                    platform = "medium",
+                    request = "posts",
+                    status = status.as_u16(),
+                    duration_ms,
+                    %body,
+                    "Failed to publish story"
+                );
+                Err(PostError::from_status(status))
+            }
+        }
+        Err(err) => {
+            error!(platform = "medium", request = "posts", error = ?err, "Error publishing to Medium");
+            Err(PostError::Network)
+        }
+    }
+}
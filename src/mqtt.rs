@@ -0,0 +1,143 @@
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{debug, error, info, warn};
+
+use crate::{history, posts, publish, settings, shared};
+
+const DEFAULT_PORT: u16 = 1883;
+/// How often the settings are checked for a broker to connect to, or a changed one to reconnect to.
+const SETTINGS_INTERVAL: Duration = Duration::from_secs(60);
+/// Wait before reconnecting after the broker drops the connection or can't be reached.
+const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+/// The connection settings, compared to notice when they change.
+#[derive(Clone, PartialEq)]
+struct Subscription {
+    broker: String,
+    topic: String,
+    username: String,
+    password: String,
+}
+
+impl Subscription {
+    /// The subscription from settings, or `None` when no broker or topic is set.
+    fn from_settings() -> Option<Self> {
+        let settings = settings::get();
+        let subscription = Subscription {
+            broker: settings.mqtt_broker.trim().to_string(),
+            topic: settings.mqtt_topic.trim().to_string(),
+            username: settings.mqtt_username,
+            password: settings.mqtt_password,
+        };
+        (!subscription.broker.is_empty() && !subscription.topic.is_empty()).then_some(subscription)
+    }
+
+    fn options(&self) -> MqttOptions {
+        let address = self.broker.trim_start_matches("mqtt://").trim_start_matches("tcp://");
+        let (host, port) = match address.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().unwrap_or(DEFAULT_PORT)),
+            None => (address, DEFAULT_PORT),
+        };
+        let client_id = format!("multique-{}", &history::new_idempotency_key()[..8]);
+        let mut options = MqttOptions::new(client_id, host, port);
+        options.set_keep_alive(Duration::from_secs(30));
+        if !self.username.is_empty() {
+            options.set_credentials(&self.username, &self.password);
+        }
+        options
+    }
+}
+
+/// Fills in `{message}` with the payload, and `{field}` with the top-level fields of a JSON object payload (e.g.
+/// `{"title": "Speedrunning", "url": "https://twitch.tv/me"}` for "Live now: {title} {url}").
+pub fn render(template: &str, payload: &str) -> String {
+    let mut text = template.replace("{message}", payload.trim());
+    if let Ok(serde_json::Value::Object(fields)) = serde_json::from_str(payload) {
+        for (name, value) in fields {
+            let value = match value {
+                serde_json::Value::String(value) => value,
+                serde_json::Value::Null => String::new(),
+                value => value.to_string(),
+            };
+            text = text.replace(&format!("{{{}}}", name), &value);
+        }
+    }
+    text
+}
+
+/// Subscribes to `settings.mqtt_topic` on `settings.mqtt_broker` and posts every message published there, through
+/// `settings.mqtt_template`, to `settings.mqtt_platforms`.  Retained messages are skipped so reconnecting doesn't
+/// repost an old one.  Reconnects when the connection drops or the settings change.
+pub async fn listen(state: Arc<Mutex<posts::AppState>>) {
+    loop {
+        let Some(subscription) = Subscription::from_settings() else {
+            tokio::time::sleep(SETTINGS_INTERVAL).await;
+            continue;
+        };
+        run(&state, &subscription).await;
+    }
+}
+
+/// One connection, until it fails or the settings change.
+async fn run(state: &Arc<Mutex<posts::AppState>>, subscription: &Subscription) {
+    let (client, mut events) = AsyncClient::new(subscription.options(), 10);
+    let mut settings_check = tokio::time::interval(SETTINGS_INTERVAL);
+    settings_check.tick().await;
+    loop {
+        tokio::select! {
+            event = events.poll() => match event {
+                // Subscriptions don't survive a reconnect with a clean session
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    info!(broker = %subscription.broker, topic = %subscription.topic, "Connected to MQTT broker");
+                    if let Err(err) = client.subscribe(&subscription.topic, QoS::AtLeastOnce).await {
+                        error!(error = ?err, "Failed to subscribe to the MQTT topic");
+                    }
+                }
+                Ok(Event::Incoming(Packet::Publish(message))) => {
+                    if message.retain {
+                        debug!(topic = %message.topic, "Skipping retained MQTT message");
+                        continue;
+                    }
+                    let payload = String::from_utf8_lossy(&message.payload).to_string();
+                    post(state, &message.topic, &payload).await;
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    warn!(broker = %subscription.broker, error = ?err, "MQTT connection failed; retrying");
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                    if Subscription::from_settings().as_ref() != Some(subscription) {
+                        break;
+                    }
+                }
+            },
+            _ = settings_check.tick() => {
+                if Subscription::from_settings().as_ref() != Some(subscription) {
+                    info!("MQTT settings changed; reconnecting");
+                    break;
+                }
+            }
+        }
+    }
+    let _ = client.disconnect().await;
+}
+
+async fn post(state: &Arc<Mutex<posts::AppState>>, topic: &str, payload: &str) {
+    let settings = settings::get();
+    let text = render(&settings.mqtt_template, payload);
+    if text.trim().is_empty() {
+        warn!(topic, "MQTT message made an empty post; skipping");
+        return;
+    }
+    info!(topic, "Posting an MQTT message");
+    publish::publish(
+        state,
+        &text,
+        &settings.mqtt_platforms,
+        &history::new_idempotency_key(),
+        &posts::PostOptions::default(),
+        &shared::current_user(),
+    )
+    .await;
+}
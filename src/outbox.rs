@@ -0,0 +1,49 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::history;
+use crate::posts::PostOptions;
+
+const OUTBOX_FILE: &str = "outbox.json";
+
+/// A post waiting to be sent once connectivity returns.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct OutboxItem {
+    pub text: String,
+    pub platforms: Vec<String>,
+    pub queued_at: DateTime<Utc>,
+    #[serde(default = "history::new_idempotency_key")]
+    pub idempotency_key: String,
+    #[serde(default)]
+    pub options: PostOptions,
+}
+
+pub fn load_outbox() -> Vec<OutboxItem> {
+    if Path::new(OUTBOX_FILE).exists() {
+        let json = fs::read_to_string(OUTBOX_FILE).expect("Failed to read outbox file");
+        serde_json::from_str(&json).unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+pub fn save_outbox(items: &[OutboxItem]) {
+    let json = serde_json::to_string_pretty(items).expect("Failed to serialize outbox");
+    fs::write(OUTBOX_FILE, json).expect("Failed to write outbox file");
+}
+
+/// Holds a post in the outbox.  Returns how many posts it holds now.
+pub fn push(text: &str, platforms: &[String], idempotency_key: &str, options: &PostOptions) -> usize {
+    let mut items = load_outbox();
+    items.push(OutboxItem {
+        text: text.to_string(),
+        platforms: platforms.to_vec(),
+        queued_at: Utc::now(),
+        idempotency_key: idempotency_key.to_string(),
+        options: options.clone(),
+    });
+    save_outbox(&items);
+    items.len()
+}
@@ -0,0 +1,339 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tokio::runtime::Handle;
+use tracing::{error, info, warn};
+use wasmtime::{Caller, Engine, Extern, Instance, Linker, Memory, Module, Store, TypedFunc};
+
+use crate::posts::PostError;
+use crate::{audit, http, tokens};
+
+const PLUGINS_DIR: &str = "plugins";
+
+static PLUGINS: OnceLock<Vec<Plugin>> = OnceLock::new();
+
+/// A platform connector shipped as a WebAssembly module.  See "Platform plugins" in the README for the interface a
+/// module has to export.  The name is leaked so it can be used as a platform key alongside the built-in ones.
+pub struct Plugin {
+    pub name: &'static str,
+    path: PathBuf,
+    engine: Engine,
+    module: Module,
+}
+
+/// What the host keeps per call: the runtime handle used to run HTTP requests for the plugin, when there is one.
+struct HostState {
+    runtime: Option<Handle>,
+}
+
+#[derive(Serialize)]
+struct PostRequest<'a> {
+    token: &'a str,
+    text: &'a str,
+}
+
+#[derive(Deserialize)]
+struct PostResponse {
+    ok: bool,
+    id: Option<String>,
+    url: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct HttpRequest {
+    method: String,
+    url: String,
+    #[serde(default)]
+    headers: Vec<(String, String)>,
+    #[serde(default)]
+    body: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PluginTokens {
+    token: String,
+}
+
+/// Loads every plugin in the plugins directory.  Broken plugins are logged and skipped.  Safe to call more than
+/// once; only the first call loads.
+pub fn load() -> &'static [Plugin] {
+    PLUGINS.get_or_init(|| {
+        let Ok(entries) = fs::read_dir(PLUGINS_DIR) else {
+            return Vec::new();
+        };
+        let engine = Engine::default();
+        let mut plugins: Vec<Plugin> = Vec::new();
+        for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+            if path.extension().and_then(|ext| ext.to_str()) != Some("wasm") {
+                continue;
+            }
+            match load_plugin(&engine, &path) {
+                Ok(plugin) if plugins.iter().any(|p| p.name == plugin.name) => {
+                    warn!(path = %path.display(), name = plugin.name, "Skipping plugin with a duplicate name");
+                }
+                Ok(plugin) => {
+                    info!(path = %path.display(), name = plugin.name, "Loaded platform plugin");
+                    plugins.push(plugin);
+                }
+                Err(err) => error!(path = %path.display(), error = %err, "Failed to load plugin"),
+            }
+        }
+        plugins.sort_by_key(|plugin| plugin.name);
+        plugins
+    })
+}
+
+/// Plugins loaded so far; empty until `load` is called.
+pub fn all() -> &'static [Plugin] {
+    PLUGINS.get().map(Vec::as_slice).unwrap_or_default()
+}
+
+pub fn find(name: &str) -> Option<&'static Plugin> {
+    all().iter().find(|plugin| plugin.name == name)
+}
+
+fn load_plugin(engine: &Engine, path: &Path) -> Result<Plugin, String> {
+    let module = Module::from_file(engine, path).map_err(|err| err.to_string())?;
+    let mut plugin = Plugin {
+        name: "",
+        path: path.to_path_buf(),
+        engine: engine.clone(),
+        module,
+    };
+    let name = plugin.call_string("multique_name", None)?;
+    let name = name.trim();
+    // Names become platform keys and token file names, so keep them plain
+    if name.is_empty() || name.contains(['/', '\\', '.']) {
+        return Err(format!("Unusable plugin name \"{}\"", name));
+    }
+    plugin.name = Box::leak(name.to_string().into_boxed_str());
+    Ok(plugin)
+}
+
+impl Plugin {
+    fn tokens_file(&self) -> String {
+        format!("plugin_{}_tokens.json", self.name.to_lowercase().replace(' ', "_"))
+    }
+
+    pub fn load_token(&self) -> Option<String> {
+        tokens::load::<PluginTokens>(&self.tokens_file(), &[]).map(|tokens| tokens.token)
+    }
+
+    fn save_token(&self, token: &str) {
+        let tokens = PluginTokens {
+            token: token.to_string(),
+        };
+        tokens::save(&self.tokens_file(), &[], &tokens);
+    }
+
+    pub async fn auth_url(&'static self) -> Option<String> {
+        self.call_blocking("multique_auth_url", None).await.ok()
+    }
+
+    /// Exchanges an authorization code for a token and saves it.  Returns whether it worked.
+    pub async fn authorize(&'static self, code: &str) -> bool {
+        match self
+            .call_blocking("multique_exchange_token", Some(code.to_string()))
+            .await
+        {
+            Ok(token) if !token.trim().is_empty() => {
+                self.save_token(token.trim());
+                info!(platform = self.name, "Plugin authorized successfully");
+                audit::record(audit::Action::Authorize, Some(self.name), "");
+                true
+            }
+            Ok(_) => {
+                warn!(platform = self.name, "Plugin rejected the authorization code");
+                false
+            }
+            Err(err) => {
+                error!(platform = self.name, error = %err, "Plugin failed to exchange the authorization code");
+                false
+            }
+        }
+    }
+
+    /// Posts through the plugin, returning the post id and URL when it reports them.
+    pub async fn post(&'static self, text: &str) -> Result<(Option<String>, Option<String>), PostError> {
+        let token = self.load_token().ok_or(PostError::Unauthorized)?;
+        let request = serde_json::to_string(&PostRequest { token: &token, text })
+            .expect("Failed to serialize plugin post request");
+        let output = self
+            .call_blocking("multique_post", Some(request))
+            .await
+            .map_err(|err| {
+                error!(platform = self.name, error = %err, "Plugin failed while posting");
+                PostError::Rejected
+            })?;
+        let response: PostResponse = serde_json::from_str(&output).map_err(|err| {
+            error!(platform = self.name, error = ?err, %output, "Plugin returned an unexpected post response");
+            PostError::Rejected
+        })?;
+        if response.ok {
+            return Ok((response.id, response.url));
+        }
+        Err(match response.error.as_deref() {
+            Some("unauthorized") => PostError::Unauthorized,
+            Some("rate_limited") => PostError::RateLimited,
+            Some("network") => PostError::Network,
+            _ => PostError::Rejected,
+        })
+    }
+
+    /// Runs an export off the async runtime, since plugin code (and its HTTP calls) block.
+    async fn call_blocking(&'static self, export: &'static str, input: Option<String>) -> Result<String, String> {
+        tokio::task::spawn_blocking(move || self.call_string(export, input.as_deref()))
+            .await
+            .map_err(|err| err.to_string())?
+    }
+
+    /// Calls an export in a fresh instance, passing `input` (if any) and returning its string result.
+    fn call_string(&self, export: &str, input: Option<&str>) -> Result<String, String> {
+        let mut store = Store::new(
+            &self.engine,
+            HostState {
+                runtime: Handle::try_current().ok(),
+            },
+        );
+        let instance = self
+            .linker()?
+            .instantiate(&mut store, &self.module)
+            .map_err(|err| err.to_string())?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or("Plugin doesn't export its memory")?;
+
+        let packed = match input {
+            Some(input) => {
+                let (ptr, len) = write_guest(&mut store, &instance, memory, input.as_bytes())?;
+                let func: TypedFunc<(i32, i32), i64> = instance
+                    .get_typed_func(&mut store, export)
+                    .map_err(|err| format!("{}: {}", export, err))?;
+                func.call(&mut store, (ptr, len)).map_err(|err| err.to_string())?
+            }
+            None => {
+                let func: TypedFunc<(), i64> = instance
+                    .get_typed_func(&mut store, export)
+                    .map_err(|err| format!("{}: {}", export, err))?;
+                func.call(&mut store, ()).map_err(|err| err.to_string())?
+            }
+        };
+        let bytes =
+            read_guest(memory.data(&store), packed).ok_or_else(|| format!("{} returned a bad pointer", export))?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| format!("{} didn't return UTF-8", export))
+    }
+
+    fn linker(&self) -> Result<Linker<HostState>, String> {
+        let mut linker = Linker::new(&self.engine);
+        // While loading, the name isn't known yet
+        let source = match self.name {
+            "" => self.path.display().to_string(),
+            name => name.to_string(),
+        };
+        linker
+            .func_wrap(
+                "multique",
+                "log",
+                move |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+                    if let Some(Extern::Memory(memory)) = caller.get_export("memory") {
+                        if let Some(bytes) = guest_slice(memory.data(&caller), ptr, len) {
+                            info!(plugin = %source, "{}", String::from_utf8_lossy(bytes));
+                        }
+                    }
+                },
+            )
+            .map_err(|err| err.to_string())?;
+        linker
+            .func_wrap(
+                "multique",
+                "http_request",
+                |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> wasmtime::Result<i64> {
+                    let Some(Extern::Memory(memory)) = caller.get_export("memory") else {
+                        return Err(wasmtime::Error::msg("Plugin doesn't export its memory"));
+                    };
+                    let input = guest_slice(memory.data(&caller), ptr, len)
+                        .map(<[u8]>::to_vec)
+                        .unwrap_or_default();
+                    let output = match caller.data().runtime.clone() {
+                        Some(runtime) => runtime.block_on(http_request(&input)),
+                        None => serde_json::json!({ "error": "HTTP isn't available while loading" })
+                            .to_string()
+                            .into_bytes(),
+                    };
+                    let Some(Extern::Func(alloc)) = caller.get_export("multique_alloc") else {
+                        return Err(wasmtime::Error::msg("Plugin doesn't export multique_alloc"));
+                    };
+                    let out_ptr = alloc
+                        .typed::<i32, i32>(&caller)?
+                        .call(&mut caller, output.len() as i32)?;
+                    memory.write(&mut caller, out_ptr as usize, &output)?;
+                    Ok(((out_ptr as i64) << 32) | output.len() as i64)
+                },
+            )
+            .map_err(|err| err.to_string())?;
+        Ok(linker)
+    }
+}
+
+fn write_guest(
+    store: &mut Store<HostState>,
+    instance: &Instance,
+    memory: Memory,
+    bytes: &[u8],
+) -> Result<(i32, i32), String> {
+    let alloc: TypedFunc<i32, i32> = instance
+        .get_typed_func(&mut *store, "multique_alloc")
+        .map_err(|err| format!("multique_alloc: {}", err))?;
+    let ptr = alloc
+        .call(&mut *store, bytes.len() as i32)
+        .map_err(|err| err.to_string())?;
+    memory
+        .write(&mut *store, ptr as usize, bytes)
+        .map_err(|err| err.to_string())?;
+    Ok((ptr, bytes.len() as i32))
+}
+
+fn guest_slice(data: &[u8], ptr: i32, len: i32) -> Option<&[u8]> {
+    let ptr = ptr as u32 as usize;
+    data.get(ptr..ptr.checked_add(len as u32 as usize)?)
+}
+
+/// Reads a string returned as `(ptr << 32) | len`.
+fn read_guest(data: &[u8], packed: i64) -> Option<&[u8]> {
+    guest_slice(data, (packed >> 32) as i32, packed as i32)
+}
+
+/// Performs an HTTP request on a plugin's behalf, returning the JSON it gets back.
+async fn http_request(input: &[u8]) -> Vec<u8> {
+    let response = match serde_json::from_slice::<HttpRequest>(input) {
+        Ok(request) => match send(request).await {
+            Ok((status, body)) => serde_json::json!({ "status": status, "body": body }),
+            Err(err) => serde_json::json!({ "error": err }),
+        },
+        Err(err) => serde_json::json!({ "error": format!("Malformed request: {}", err) }),
+    };
+    response.to_string().into_bytes()
+}
+
+async fn send(request: HttpRequest) -> Result<(u16, String), String> {
+    let method =
+        reqwest::Method::from_bytes(request.method.to_uppercase().as_bytes()).map_err(|err| err.to_string())?;
+    let mut builder = http::client().request(method.clone(), &request.url);
+    for (name, value) in request.headers {
+        builder = builder.header(name, value);
+    }
+    if let Some(body) = request.body {
+        builder = builder.body(body);
+    }
+    let response = if method == reqwest::Method::GET {
+        http::send_idempotent(builder).await
+    } else {
+        http::send(builder).await
+    }
+    .map_err(|err| err.to_string())?;
+    let status = response.status().as_u16();
+    let body = response.text().await.map_err(|err| err.to_string())?;
+    Ok((status, body))
+}
@@ -1,10 +1,233 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use crate::autocomplete::Suggestion;
+use crate::engagement::{ComparisonRow, Sample};
+use crate::github::ReleaseDraft;
+use crate::history::HistoryEntry;
+use crate::inbox::Notification;
+use crate::limits::{self, PlatformLimits};
+use crate::lint::Warning;
+#[cfg(feature = "mastodon")]
+use crate::mastodon;
+use crate::media::ImageAttachment;
+use crate::queues::Queue;
+use crate::scheduler::{Pause, ScheduledPost};
+
+/// Where a post stands on one platform while it is being published.
+#[derive(Clone, Copy, PartialEq)]
+pub enum PostProgress {
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+/// Why a post failed on one platform.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum PostError {
+    /// The token was rejected and couldn't be refreshed; the platform needs authorizing again.
+    Unauthorized,
+    RateLimited,
+    /// The platform refused the post (too long, duplicate, malformed response, ...).
+    Rejected,
+    /// The platform couldn't be reached.
+    Network,
+}
+
+impl PostError {
+    pub fn from_status(status: reqwest::StatusCode) -> Self {
+        match status.as_u16() {
+            401 | 403 => PostError::Unauthorized,
+            429 => PostError::RateLimited,
+            _ => PostError::Rejected,
+        }
+    }
+}
+
+const TOAST_LIFETIME: Duration = Duration::from_secs(4);
+
+/// Choices made for one post, kept with it while it waits in the schedule or outbox.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PostOptions {
+    /// Show a preview card for links where the platform lets the poster choose (Bluesky).
+    pub link_previews: bool,
+    /// Images to attach.  Only Bluesky uploads them so far.
+    pub images: Vec<ImageAttachment>,
+    /// Text written for one platform (or account), posted there as is instead of the composed text.
+    pub texts: HashMap<String, String>,
+    /// Shown on Mastodon in place of the post until it's opened; empty for none.
+    pub content_warning: String,
+    pub visibility: Visibility,
+    pub reply_settings: ReplySettings,
+    /// The posts after the composed text in a thread written post by post, in order.  Empty for a single post, which
+    /// may still be split into a thread when it's too long.
+    pub thread: Vec<ThreadSegment>,
+    /// Days after posting that it's deleted again, on the platforms that can delete (see `expiry`).  `None` keeps it.
+    pub expire_after_days: Option<u32>,
+}
+
+/// One post of a thread written post by post, after the first.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThreadSegment {
+    pub text: String,
+    pub images: Vec<ImageAttachment>,
+}
+
+impl Default for PostOptions {
+    fn default() -> Self {
+        Self {
+            link_previews: true,
+            images: Vec::new(),
+            texts: HashMap::new(),
+            content_warning: String::new(),
+            visibility: Visibility::default(),
+            reply_settings: ReplySettings::default(),
+            thread: Vec::new(),
+            expire_after_days: None,
+        }
+    }
+}
+
+/// Who sees a post on Mastodon.  Serialized as the API names it.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Visibility {
+    #[default]
+    Public,
+    /// Public, but kept out of the public timelines.
+    Unlisted,
+    /// Followers only.
+    Private,
+    /// Mentioned accounts only.
+    Direct,
+}
+
+impl Visibility {
+    pub const ALL: [Visibility; 4] = [
+        Visibility::Public,
+        Visibility::Unlisted,
+        Visibility::Private,
+        Visibility::Direct,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Visibility::Public => "Public",
+            Visibility::Unlisted => "Unlisted",
+            Visibility::Private => "Followers only",
+            Visibility::Direct => "Mentioned only",
+        }
+    }
+}
+
+/// Who can reply to a tweet.  Serialized as the API names it.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReplySettings {
+    #[default]
+    Everyone,
+    Following,
+    MentionedUsers,
+}
+
+impl ReplySettings {
+    pub const ALL: [ReplySettings; 3] = [
+        ReplySettings::Everyone,
+        ReplySettings::Following,
+        ReplySettings::MentionedUsers,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ReplySettings::Everyone => "Everyone",
+            ReplySettings::Following => "Accounts you follow",
+            ReplySettings::MentionedUsers => "Mentioned accounts",
+        }
+    }
+}
+
+/// A transient notification shown in the corner of the window.
+pub struct Toast {
+    pub message: String,
+    pub success: bool,
+    pub created: Instant,
+}
+
 #[derive(Default)]
 pub struct AppState {
     pub twitter_authorized: bool,
     pub mastodon_authorized: bool,
     pub bluesky_authorized: bool,
     pub linkedin_authorized: bool,
+    pub medium_authorized: bool,
+    pub hashnode_authorized: bool,
     pub post_text: String,
+    pub compose_key: String, // Idempotency key of the post being composed
+    pub compose_options: PostOptions,
+    pub compose_targets: Option<Vec<String>>, // Accounts picked in the composer, overriding the checkboxes for one post
     pub bluesky_token: Option<String>,
     pub did: Option<String>,
+    pub history: Vec<HistoryEntry>,
+    pub comparisons: HashMap<String, Vec<ComparisonRow>>, // Keyed by history entry id
+    pub engagement_samples: HashMap<String, Vec<Sample>>, // Engagement over time, keyed by history entry id
+    pub offline: bool,
+    pub outbox_count: usize,
+    pub posting: bool,                              // A post is in flight
+    pub linting: bool,                              // The checks before posting are still looking up mentions
+    pub lint_warnings: Option<Vec<Warning>>,        // Found by the checks before posting, until posted or cancelled
+    pub post_progress: Vec<(String, PostProgress)>, // Per-platform progress of the latest post
+    pub toasts: Vec<Toast>,
+    pub detected_limits: HashMap<String, PlatformLimits>, // Limits reported by the servers, keyed by platform
+    pub release_drafts: Vec<ReleaseDraft>,                // Release announcements waiting for review
+    pub plugins_authorized: HashSet<String>,              // Names of plugin platforms with a stored token
+    pub mention_suggestions: Option<(String, Vec<Suggestion>)>, // Handles found for the mention being typed
+    pub trending_tags: Vec<String>,                       // Trending on the Mastodon instance, without `#`
+    pub scheduler_checked_at: Option<DateTime<Utc>>,      // When the scheduler last started looking for due posts
+    pub schedule: Vec<ScheduledPost>,                     // Posts waiting to go out, soonest first
+    pub queues: Vec<Queue>,                               // Reloaded with the schedule
+    pub schedule_pause: Option<Pause>,                    // Set while scheduled posts are paused
+    pub inbox: Vec<Notification>,                         // Notifications received on the accounts, newest first
+    #[cfg(feature = "mastodon")]
+    pub mastodon_accounts: Vec<mastodon::Account>, // Accounts on other instances than the main one
+}
+
+impl AppState {
+    pub fn toast(&mut self, message: impl Into<String>, success: bool) {
+        self.toasts.push(Toast {
+            message: message.into(),
+            success,
+            created: Instant::now(),
+        });
+    }
+
+    /// Drops toasts that have been shown long enough.
+    pub fn expire_toasts(&mut self) {
+        self.toasts.retain(|toast| toast.created.elapsed() < TOAST_LIFETIME);
+    }
+
+    /// Whether `platform` is an additional Mastodon account with a token.
+    #[cfg(feature = "mastodon")]
+    pub fn mastodon_account_authorized(&self, platform: &str) -> bool {
+        self.mastodon_accounts
+            .iter()
+            .any(|account| account.platform() == platform && account.access_token.is_some())
+    }
+
+    /// Limits for a platform: what its server reported, or the built-in defaults.
+    pub fn limits(&self, platform: &str) -> PlatformLimits {
+        self.detected_limits
+            .get(platform)
+            .copied()
+            .unwrap_or_else(|| limits::default_for(platform))
+    }
+
+    pub fn set_progress(&mut self, platform: &str, progress: PostProgress) {
+        if let Some(entry) = self.post_progress.iter_mut().find(|(p, _)| p == platform) {
+            entry.1 = progress;
+        }
+    }
 }
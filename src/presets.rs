@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::posts::Visibility;
+
+const PRESETS_FILE: &str = "presets.json";
+
+/// A named audience ("Tech" for Mastodon and Bluesky, "Everything"): the platforms to check, and optionally what
+/// posts to it carry, applied with one click in the composer.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Preset {
+    pub name: String,
+    pub platforms: Vec<String>,
+    /// Added to the end of the post unless already in it, space separated, e.g. "#rust #oss".
+    #[serde(default)]
+    pub hashtags: String,
+    /// Line added below the post, e.g. "— Erik from Multique".
+    #[serde(default)]
+    pub signature: String,
+    /// Mastodon visibility to switch to; `None` leaves it as it is.
+    #[serde(default)]
+    pub visibility: Option<Visibility>,
+}
+
+impl Preset {
+    pub fn new(name: &str, platforms: Vec<String>) -> Self {
+        Preset {
+            name: name.to_string(),
+            platforms,
+            hashtags: String::new(),
+            signature: String::new(),
+            visibility: None,
+        }
+    }
+
+    /// `text` with the preset's hashtags and signature added, leaving out those it already has, so applying a
+    /// preset twice doesn't repeat them.
+    pub fn decorate(&self, text: &str) -> String {
+        let lower = text.to_lowercase();
+        let tags: Vec<String> = self
+            .hashtags
+            .split_whitespace()
+            .map(|tag| format!("#{}", tag.trim_start_matches('#')))
+            .filter(|tag| tag.len() > 1 && !lower.contains(&tag.to_lowercase()))
+            .collect();
+        let signature = self.signature.trim();
+
+        let mut lines = Vec::new();
+        if !tags.is_empty() {
+            lines.push(tags.join(" "));
+        }
+        if !signature.is_empty() && !text.contains(signature) {
+            lines.push(signature.to_string());
+        }
+        if lines.is_empty() {
+            text.to_string()
+        } else if text.trim().is_empty() {
+            lines.join("\n")
+        } else {
+            format!("{}\n\n{}", text.trim_end(), lines.join("\n"))
+        }
+    }
+}
+
+pub fn load_presets() -> Vec<Preset> {
+    if Path::new(PRESETS_FILE).exists() {
+        let json = fs::read_to_string(PRESETS_FILE).expect("Failed to read presets file");
+        serde_json::from_str(&json).unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+pub fn save_presets(presets: &[Preset]) {
+    let json = serde_json::to_string_pretty(presets).expect("Failed to serialize presets");
+    fs::write(PRESETS_FILE, json).expect("Failed to write presets file");
+}
@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+use crate::posts::PostOptions;
+
+/// What the filter does with a flagged word.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Fix {
+    /// Keeps the first letter and stars out the rest ("s***").
+    Mask,
+    /// Swaps in the milder word from the list, or masks when there is none.
+    Replace,
+}
+
+/// Flagged words and their replacements; an empty replacement means there is none.
+fn words() -> &'static HashMap<&'static str, &'static str> {
+    static WORDS: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+    WORDS.get_or_init(|| {
+        include_str!("profanity_words.txt")
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| line.split_once(' ').unwrap_or((line, "")))
+            .collect()
+    })
+}
+
+/// Byte ranges of the flagged words in `text`.
+pub fn find(text: &str) -> Vec<Range<usize>> {
+    let mut found = Vec::new();
+    let mut start = None;
+    for (index, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        match (start, c.is_alphanumeric()) {
+            (None, true) => start = Some(index),
+            (Some(word_start), false) => {
+                if words().contains_key(text[word_start..index].to_lowercase().as_str()) {
+                    found.push(word_start..index);
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    found
+}
+
+/// `text` with every flagged word fixed.
+pub fn fix(text: &str, fix: Fix) -> String {
+    let mut fixed = text.to_string();
+    for range in find(text).into_iter().rev() {
+        let word = &text[range.clone()];
+        let replacement = words()[word.to_lowercase().as_str()];
+        let new = match fix {
+            Fix::Replace if !replacement.is_empty() => match_case(word, replacement),
+            _ => mask(word),
+        };
+        fixed.replace_range(range, &new);
+    }
+    fixed
+}
+
+/// Fixes the flagged words in everything a post is made of: its text, the texts written for each platform and the
+/// other posts of a thread.  Returns how many words were changed.
+pub fn fix_post(text: &mut String, options: &mut PostOptions, how: Fix) -> usize {
+    let texts = std::iter::once(text)
+        .chain(options.texts.values_mut())
+        .chain(options.thread.iter_mut().map(|segment| &mut segment.text));
+    let mut changed = 0;
+    for text in texts {
+        changed += find(text).len();
+        *text = fix(text, how);
+    }
+    changed
+}
+
+fn mask(word: &str) -> String {
+    let mut chars = word.chars();
+    let first = chars.next().map(String::from).unwrap_or_default();
+    first + &"*".repeat(chars.count())
+}
+
+/// `replacement` capitalized like `word`: all caps, first letter only, or as written.
+fn match_case(word: &str, replacement: &str) -> String {
+    let letters: Vec<char> = word.chars().filter(|c| c.is_alphabetic()).collect();
+    if letters.len() > 1 && letters.iter().all(|c| c.is_uppercase()) {
+        replacement.to_uppercase()
+    } else if letters.first().is_some_and(|c| c.is_uppercase()) {
+        let mut chars = replacement.chars();
+        chars
+            .next()
+            .map_or_else(String::new, |first| first.to_uppercase().chain(chars).collect())
+    } else {
+        replacement.to_string()
+    }
+}
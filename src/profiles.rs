@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::rules::ContentRules;
+use crate::settings;
+
+const PROFILES_DIR: &str = "profiles";
+const PROFILE_FILE: &str = "profile.json";
+
+/// The profile that owns the token files in the working directory, as created before profiles existed.
+pub const DEFAULT_PROFILE: &str = "Default";
+
+/// Per-profile preferences, stored alongside that profile's token files.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileConfig {
+    /// Platforms checked by default for new posts.  `None` uses the built-in defaults.
+    pub default_platforms: Option<Vec<String>>,
+    /// Platforms checked when the app last ran, restored at startup when there's no `default_platforms`.
+    pub last_platforms: Option<Vec<String>>,
+    /// Banned words and required disclaimers for the profile's posts.
+    pub content_rules: ContentRules,
+}
+
+/// Name of the active profile.
+pub fn active() -> String {
+    settings::get().active_profile
+}
+
+/// Directory holding a profile's token files and config.  The default profile's is the working directory, or
+/// `MULTIQUE_TOKEN_DIR` when set (e.g. a secrets volume in a container).
+pub fn dir(profile: &str) -> PathBuf {
+    if profile == DEFAULT_PROFILE {
+        std::env::var("MULTIQUE_TOKEN_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("."))
+    } else {
+        Path::new(PROFILES_DIR).join(profile)
+    }
+}
+
+/// Resolves a per-account file (token files, etc.) inside the active profile's directory.
+pub fn path(file: &str) -> PathBuf {
+    dir(&active()).join(file)
+}
+
+/// All known profiles, with the default profile first.
+pub fn list() -> Vec<String> {
+    let mut profiles: Vec<String> = fs::read_dir(PROFILES_DIR)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_dir())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    profiles.sort();
+    profiles.insert(0, DEFAULT_PROFILE.to_string());
+    profiles
+}
+
+/// Token files of every profile, as paths relative to the working directory.
+pub fn token_files() -> Vec<PathBuf> {
+    list()
+        .iter()
+        .filter_map(|profile| fs::read_dir(dir(profile)).ok())
+        .flat_map(|entries| entries.filter_map(|entry| entry.ok()))
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.ends_with("_tokens.json"))
+        })
+        .collect()
+}
+
+/// Whether `path` is somewhere a token file may be restored to: a profile directory, never outside the working
+/// directory.
+pub fn is_token_path(path: &Path) -> bool {
+    let file_name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    let parent = path.parent().unwrap_or(Path::new(""));
+    file_name.ends_with("_tokens.json") && list().iter().any(|profile| dir(profile) == parent)
+}
+
+/// Creates a new, empty profile.  Returns false if the name is unusable or already taken.
+pub fn create(name: &str) -> bool {
+    let name = name.trim();
+    if name.is_empty() || name.contains(['/', '\\', '.']) || list().iter().any(|p| p == name) {
+        return false;
+    }
+    fs::create_dir_all(dir(name)).expect("Failed to create profile directory");
+    true
+}
+
+/// Makes `name` the active profile.
+pub fn switch(name: &str) {
+    let mut settings = settings::get();
+    settings.active_profile = name.to_string();
+    settings::update(settings);
+}
+
+/// Config of the active profile.
+pub fn load_config() -> ProfileConfig {
+    load_config_for(&active())
+}
+
+pub fn save_config(config: &ProfileConfig) {
+    save_config_for(&active(), config);
+}
+
+pub fn load_config_for(profile: &str) -> ProfileConfig {
+    let path = dir(profile).join(PROFILE_FILE);
+    if path.exists() {
+        let json = fs::read_to_string(path).expect("Failed to read profile file");
+        serde_json::from_str(&json).unwrap_or_default()
+    } else {
+        ProfileConfig::default()
+    }
+}
+
+pub fn save_config_for(profile: &str, config: &ProfileConfig) {
+    fs::create_dir_all(dir(profile)).expect("Failed to create profile directory");
+    let json = serde_json::to_string_pretty(config).expect("Failed to serialize profile");
+    fs::write(dir(profile).join(PROFILE_FILE), json).expect("Failed to write profile file");
+}
@@ -0,0 +1,929 @@
+use chrono::{TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::audit;
+#[cfg(feature = "bluesky")]
+use crate::bluesky;
+use crate::budget;
+#[cfg(feature = "demo")]
+use crate::demo;
+#[cfg(feature = "email")]
+use crate::email;
+#[cfg(feature = "hashnode")]
+use crate::hashnode;
+use crate::history;
+use crate::limits::{self, OverLimit, PlatformLimits};
+#[cfg(feature = "linkedin")]
+use crate::linkedin;
+use crate::links;
+#[cfg(feature = "mastodon")]
+use crate::mastodon;
+use crate::media::ImageAttachment;
+#[cfg(feature = "medium")]
+use crate::medium;
+#[cfg(feature = "plugins")]
+use crate::plugins;
+use crate::posts::{self, PostError, PostProgress};
+use crate::profiles;
+use crate::ratelimit;
+use crate::rules::Enforcement;
+use crate::scheduler;
+#[cfg(feature = "scripting")]
+use crate::scripting;
+use crate::settings;
+use crate::threads;
+use crate::timezone;
+use crate::transforms;
+#[cfg(feature = "twitter")]
+use crate::twitter;
+use crate::webhooks;
+
+/// Posts `text` to each platform in `platforms` that is authorized, recording the outcome in history.
+///
+/// Platforms that already have a successful post under `idempotency_key` are skipped, so retrying a compose action
+/// only resends to the platforms that failed.  Progress is reported per platform through `AppState::post_progress`;
+/// the state lock is only held briefly so the UI stays responsive.  Text over a platform's limit is threaded,
+/// truncated, or skipped as chosen in settings (`limits::over_limit_choice`).  Returns the history entry, or `None` when none of
+/// the platforms could be attempted.
+pub async fn publish(
+    state: &Arc<Mutex<posts::AppState>>,
+    text: &str,
+    platforms: &[String],
+    idempotency_key: &str,
+    options: &posts::PostOptions,
+    author: &str,
+) -> Option<history::HistoryEntry> {
+    let already_posted = history::posted_platforms(idempotency_key);
+    let selected = |name: &str| {
+        if !platforms.iter().any(|p| p == name) {
+            return false;
+        }
+        if already_posted.contains(name) {
+            warn!(
+                platform = name,
+                idempotency_key, "Already posted with this idempotency key; not resending"
+            );
+            return false;
+        }
+        // Posting it again would repeat the posts already made; it's finished with `resume_thread`
+        if threads::is_unfinished(name, idempotency_key) {
+            warn!(
+                platform = name,
+                idempotency_key, "Thread stopped partway with this idempotency key; resume it instead"
+            );
+            return false;
+        }
+        true
+    };
+
+    let (
+        twitter_authorized,
+        bluesky_session,
+        mastodon_authorized,
+        linkedin_authorized,
+        medium_authorized,
+        hashnode_authorized,
+        platform_limits,
+    ) = {
+        let state = state.lock().await;
+        let bluesky_session = bluesky_session(&state);
+        let platform_limits: HashMap<String, PlatformLimits> = platforms
+            .iter()
+            .map(|platform| (platform.clone(), state.limits(platform)))
+            .collect();
+        (
+            state.twitter_authorized,
+            bluesky_session,
+            state.mastodon_authorized,
+            state.linkedin_authorized,
+            state.medium_authorized,
+            state.hashnode_authorized,
+            platform_limits,
+        )
+    };
+
+    // Post only to platforms that are authorized and selected
+    let mut attempts: Vec<&str> = Vec::new();
+    #[cfg(feature = "twitter")]
+    if selected("Twitter") && twitter_authorized {
+        attempts.push("Twitter");
+    }
+    #[cfg(feature = "bluesky")]
+    if selected("Bluesky") && bluesky_session.is_some() {
+        attempts.push("Bluesky");
+    }
+    #[cfg(feature = "mastodon")]
+    if selected("Mastodon") && mastodon_authorized {
+        attempts.push("Mastodon");
+    }
+    #[cfg(feature = "mastodon")]
+    for platform in platforms {
+        let authorized = mastodon::find_account(platform).is_some_and(|account| account.access_token.is_some());
+        if authorized && selected(platform) {
+            attempts.push(platform);
+        }
+    }
+    #[cfg(feature = "linkedin")]
+    if selected("LinkedIn") && linkedin_authorized {
+        attempts.push("LinkedIn");
+    }
+    #[cfg(feature = "medium")]
+    if selected("Medium") && medium_authorized {
+        attempts.push("Medium");
+    }
+    #[cfg(feature = "hashnode")]
+    if selected("Hashnode") && hashnode_authorized {
+        attempts.push("Hashnode");
+    }
+    #[cfg(feature = "email")]
+    if selected("Email") && email::is_configured() {
+        attempts.push("Email");
+    }
+    #[cfg(feature = "demo")]
+    if selected("Demo") {
+        attempts.push("Demo");
+    }
+    #[cfg(feature = "plugins")]
+    for plugin in plugins::all() {
+        if selected(plugin.name) && plugin.load_token().is_some() {
+            attempts.push(plugin.name);
+        }
+    }
+
+    // Apply the platform's transforms and the user's pre-post hook, then decide how each platform gets text that is
+    // over its limit
+    let mut plans: Vec<(&str, Part, Plan)> = Vec::new();
+    let mut results = Vec::new();
+    let history = history::load_history();
+    let rules = profiles::load_config().content_rules;
+    for platform in attempts {
+        // A platform that is at its posting cap, or whose rate limit is used up for a while, gets the post later
+        let hold_until = match budget::check(&history, platform) {
+            budget::Status::Exceeded { until } => Some((until, "posting cap")),
+            budget::Status::Approaching { used, cap, window } => {
+                state.lock().await.toast(
+                    format!("{}: {} of {} posts this {} used", platform, used + 1, cap, window),
+                    false,
+                );
+                None
+            }
+            budget::Status::Within => None,
+        };
+        let hold_until = hold_until.or_else(|| {
+            ratelimit::exhausted_until(platform)
+                .filter(|reset| *reset - Utc::now() > ratelimit::MAX_WAIT)
+                .map(|reset| (reset, "rate limit"))
+        });
+        if let Some((until, reason)) = hold_until {
+            info!(platform, %until, reason, "Holding the post until the platform has room");
+            audit::record(
+                audit::Action::Post,
+                Some(platform),
+                &format!("Held until {} ({}), key {}", until, reason, idempotency_key),
+            );
+            scheduler::retry_at(text, &[platform.to_string()], until, idempotency_key, options, author);
+            state.lock().await.toast(
+                format!(
+                    "{} {} reached; will post at {}",
+                    platform,
+                    reason,
+                    timezone::format(until)
+                ),
+                false,
+            );
+            results.push(history::PlatformResult {
+                platform: platform.to_string(),
+                success: false,
+                post_id: None,
+                url: None,
+                error: Some(PostError::RateLimited),
+                thread_ids: Vec::new(),
+            });
+            continue;
+        }
+        let posts = thread_parts(platform, text, options);
+        #[cfg(feature = "scripting")]
+        let posts: Vec<Part> = posts
+            .into_iter()
+            .map(|post| Part {
+                text: scripting::before_post(platform, &post.text),
+                ..post
+            })
+            .collect();
+        if rules.enforcement == Enforcement::Block {
+            let all_text: Vec<&str> = posts.iter().map(|post| post.text.as_str()).collect();
+            if let Some(violation) = rules.violations(&all_text.join("\n")).first() {
+                warn!(platform, %violation, "Post breaks the profile's content rules; not posting");
+                state
+                    .lock()
+                    .await
+                    .toast(format!("Blocked on {}: {}", platform, violation), false);
+                // Recorded as a failure, so a scheduled post that's blocked shows up in history and alerts
+                results.push(history::PlatformResult {
+                    platform: platform.to_string(),
+                    success: false,
+                    post_id: None,
+                    url: None,
+                    error: Some(PostError::Rejected),
+                    thread_ids: Vec::new(),
+                });
+                continue;
+            }
+        }
+        let max_chars = platform_limits[platform].max_chars;
+        let Some((post, plan)) = plan_for(platform, posts, max_chars) else {
+            info!(platform, max_chars, "Text is over the limit; skipping platform");
+            state
+                .lock()
+                .await
+                .toast(format!("Skipped {}: text is too long", platform), false);
+            continue;
+        };
+        plans.push((platform, post, plan));
+    }
+    // Truncated posts go last so they can link to a full copy posted elsewhere.  The sort is stable, so accounts on
+    // the same platform stay next to each other, as they were attempted, and can be posted together.
+    plans.sort_by_key(|(_, _, plan)| matches!(plan, Plan::Truncate(_)));
+
+    if plans.is_empty() && results.is_empty() {
+        return None;
+    }
+
+    state.lock().await.post_progress = plans
+        .iter()
+        .map(|(platform, _, _)| (platform.to_string(), PostProgress::Pending))
+        .collect();
+
+    // Accounts on the same platform are posted to at the same time; platforms still go one after another so a
+    // truncated post can link to a full copy posted earlier
+    let mut full_post_url: Option<String> = None;
+    let mut plans = plans.into_iter().peekable();
+    while let Some(first) = plans.next() {
+        let mut batch = vec![first];
+        while let Some(next) = plans.next_if(|next| same_batch(&batch[0], next)) {
+            batch.push(next);
+        }
+        let posts = batch.iter().map(|(platform, post, plan)| {
+            post_to_platform(
+                state,
+                platform,
+                post,
+                plan,
+                full_post_url.as_deref(),
+                idempotency_key,
+                options,
+                &bluesky_session,
+            )
+        });
+        let batch_results = futures::future::join_all(posts).await;
+        for ((_, _, plan), result) in batch.iter().zip(&batch_results) {
+            if result.success && !matches!(plan, Plan::Truncate(_)) && full_post_url.is_none() {
+                full_post_url = result.url.clone();
+            }
+        }
+        results.extend(batch_results);
+    }
+
+    let images = options
+        .images
+        .iter()
+        .chain(options.thread.iter().flat_map(|segment| &segment.images))
+        .cloned()
+        .collect();
+    let mut entry = history::HistoryEntry::new(text, results, idempotency_key, author, images);
+    entry.expires_at = options
+        .expire_after_days
+        .map(|days| entry.posted_at + TimeDelta::days(days.into()));
+    history::record(&entry);
+    state.lock().await.history.push(entry.clone());
+    webhooks::notify(&entry, &entry.results).await;
+    Some(entry)
+}
+
+/// Text for `platform`: what was written for it in the composer's tabs, posted as written, or else the composed
+/// text with the platform's transforms applied.
+pub fn platform_text(platform: &str, text: &str, options: &posts::PostOptions) -> String {
+    match options.texts.get(platform).filter(|custom| !custom.trim().is_empty()) {
+        Some(custom) => custom.clone(),
+        None => transforms::apply(platform, text),
+    }
+}
+
+/// The posts written for `platform`, each with the platform's transforms: the composed text and its images, then
+/// the other posts of a thread written post by post.
+pub fn thread_parts(platform: &str, text: &str, options: &posts::PostOptions) -> Vec<Part> {
+    let first = Part {
+        text: platform_text(platform, text, options),
+        images: options.images.clone(),
+    };
+    let rest = options.thread.iter().map(|segment| Part {
+        text: transforms::apply(platform, &segment.text),
+        images: segment.images.clone(),
+    });
+    std::iter::once(first).chain(rest).collect()
+}
+
+/// How `posts` go to a platform whose limit is `max_chars`, with the post a single-post plan sends, or `None` when
+/// the text is too long and the platform is skipped.  A thread written post by post goes out as written where the
+/// platform has threads (any post too long for it is split further), and as one post, a blank line between the
+/// parts, where it doesn't.
+fn plan_for(platform: &str, mut posts: Vec<Part>, max_chars: usize) -> Option<(Part, Plan)> {
+    if posts.len() > 1 && !limits::supports_threads(platform) {
+        let text = posts
+            .iter()
+            .map(|post| post.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let images = posts.iter().flat_map(|post| post.images.clone()).collect();
+        posts = vec![Part { text, images }];
+    }
+    if posts.len() > 1 {
+        let first = posts[0].clone();
+        return Some((first, Plan::Thread(thread(posts, max_chars))));
+    }
+
+    let post = posts.remove(0);
+    if limits::count(&post.text) <= max_chars {
+        return Some((post, Plan::Full));
+    }
+    match limits::over_limit_choice(platform) {
+        OverLimit::Thread if limits::supports_threads(platform) => {
+            let parts = thread(vec![post.clone()], max_chars);
+            Some((post, Plan::Thread(parts)))
+        }
+        OverLimit::Thread | OverLimit::Truncate => Some((post, Plan::Truncate(max_chars))),
+        OverLimit::Skip => None,
+    }
+}
+
+/// The parts of a thread made of `posts`, each split further when it is too long, numbered in the configured style
+/// with room left for the counters.
+fn thread(posts: Vec<Part>, max_chars: usize) -> Vec<Part> {
+    let numbering = limits::thread_numbering();
+    let budget = max_chars.saturating_sub(numbering.room()).max(1);
+    let mut parts: Vec<Part> = posts.into_iter().flat_map(|post| split_part(post, budget)).collect();
+    let total = parts.len();
+    if total > 1 {
+        for (i, part) in parts.iter_mut().enumerate() {
+            part.text = numbering.apply(&part.text, i + 1, total);
+        }
+    }
+    parts
+}
+
+/// Splits a post that is too long into a chain of parts, the first keeping the images.
+fn split_part(post: Part, max_chars: usize) -> Vec<Part> {
+    if limits::count(&post.text) <= max_chars {
+        return vec![post];
+    }
+    let mut images = Some(post.images);
+    limits::split_thread(&post.text, max_chars)
+        .into_iter()
+        .map(|text| Part {
+            text,
+            images: images.take().unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// What one platform would get from a post, for comparing platforms before sending.
+pub struct Preview {
+    /// The posts as they'd go out: one, the parts of a thread, or one shortened.  Empty when the platform is skipped.
+    pub parts: Vec<String>,
+    pub truncated: bool,
+    pub hashtags: usize,
+    /// Whether the first link gets a preview card, made by the platform or attached by us.
+    pub link_card: bool,
+    pub images: usize,
+}
+
+/// Previews `text` on `platform`, before the pre-post hook (which may have side effects) runs.  A shortened post
+/// links to the full copy on another platform, which doesn't exist yet, so the preview leaves the link out.
+pub fn preview(platform: &str, text: &str, options: &posts::PostOptions, max_chars: usize) -> Preview {
+    let (parts, truncated) = match plan_for(platform, thread_parts(platform, text, options), max_chars) {
+        Some((post, Plan::Full)) => (vec![post], false),
+        Some((_, Plan::Thread(parts))) => (parts, false),
+        Some((post, Plan::Truncate(max_chars))) => (
+            vec![Part {
+                text: limits::truncate(&post.text, max_chars, None),
+                images: post.images,
+            }],
+            true,
+        ),
+        None => (Vec::new(), false),
+    };
+    let images: usize = parts.iter().map(|part| part.images.len()).sum();
+    let parts: Vec<String> = parts.into_iter().map(|part| part.text).collect();
+    let posted: String = parts.concat();
+    let hashtags = posted
+        .split_whitespace()
+        .filter(|word| word.len() > 1 && word.starts_with('#'))
+        .count();
+    let has_link = !links::find_urls(&posted).is_empty();
+    let bluesky = limits::base_platform(platform) == "Bluesky";
+    let link_card = has_link
+        && match limits::base_platform(platform) {
+            "Bluesky" => options.link_previews && images == 0,
+            "Twitter" | "Mastodon" => true,
+            _ => false,
+        };
+    Preview {
+        parts,
+        truncated,
+        hashtags,
+        link_card,
+        images: if bluesky { images } else { 0 },
+    }
+}
+
+/// Whether `next` can be posted alongside `first`: another account on the same platform, sent the same way.
+fn same_batch((first_platform, _, first_plan): &(&str, Part, Plan), (platform, _, plan): &(&str, Part, Plan)) -> bool {
+    limits::base_platform(first_platform) == limits::base_platform(platform)
+        && matches!(first_plan, Plan::Truncate(_)) == matches!(plan, Plan::Truncate(_))
+}
+
+/// Posts to one platform (or account), reporting progress as it goes.  `full_post_url` is where truncated text links
+/// to.
+#[allow(clippy::too_many_arguments)]
+async fn post_to_platform(
+    state: &Arc<Mutex<posts::AppState>>,
+    platform: &str,
+    post: &Part,
+    plan: &Plan,
+    full_post_url: Option<&str>,
+    idempotency_key: &str,
+    options: &posts::PostOptions,
+    bluesky_session: &Option<(String, String)>,
+) -> history::PlatformResult {
+    let parts = match plan {
+        Plan::Full => vec![post.clone()],
+        Plan::Thread(parts) => parts.clone(),
+        Plan::Truncate(max_chars) => vec![Part {
+            text: limits::truncate(&post.text, *max_chars, full_post_url),
+            images: post.images.clone(),
+        }],
+    };
+
+    if parts.iter().any(|part| !part.images.is_empty()) && platform != "Bluesky" {
+        warn!(
+            platform,
+            "Images can't be attached on {} yet; posting the text only", platform
+        );
+    }
+    let mut posted: Vec<Posted> = Vec::new();
+    let error = post_chain(platform, &parts, &mut posted, idempotency_key, options, bluesky_session).await;
+    let success = error.is_none();
+    let mut rolled_back = false;
+    if !success && !posted.is_empty() {
+        warn!(
+            platform,
+            posted = posted.len(),
+            parts = parts.len(),
+            "Thread stopped at post {} of {}",
+            posted.len() + 1,
+            parts.len()
+        );
+        // A thread stopped by the rate limit is finished once it resets rather than removed
+        if settings::get().roll_back_threads && error != Some(PostError::RateLimited) {
+            rolled_back = roll_back(platform, &posted).await;
+            if rolled_back {
+                posted.clear();
+            }
+        } else {
+            threads::record(threads::Unfinished {
+                platform: platform.to_string(),
+                idempotency_key: idempotency_key.to_string(),
+                parts: parts.clone(),
+                posted: posted.clone(),
+                options: options.clone(),
+                stopped_at: Utc::now(),
+            });
+        }
+    }
+    let Posted { id: post_id, url, .. } = posted.first().cloned().unwrap_or_default();
+    let thread_ids = thread_ids(&parts, &posted);
+
+    if success {
+        info!(platform, parts = parts.len(), "Posted to {} successfully", platform);
+    } else {
+        warn!(platform, error = ?error, "Failed to post to {}", platform);
+    }
+    let detail = match (&error, &url) {
+        (Some(err), _) if rolled_back => format!("Failed ({:?}), thread rolled back, key {}", err, idempotency_key),
+        (Some(err), _) => format!("Failed ({:?}), key {}", err, idempotency_key),
+        (None, Some(url)) => format!("Posted {}, key {}", url, idempotency_key),
+        (None, None) => format!("Posted, key {}", idempotency_key),
+    };
+    audit::record(audit::Action::Post, Some(platform), &detail);
+
+    {
+        let mut state = state.lock().await;
+        if success {
+            state.set_progress(platform, PostProgress::Succeeded);
+            state.toast(format!("Posted to {}", platform), true);
+        } else {
+            state.set_progress(platform, PostProgress::Failed);
+            let note = if rolled_back { "; the thread was removed" } else { "" };
+            state.toast(format!("Failed to post to {}{}", platform, note), false);
+        }
+    }
+    history::PlatformResult {
+        platform: platform.to_string(),
+        success,
+        post_id,
+        url,
+        error,
+        thread_ids,
+    }
+}
+
+/// Posts `parts` one after another as a reply chain, from the first one not in `posted` yet, adding each post made
+/// to `posted`.  The chain stops at the first part that fails, returning why.
+async fn post_chain(
+    platform: &str,
+    parts: &[Part],
+    posted: &mut Vec<Posted>,
+    idempotency_key: &str,
+    options: &posts::PostOptions,
+    bluesky_session: &Option<(String, String)>,
+) -> Option<PostError> {
+    for (i, part) in parts.iter().enumerate().skip(posted.len()) {
+        if let Err(err) = pace(platform, i).await {
+            return Some(err);
+        }
+        let part_key = match i {
+            0 => idempotency_key.to_string(),
+            _ => format!("{}-{}", idempotency_key, i),
+        };
+        let part_options = posts::PostOptions {
+            images: part.images.clone(),
+            ..options.clone()
+        };
+        let reply = posted.first().zip(posted.last());
+        match post_part(platform, &part.text, reply, &part_key, &part_options, bluesky_session).await {
+            Ok(post) => posted.push(post),
+            Err(err) => return Some(err),
+        }
+    }
+    None
+}
+
+/// Ids of the posts made of a thread, for history.  Empty for a single post.
+fn thread_ids(parts: &[Part], posted: &[Posted]) -> Vec<String> {
+    match parts.len() {
+        0 | 1 => Vec::new(),
+        _ => posted.iter().filter_map(|post| post.id.clone()).collect(),
+    }
+}
+
+fn bluesky_session(state: &posts::AppState) -> Option<(String, String)> {
+    match (state.bluesky_authorized, &state.bluesky_token, &state.did) {
+        (true, Some(token), Some(did)) => Some((token.clone(), did.clone())),
+        _ => None,
+    }
+}
+
+/// Finishes a thread that stopped partway, from the first post that wasn't made, replying to the last one that was.
+/// The platform's result in history is updated with the outcome, and the thread is kept for another try when it
+/// stops again.  Returns whether the thread is complete.
+pub async fn resume_thread(state: &Arc<Mutex<posts::AppState>>, mut thread: threads::Unfinished) -> bool {
+    let platform = thread.platform.clone();
+    let bluesky_session = bluesky_session(&state.lock().await);
+    info!(
+        platform = %platform,
+        from = thread.next_post(),
+        parts = thread.parts.len(),
+        "Resuming thread"
+    );
+    let error = post_chain(
+        &platform,
+        &thread.parts,
+        &mut thread.posted,
+        &thread.idempotency_key,
+        &thread.options,
+        &bluesky_session,
+    )
+    .await;
+    let success = error.is_none();
+    let Posted { id: post_id, url, .. } = thread.posted.first().cloned().unwrap_or_default();
+    let result = history::PlatformResult {
+        platform: platform.clone(),
+        success,
+        post_id,
+        url,
+        error,
+        thread_ids: thread_ids(&thread.parts, &thread.posted),
+    };
+
+    // Replace the failed result in the entry the thread was first posted under
+    let mut entries = history::load_history();
+    let entry = entries
+        .iter_mut()
+        .rev()
+        .find(|entry| entry.idempotency_key.as_deref() == Some(thread.idempotency_key.as_str()));
+    if let Some(entry) = entry {
+        entry.results.retain(|kept| kept.platform != platform);
+        entry.results.push(result.clone());
+        history::save_history(&entries);
+        webhooks::notify(entry, &[result]).await;
+    }
+
+    let detail = match &error {
+        Some(err) => format!(
+            "Resumed thread failed ({:?}) at post {}, key {}",
+            err,
+            thread.next_post(),
+            thread.idempotency_key
+        ),
+        None => format!("Finished thread, key {}", thread.idempotency_key),
+    };
+    audit::record(audit::Action::Post, Some(&platform), &detail);
+
+    let mut state = state.lock().await;
+    state.history = entries;
+    if success {
+        threads::remove(&platform, &thread.idempotency_key);
+        state.toast(format!("Finished the thread on {}", platform), true);
+    } else {
+        warn!(platform = %platform, error = ?error, "Thread stopped again at post {}", thread.next_post());
+        state.toast(
+            format!("Thread on {} stopped again at post {}", platform, thread.next_post()),
+            false,
+        );
+        thread.stopped_at = Utc::now();
+        threads::record(thread);
+    }
+    success
+}
+
+/// Waits before post `i` of a thread: until the rate limit resets when the budget is used up, and on Twitter a few
+/// seconds between posts anyway.  A reset further off than `ratelimit::MAX_WAIT` isn't waited for; the thread stops
+/// there as rate limited, to be finished later.
+#[cfg_attr(not(feature = "twitter"), allow(unused_variables))]
+async fn pace(platform: &str, i: usize) -> Result<(), PostError> {
+    if let Some(reset) = ratelimit::exhausted_until(platform) {
+        let wait = reset - Utc::now();
+        if wait > ratelimit::MAX_WAIT {
+            info!(platform, %reset, "Rate limit used up; stopping the thread until it resets");
+            return Err(PostError::RateLimited);
+        }
+        info!(
+            platform,
+            wait_secs = wait.num_seconds(),
+            "Rate limit used up; waiting for it to reset"
+        );
+        tokio::time::sleep(wait.to_std().unwrap_or_default()).await;
+    }
+    #[cfg(feature = "twitter")]
+    if i > 0 && platform == "Twitter" {
+        tokio::time::sleep(twitter::THREAD_POST_INTERVAL).await;
+    }
+    Ok(())
+}
+
+/// Deletes the posts already made of a thread that failed partway, newest first.  Returns whether they're all gone.
+async fn roll_back(platform: &str, posted: &[Posted]) -> bool {
+    let mut all_deleted = true;
+    for post_id in posted.iter().rev().filter_map(|post| post.id.as_deref()) {
+        all_deleted &= delete_post(platform, post_id).await;
+    }
+    if all_deleted {
+        audit::record(
+            audit::Action::Delete,
+            Some(platform),
+            &format!("Rolled back a failed thread ({} posts)", posted.len()),
+        );
+    }
+    all_deleted
+}
+
+/// Whether posts made on `platform` can be deleted from the app.
+pub fn can_delete(platform: &str) -> bool {
+    match limits::base_platform(platform) {
+        #[cfg(feature = "twitter")]
+        "Twitter" => true,
+        #[cfg(feature = "bluesky")]
+        "Bluesky" => true,
+        #[cfg(feature = "mastodon")]
+        "Mastodon" => true,
+        _ => false,
+    }
+}
+
+/// Deletes what a successful post made on its platform: every post of a thread, newest first, or the single post.
+/// Returns whether it's all gone.
+pub async fn delete_published(result: &history::PlatformResult) -> bool {
+    let ids: Vec<&String> = if result.thread_ids.is_empty() {
+        result.post_id.iter().collect()
+    } else {
+        result.thread_ids.iter().rev().collect()
+    };
+    let mut all_deleted = true;
+    for post_id in ids {
+        all_deleted &= delete_post(&result.platform, post_id).await;
+    }
+    all_deleted
+}
+
+#[cfg_attr(
+    not(all(feature = "twitter", feature = "bluesky", feature = "mastodon")),
+    allow(unused_variables)
+)]
+async fn delete_post(platform: &str, post_id: &str) -> bool {
+    match platform {
+        #[cfg(feature = "twitter")]
+        "Twitter" => match twitter::load_bearer_token() {
+            Some(token) => twitter::delete_tweet(&token, post_id).await,
+            None => false,
+        },
+        #[cfg(feature = "bluesky")]
+        "Bluesky" => match bluesky::load_tokens() {
+            Some(token_data) => bluesky::delete_post(&token_data.access_jwt, post_id).await,
+            None => false,
+        },
+        #[cfg(feature = "mastodon")]
+        "Mastodon" => match mastodon::load_tokens() {
+            Some(token_data) => {
+                mastodon::delete_status(&mastodon::api_base_url(), &token_data.access_token, post_id).await
+            }
+            None => false,
+        },
+        #[cfg(feature = "mastodon")]
+        name if name.starts_with(mastodon::ACCOUNT_PREFIX) => {
+            match mastodon::find_account(name).and_then(|account| Some((account.base_url(), account.access_token?))) {
+                Some((base_url, token)) => mastodon::delete_status(&base_url, &token, post_id).await,
+                None => false,
+            }
+        }
+        _ => {
+            warn!(platform, "Deleting posts on {} isn't supported", platform);
+            false
+        }
+    }
+}
+
+/// How the text is sent to one platform.
+enum Plan {
+    Full,
+    Thread(Vec<Part>),
+    Truncate(usize), // Shortened to this many characters
+}
+
+/// One post as sent to a platform: a whole post, or one part of a thread.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Part {
+    pub text: String,
+    pub images: Vec<ImageAttachment>,
+}
+
+/// A post made on a platform, as the next part of a thread refers to it.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Posted {
+    pub id: Option<String>,
+    pub url: Option<String>,
+    /// Content hash of a Bluesky record, which replies need along with its URI.
+    pub cid: Option<String>,
+}
+
+impl Posted {
+    fn new((id, url): (Option<String>, Option<String>)) -> Self {
+        Posted { id, url, cid: None }
+    }
+}
+
+/// Answers the post `status_id` on `platform`, e.g. a mention from the inbox, with `visibility`.  Unlike a
+/// cross-post it goes to that one platform as is and isn't kept in history.  Returns the reply's URL if the platform
+/// gives one.
+pub async fn reply(
+    platform: &str,
+    text: &str,
+    status_id: &str,
+    visibility: posts::Visibility,
+) -> Result<Option<String>, PostError> {
+    let parent = Posted {
+        id: Some(status_id.to_string()),
+        ..Default::default()
+    };
+    let options = posts::PostOptions {
+        visibility,
+        ..Default::default()
+    };
+    let posted = post_part(
+        platform,
+        text,
+        Some((&parent, &parent)),
+        &history::new_idempotency_key(),
+        &options,
+        &None,
+    )
+    .await?;
+    info!(platform, in_reply_to = status_id, "Replied from the inbox");
+    Ok(posted.url)
+}
+
+/// Posts one piece of text, returning the post id and its public URL when the platform provides them.  `reply`, the
+/// first post of the thread and the one to answer, is only honored on platforms that support threads.
+#[cfg_attr(
+    not(all(feature = "twitter", feature = "bluesky", feature = "mastodon")),
+    allow(unused_variables)
+)]
+async fn post_part(
+    platform: &str,
+    text: &str,
+    reply: Option<(&Posted, &Posted)>,
+    idempotency_key: &str,
+    options: &posts::PostOptions,
+    bluesky_session: &Option<(String, String)>,
+) -> Result<Posted, PostError> {
+    let reply_to = reply.and_then(|(_, parent)| parent.id.as_deref());
+    match platform {
+        #[cfg(feature = "twitter")]
+        "Twitter" => {
+            let bearer_token = twitter::load_bearer_token().ok_or(PostError::Unauthorized)?;
+            let tweet_id = twitter::post_to_twitter(&bearer_token, text, reply_to, options.reply_settings).await?;
+            let url = twitter::post_url(&tweet_id);
+            Ok(Posted::new((Some(tweet_id), Some(url))))
+        }
+        #[cfg(feature = "bluesky")]
+        "Bluesky" => {
+            let (token, user_did) = bluesky_session.as_ref().ok_or(PostError::Unauthorized)?;
+            let reply = reply.and_then(|(root, parent)| {
+                Some(bluesky::ReplyRef {
+                    root: bluesky::StrongRef {
+                        uri: root.id.clone()?,
+                        cid: root.cid.clone()?,
+                    },
+                    parent: bluesky::StrongRef {
+                        uri: parent.id.clone()?,
+                        cid: parent.cid.clone()?,
+                    },
+                })
+            });
+            let created = bluesky::post_to_bluesky(token, text, user_did, reply.as_ref(), options).await?;
+            Ok(created.map_or_else(Posted::default, |created| Posted {
+                url: bluesky::post_url(&created.uri),
+                id: Some(created.uri),
+                cid: Some(created.cid),
+            }))
+        }
+        #[cfg(feature = "mastodon")]
+        "Mastodon" => {
+            let token_data = mastodon::load_tokens().ok_or(PostError::Unauthorized)?;
+            let status = mastodon::post_to_mastodon(
+                &mastodon::api_base_url(),
+                &token_data.access_token,
+                text,
+                reply_to,
+                idempotency_key,
+                options,
+            )
+            .await?;
+            Ok(Posted::new((Some(status.id), status.url)))
+        }
+        #[cfg(feature = "mastodon")]
+        name if name.starts_with(mastodon::ACCOUNT_PREFIX) => {
+            let account = mastodon::find_account(name).ok_or(PostError::Unauthorized)?;
+            let token = account.access_token.as_deref().ok_or(PostError::Unauthorized)?;
+            let status =
+                mastodon::post_to_mastodon(&account.base_url(), token, text, reply_to, idempotency_key, options)
+                    .await?;
+            Ok(Posted::new((Some(status.id), status.url)))
+        }
+        #[cfg(feature = "linkedin")]
+        "LinkedIn" => {
+            let linkedin_token = linkedin::load_bearer_token().ok_or(PostError::Unauthorized)?;
+            if linkedin::post_to_linkedin(&linkedin_token, text).await {
+                Ok(Posted::default())
+            } else {
+                Err(PostError::Rejected)
+            }
+        }
+        #[cfg(feature = "medium")]
+        "Medium" => {
+            let token = medium::load_token().ok_or(PostError::Unauthorized)?;
+            let (id, url) = medium::post_to_medium(&token, text).await?;
+            Ok(Posted::new((Some(id), Some(url))))
+        }
+        #[cfg(feature = "hashnode")]
+        "Hashnode" => {
+            let token = hashnode::load_token().ok_or(PostError::Unauthorized)?;
+            let (id, url) = hashnode::post_to_hashnode(&token, text).await?;
+            Ok(Posted::new((Some(id), Some(url))))
+        }
+        #[cfg(feature = "email")]
+        "Email" => Ok(Posted::new((email::send_post(text).await?, None))),
+        #[cfg(feature = "demo")]
+        "Demo" => Ok(Posted::new((Some(demo::post_to_demo(text).await?), None))),
+        #[cfg(feature = "plugins")]
+        name => match plugins::find(name) {
+            Some(plugin) => plugin.post(text).await.map(Posted::new),
+            None => Err(PostError::Rejected),
+        },
+        #[cfg(not(feature = "plugins"))]
+        _ => Err(PostError::Rejected),
+    }
+}
@@ -0,0 +1,84 @@
+use chrono::{DateTime, Datelike, NaiveTime, TimeDelta, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use crate::{audit, scheduler, shared, timezone};
+
+const QUEUES_FILE: &str = "queues.json";
+
+/// How far ahead to look for a free slot.
+const SLOT_SEARCH_DAYS: i64 = 56;
+
+/// A weekly posting time, in the configured time zone.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Slot {
+    pub weekday: Weekday,
+    pub time: NaiveTime,
+}
+
+/// A named category of posts ("product updates", "blog reposts") with its own posting slots and platforms.  Posts
+/// added to a queue take its next free slot.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Queue {
+    pub name: String,
+    pub slots: Vec<Slot>,
+    /// Platforms checked when composing for this queue.
+    pub platforms: Vec<String>,
+    /// Its posts are held, even when due, until the queue is resumed.
+    #[serde(default)]
+    pub paused: bool,
+}
+
+pub fn load_queues() -> Vec<Queue> {
+    let path = shared::path(QUEUES_FILE);
+    if path.exists() {
+        let json = fs::read_to_string(path).expect("Failed to read queues file");
+        serde_json::from_str(&json).unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+pub fn save_queues(queues: &[Queue]) {
+    let json = serde_json::to_string_pretty(queues).expect("Failed to serialize queues");
+    fs::write(shared::path(QUEUES_FILE), json).expect("Failed to write queues file");
+}
+
+/// Pauses or resumes the queue named `name`.  Returns false when there's no such queue.
+pub fn set_paused(name: &str, paused: bool) -> bool {
+    let mut queues = load_queues();
+    let Some(queue) = queues.iter_mut().find(|queue| queue.name == name) else {
+        return false;
+    };
+    if queue.paused != paused {
+        queue.paused = paused;
+        save_queues(&queues);
+        let action = if paused { "Paused" } else { "Resumed" };
+        audit::record(audit::Action::Settings, None, &format!("{} queue \"{}\"", action, name));
+        tracing::info!(queue = name, paused, "Changed queue pause");
+    }
+    true
+}
+
+/// The earliest slot of `queue` that is in the future and not already taken by a post in that queue.
+pub fn next_free_slot(queue: &Queue) -> Option<DateTime<Utc>> {
+    let now = Utc::now();
+    let taken: Vec<DateTime<Utc>> = scheduler::load_schedule()
+        .into_iter()
+        .filter(|post| post.queue.as_deref() == Some(queue.name.as_str()))
+        .map(|post| post.scheduled_at)
+        .collect();
+
+    let today = timezone::to_wall_clock(now).date();
+    let mut slots = queue.slots.clone();
+    slots.sort_by_key(|slot| slot.time);
+    (0..SLOT_SEARCH_DAYS)
+        .map(|offset| today + TimeDelta::days(offset))
+        .flat_map(|date| {
+            slots
+                .iter()
+                .filter(move |slot| slot.weekday == date.weekday())
+                .filter_map(move |slot| timezone::from_wall_clock(date.and_time(slot.time)).ok())
+        })
+        .find(|time| *time > now && !taken.contains(time))
+}
@@ -0,0 +1,81 @@
+use chrono::{DateTime, TimeZone, Utc};
+use reqwest::header::HeaderMap;
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use tracing::debug;
+
+/// Waits shorter than this are sat out before posting; longer ones hold the post until the limit resets.
+pub const MAX_WAIT: chrono::Duration = chrono::Duration::minutes(2);
+
+static BUDGETS: OnceLock<RwLock<HashMap<String, RateLimit>>> = OnceLock::new();
+
+/// Request budget a platform reported in its latest response.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RateLimit {
+    pub limit: Option<u64>,
+    pub remaining: u64,
+    pub reset: DateTime<Utc>,
+}
+
+fn budgets() -> &'static RwLock<HashMap<String, RateLimit>> {
+    BUDGETS.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Stores the budget from a response's `X-RateLimit-*` headers (Mastodon, with an ISO 8601 reset time) or
+/// `x-rate-limit-*` headers (Twitter, with the reset in epoch seconds).  Responses without them are ignored.
+pub fn record(platform: &str, headers: &HeaderMap) {
+    let header = |names: [&str; 2]| {
+        names
+            .iter()
+            .find_map(|name| headers.get(*name))
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+    };
+    let Some(remaining) = header(["x-ratelimit-remaining", "x-rate-limit-remaining"]).and_then(|v| v.parse().ok())
+    else {
+        return;
+    };
+    let Some(reset) = header(["x-ratelimit-reset", "x-rate-limit-reset"]).and_then(parse_reset) else {
+        return;
+    };
+    let limit = header(["x-ratelimit-limit", "x-rate-limit-limit"]).and_then(|v| v.parse().ok());
+
+    debug!(platform, remaining, %reset, "Rate limit budget");
+    budgets().write().unwrap().insert(
+        platform.to_string(),
+        RateLimit {
+            limit,
+            remaining,
+            reset,
+        },
+    );
+}
+
+fn parse_reset(value: &str) -> Option<DateTime<Utc>> {
+    match value.parse::<i64>() {
+        Ok(seconds) => Utc.timestamp_opt(seconds, 0).single(),
+        Err(_) => DateTime::parse_from_rfc3339(value)
+            .ok()
+            .map(|time| time.with_timezone(&Utc)),
+    }
+}
+
+/// The budgets that haven't reset yet, by platform.
+pub fn all() -> Vec<(String, RateLimit)> {
+    let now = Utc::now();
+    let mut all: Vec<(String, RateLimit)> = budgets()
+        .read()
+        .unwrap()
+        .iter()
+        .filter(|(_, limit)| limit.reset > now)
+        .map(|(platform, limit)| (platform.clone(), *limit))
+        .collect();
+    all.sort_by(|a, b| a.0.cmp(&b.0));
+    all
+}
+
+/// When `platform` has used up its budget, the time it resets.
+pub fn exhausted_until(platform: &str) -> Option<DateTime<Utc>> {
+    let limit = *budgets().read().unwrap().get(platform)?;
+    (limit.remaining == 0 && limit.reset > Utc::now()).then_some(limit.reset)
+}
@@ -0,0 +1,29 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+const RECOVERY_FILE: &str = "compose_recovery.txt";
+
+/// How often the composer text is written to the recovery file.
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Writes the unsent composer text to the recovery file, or removes the file once there is nothing left to recover.
+pub fn save(text: &str) {
+    if text.trim().is_empty() {
+        if Path::new(RECOVERY_FILE).exists() {
+            fs::remove_file(RECOVERY_FILE).expect("Failed to remove recovery file");
+        }
+    } else {
+        fs::write(RECOVERY_FILE, text).expect("Failed to write recovery file");
+    }
+}
+
+/// Returns text left unsent by the previous session, if any.
+pub fn load() -> Option<String> {
+    let text = fs::read_to_string(RECOVERY_FILE).ok()?;
+    if text.trim().is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
@@ -0,0 +1,191 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+use crate::engagement::Sample;
+use crate::history::HistoryEntry;
+use crate::timezone;
+
+/// Longest excerpt of the top post shown in the report.
+const EXCERPT_CHARS: usize = 80;
+
+/// Stretch of time a report covers, ending now.
+#[derive(Clone, Copy, PartialEq, Debug, Default, clap::ValueEnum)]
+pub enum Period {
+    #[default]
+    Week,
+    Month,
+}
+
+impl Period {
+    pub const ALL: [Period; 2] = [Period::Week, Period::Month];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Period::Week => "Last 7 days",
+            Period::Month => "Last 30 days",
+        }
+    }
+
+    fn days(self) -> i64 {
+        match self {
+            Period::Week => 7,
+            Period::Month => 30,
+        }
+    }
+}
+
+/// How one platform did over the period.
+pub struct PlatformSummary {
+    pub platform: String,
+    pub sent: usize,
+    pub failed: usize,
+    /// Latest sampled likes and reposts, summed over the posts; see `engagement::track`.
+    pub likes: u64,
+    pub reposts: u64,
+}
+
+impl PlatformSummary {
+    /// Share of the attempts that failed, from 0 to 1.
+    pub fn failure_rate(&self) -> f64 {
+        let attempts = self.sent + self.failed;
+        if attempts == 0 {
+            0.0
+        } else {
+            self.failed as f64 / attempts as f64
+        }
+    }
+}
+
+/// The post with the most likes and reposts over all platforms.
+pub struct TopPost {
+    pub text: String,
+    pub posted_at: DateTime<Utc>,
+    pub engagement: u64,
+    pub url: Option<String>,
+}
+
+impl TopPost {
+    /// The first line of the post, shortened.
+    pub fn excerpt(&self) -> String {
+        let first_line = self.text.lines().next().unwrap_or_default();
+        let mut excerpt: String = first_line.chars().take(EXCERPT_CHARS).collect();
+        if first_line.chars().count() > EXCERPT_CHARS || self.text.lines().nth(1).is_some() {
+            excerpt.push('…');
+        }
+        excerpt
+    }
+}
+
+pub struct Report {
+    pub period: Period,
+    pub since: DateTime<Utc>,
+    /// Posts sent to at least one platform.
+    pub posts: usize,
+    /// Sorted by platform name.
+    pub platforms: Vec<PlatformSummary>,
+    pub top_post: Option<TopPost>,
+}
+
+/// Summarizes the posts in `history` from the last `period`, with the engagement last sampled for each.
+pub fn build(history: &[HistoryEntry], samples: &HashMap<String, Vec<Sample>>, period: Period) -> Report {
+    let since = Utc::now() - chrono::Duration::days(period.days());
+    let mut platforms: HashMap<String, PlatformSummary> = HashMap::new();
+    let mut posts = 0;
+    let mut top_post: Option<TopPost> = None;
+
+    for entry in history.iter().filter(|entry| entry.posted_at >= since) {
+        if entry.results.iter().any(|result| result.success) {
+            posts += 1;
+        }
+        // Samples are oldest first, so the last one per platform is the latest
+        let mut latest: HashMap<&str, &Sample> = HashMap::new();
+        for sample in samples.get(&entry.id).into_iter().flatten() {
+            latest.insert(sample.platform.as_str(), sample);
+        }
+
+        for result in &entry.results {
+            let summary = platforms
+                .entry(result.platform.clone())
+                .or_insert_with(|| PlatformSummary {
+                    platform: result.platform.clone(),
+                    sent: 0,
+                    failed: 0,
+                    likes: 0,
+                    reposts: 0,
+                });
+            if result.success {
+                summary.sent += 1;
+            } else {
+                summary.failed += 1;
+            }
+            if let Some(sample) = latest.get(result.platform.as_str()) {
+                summary.likes += sample.likes.unwrap_or(0);
+                summary.reposts += sample.reposts.unwrap_or(0);
+            }
+        }
+
+        let engagement: u64 = latest
+            .values()
+            .map(|sample| sample.likes.unwrap_or(0) + sample.reposts.unwrap_or(0))
+            .sum();
+        if engagement > 0 && top_post.as_ref().map_or(true, |top| engagement > top.engagement) {
+            top_post = Some(TopPost {
+                text: entry.text.clone(),
+                posted_at: entry.posted_at,
+                engagement,
+                url: entry.results.iter().find_map(|result| result.url.clone()),
+            });
+        }
+    }
+
+    let mut platforms: Vec<PlatformSummary> = platforms.into_values().collect();
+    platforms.sort_by(|a, b| a.platform.cmp(&b.platform));
+    Report {
+        period,
+        since,
+        posts,
+        platforms,
+        top_post,
+    }
+}
+
+impl Report {
+    /// The report as Markdown, to paste into notes or save with `multique report`.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!(
+            "# Multique report: {}\n\nSince {}: {} post(s) sent.\n\n",
+            self.period.label().to_lowercase(),
+            timezone::format(self.since),
+            self.posts
+        );
+        if !self.platforms.is_empty() {
+            markdown.push_str("| Platform | Sent | Failed | Failure rate | Likes | Reposts |\n");
+            markdown.push_str("|---|---:|---:|---:|---:|---:|\n");
+            for summary in &self.platforms {
+                markdown.push_str(&format!(
+                    "| {} | {} | {} | {:.0}% | {} | {} |\n",
+                    summary.platform,
+                    summary.sent,
+                    summary.failed,
+                    summary.failure_rate() * 100.0,
+                    summary.likes,
+                    summary.reposts
+                ));
+            }
+            markdown.push('\n');
+        }
+        if let Some(top) = &self.top_post {
+            markdown.push_str(&format!(
+                "Top post ({} likes and reposts, {}): {}",
+                top.engagement,
+                timezone::format(top.posted_at),
+                top.excerpt()
+            ));
+            if let Some(url) = &top.url {
+                markdown.push_str(&format!(" <{}>", url));
+            }
+            markdown.push('\n');
+        }
+        markdown
+    }
+}
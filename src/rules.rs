@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+/// What happens when a post breaks its profile's content rules.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum Enforcement {
+    /// Listed with the other warnings before posting, and can be posted anyway.
+    #[default]
+    Warn,
+    /// Not posted until it follows them, wherever it's posted from.
+    Block,
+}
+
+impl Enforcement {
+    pub const ALL: [Enforcement; 2] = [Enforcement::Warn, Enforcement::Block];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Enforcement::Warn => "Warn",
+            Enforcement::Block => "Block",
+        }
+    }
+}
+
+/// Words a profile's posts must not use and disclaimers they must include, as a team sets them for an account.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContentRules {
+    /// Words and phrases, matched ignoring case and only as whole words.
+    pub banned: Vec<String>,
+    /// Text every post must contain somewhere, ignoring case.
+    pub required: Vec<String>,
+    pub enforcement: Enforcement,
+}
+
+impl ContentRules {
+    /// How `text` breaks the rules, one message per rule.
+    pub fn violations(&self, text: &str) -> Vec<String> {
+        let lower = text.to_lowercase();
+        let banned = self
+            .banned
+            .iter()
+            .filter(|phrase| contains_words(&lower, &phrase.to_lowercase()))
+            .map(|phrase| format!("Uses \"{}\", which isn't allowed", phrase));
+        let missing = self
+            .required
+            .iter()
+            .filter(|disclaimer| !lower.contains(&disclaimer.to_lowercase()))
+            .map(|disclaimer| format!("Is missing the required \"{}\"", disclaimer));
+        banned.chain(missing).collect()
+    }
+}
+
+/// Whether `phrase` appears in `text` with no letter or digit right before or after it, so a banned "ass" doesn't
+/// catch "class".
+fn contains_words(text: &str, phrase: &str) -> bool {
+    if phrase.is_empty() {
+        return false;
+    }
+    let is_word = |c: char| c.is_alphanumeric();
+    text.match_indices(phrase).any(|(start, _)| {
+        let end = start + phrase.len();
+        !text[..start].chars().next_back().is_some_and(is_word) && !text[end..].chars().next().is_some_and(is_word)
+    })
+}
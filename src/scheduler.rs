@@ -0,0 +1,441 @@
+use chrono::{DateTime, Months, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::audit;
+use crate::cron::CronSchedule;
+use crate::posts::PostOptions;
+use crate::shared::{self, current_user};
+use crate::{alerts, history, posts, publish, queues, settings, timezone};
+
+const SCHEDULE_FILE: &str = "schedule.json";
+const PAUSE_FILE: &str = "schedule_pause.json";
+const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+/// How long a pass of the scheduler may take (posting threads and waiting out rate limits included) before it is
+/// considered stuck.
+pub const STALLED_AFTER: TimeDelta = TimeDelta::minutes(15);
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl Frequency {
+    pub const ALL: [Frequency; 3] = [Frequency::Daily, Frequency::Weekly, Frequency::Monthly];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Frequency::Daily => "Daily",
+            Frequency::Weekly => "Weekly",
+            Frequency::Monthly => "Monthly",
+        }
+    }
+}
+
+/// How a scheduled post repeats.  Occurrences keep the same wall-clock time in the configured time zone, so "every
+/// Monday 9am" stays at 9am across daylight saving changes.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    /// Cron expression (see the `cron` module) giving the occurrences instead of `frequency`.
+    #[serde(default)]
+    pub cron: Option<String>,
+    /// No occurrences after this time.
+    pub until: Option<DateTime<Utc>>,
+    /// Occurrences left, including the scheduled one.  `None` repeats until `until`, or forever.
+    pub remaining: Option<u32>,
+}
+
+/// Sign-off state of a post scheduled while approval is required.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Approval {
+    pub requested_by: String,
+    pub requested_at: DateTime<Utc>,
+    pub approved_by: Option<String>,
+    pub approved_at: Option<DateTime<Utc>>,
+}
+
+impl Approval {
+    pub fn is_approved(&self) -> bool {
+        self.approved_at.is_some()
+    }
+
+    /// When the requester may approve their own post, after the configured cooldown.
+    pub fn self_approval_at(&self) -> DateTime<Utc> {
+        self.requested_at + TimeDelta::minutes(settings::get().approval_cooldown_mins as i64)
+    }
+}
+
+/// Who stopped scheduled posts from going out, and since when.  Kept next to the schedule, so every instance sharing
+/// it holds off.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Pause {
+    pub paused_by: String,
+    pub paused_at: DateTime<Utc>,
+}
+
+/// A post waiting for its time to come.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScheduledPost {
+    pub id: String,
+    pub text: String,
+    pub platforms: Vec<String>,
+    /// When to post, always stored in UTC; see the `timezone` module for display.
+    pub scheduled_at: DateTime<Utc>,
+    pub idempotency_key: String,
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+    /// Queue the post was added to, if any; see the `queues` module.
+    #[serde(default)]
+    pub queue: Option<String>,
+    /// Present when the post needs sign-off; the scheduler holds it until approved.
+    #[serde(default)]
+    pub approval: Option<Approval>,
+    /// Who scheduled the post, credited in history once it is published.
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub options: PostOptions,
+}
+
+impl ScheduledPost {
+    /// The next occurrence of a recurring post after this one, or `None` when the recurrence has ended.
+    fn next(&self) -> Option<ScheduledPost> {
+        let recurrence = self.recurrence.as_ref()?;
+        let remaining = match recurrence.remaining {
+            Some(remaining) if remaining <= 1 => return None,
+            remaining => remaining.map(|r| r - 1),
+        };
+
+        let wall_clock = timezone::to_wall_clock(self.scheduled_at);
+        let next_wall_clock = match &recurrence.cron {
+            Some(expression) => match CronSchedule::parse(expression) {
+                Ok(cron) => cron.next_after(wall_clock)?,
+                Err(err) => {
+                    warn!(id = %self.id, cron = %expression, error = %err, "Invalid cron expression; not repeating");
+                    return None;
+                }
+            },
+            None => match recurrence.frequency {
+                Frequency::Daily => wall_clock + TimeDelta::days(1),
+                Frequency::Weekly => wall_clock + TimeDelta::weeks(1),
+                Frequency::Monthly => wall_clock.checked_add_months(Months::new(1))?,
+            },
+        };
+        // A wall-clock time skipped by a daylight saving change happens an hour later that day
+        let scheduled_at = timezone::from_wall_clock(next_wall_clock)
+            .or_else(|_| timezone::from_wall_clock(next_wall_clock + TimeDelta::hours(1)))
+            .ok()?;
+        if recurrence.until.is_some_and(|until| scheduled_at > until) {
+            return None;
+        }
+
+        Some(ScheduledPost {
+            id: history::new_idempotency_key(),
+            text: self.text.clone(),
+            platforms: self.platforms.clone(),
+            scheduled_at,
+            idempotency_key: history::new_idempotency_key(),
+            recurrence: Some(Recurrence {
+                remaining,
+                ..recurrence.clone()
+            }),
+            queue: self.queue.clone(),
+            // Approving a recurring post approves the whole series
+            approval: self.approval.clone(),
+            author: self.author.clone(),
+            options: self.options.clone(),
+        })
+    }
+}
+
+pub fn load_schedule() -> Vec<ScheduledPost> {
+    let path = shared::path(SCHEDULE_FILE);
+    if path.exists() {
+        let json = fs::read_to_string(path).expect("Failed to read schedule file");
+        serde_json::from_str(&json).unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+pub fn save_schedule(posts: &[ScheduledPost]) {
+    let json = serde_json::to_string_pretty(posts).expect("Failed to serialize schedule");
+    fs::write(shared::path(SCHEDULE_FILE), json).expect("Failed to write schedule file");
+}
+
+/// The pause on all scheduled posts, if they're paused.
+pub fn load_pause() -> Option<Pause> {
+    let json = fs::read_to_string(shared::path(PAUSE_FILE)).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// Holds every scheduled post, queues included, until `resume`.  Due posts stay in the schedule meanwhile.  Returns
+/// the pause now in place, which is the earlier one when already paused.
+pub fn pause() -> Pause {
+    if let Some(pause) = load_pause() {
+        return pause;
+    }
+    let pause = Pause {
+        paused_by: current_user(),
+        paused_at: Utc::now(),
+    };
+    let json = serde_json::to_string_pretty(&pause).expect("Failed to serialize pause");
+    fs::write(shared::path(PAUSE_FILE), json).expect("Failed to write pause file");
+    audit::record(audit::Action::Settings, None, "Paused scheduled posts");
+    info!(paused_by = %pause.paused_by, "Paused scheduled posts");
+    pause
+}
+
+/// Lets scheduled posts go out again; the ones that came due while paused go out on the next check.
+pub fn resume() {
+    if load_pause().is_none() {
+        return;
+    }
+    if let Err(err) = fs::remove_file(shared::path(PAUSE_FILE)) {
+        warn!(error = %err, "Failed to remove pause file");
+        return;
+    }
+    audit::record(audit::Action::Settings, None, "Resumed scheduled posts");
+    info!("Resumed scheduled posts");
+}
+
+/// Adds a post to the schedule, optionally repeating or as part of a queue.  Returns the new post's id.
+pub fn schedule(
+    text: &str,
+    platforms: &[String],
+    scheduled_at: DateTime<Utc>,
+    idempotency_key: &str,
+    recurrence: Option<Recurrence>,
+    queue: Option<&str>,
+    options: &PostOptions,
+) -> String {
+    let id = history::new_idempotency_key();
+    let mut posts = load_schedule();
+    posts.push(ScheduledPost {
+        id: id.clone(),
+        text: text.to_string(),
+        platforms: platforms.to_vec(),
+        scheduled_at,
+        idempotency_key: idempotency_key.to_string(),
+        recurrence,
+        queue: queue.map(str::to_string),
+        approval: settings::get().require_approval.then(|| Approval {
+            requested_by: current_user(),
+            requested_at: Utc::now(),
+            approved_by: None,
+            approved_at: None,
+        }),
+        author: Some(current_user()),
+        options: options.clone(),
+    });
+    posts.sort_by_key(|post| post.scheduled_at);
+    save_schedule(&posts);
+    id
+}
+
+/// Schedules another try of a post that was already approved, on `platforms` only, e.g. once a rate limit resets.
+pub fn retry_at(
+    text: &str,
+    platforms: &[String],
+    retry_at: DateTime<Utc>,
+    idempotency_key: &str,
+    options: &PostOptions,
+    author: &str,
+) {
+    let mut posts = load_schedule();
+    posts.push(ScheduledPost {
+        id: history::new_idempotency_key(),
+        text: text.to_string(),
+        platforms: platforms.to_vec(),
+        scheduled_at: retry_at,
+        idempotency_key: idempotency_key.to_string(),
+        recurrence: None,
+        queue: None,
+        approval: None,
+        author: Some(author.to_string()),
+        options: options.clone(),
+    });
+    posts.sort_by_key(|post| post.scheduled_at);
+    save_schedule(&posts);
+}
+
+/// Saves changes to a scheduled post in place, keeping its id and idempotency key.  A post that needed approval
+/// has to be approved again, since what was approved has changed.  Returns false when the post is no longer
+/// scheduled, e.g. it went out while being edited.
+pub fn update(edited: &ScheduledPost) -> bool {
+    let mut posts = load_schedule();
+    let Some(post) = posts.iter_mut().find(|post| post.id == edited.id) else {
+        return false;
+    };
+    *post = ScheduledPost {
+        approval: edited.approval.as_ref().map(|_| Approval {
+            requested_by: current_user(),
+            requested_at: Utc::now(),
+            approved_by: None,
+            approved_at: None,
+        }),
+        ..edited.clone()
+    };
+    posts.sort_by_key(|post| post.scheduled_at);
+    save_schedule(&posts);
+    audit::record(
+        audit::Action::Edit,
+        None,
+        &format!("Edited scheduled post {}", edited.id),
+    );
+    true
+}
+
+pub fn cancel(id: &str) {
+    let mut posts = load_schedule();
+    posts.retain(|post| post.id != id);
+    save_schedule(&posts);
+    audit::record(audit::Action::Delete, None, &format!("Cancelled scheduled post {}", id));
+}
+
+/// Approves a post awaiting approval.  Someone other than the requester can approve right away; the requester only
+/// after the cooldown.
+pub fn approve(id: &str) -> Result<(), String> {
+    let mut posts = load_schedule();
+    let post = posts
+        .iter_mut()
+        .find(|post| post.id == id)
+        .ok_or("The post is no longer scheduled")?;
+    let Some(approval) = post.approval.as_mut() else {
+        return Ok(());
+    };
+
+    let user = current_user();
+    if user == approval.requested_by && Utc::now() < approval.self_approval_at() {
+        return Err(format!(
+            "You scheduled this post; approve it yourself after {} or ask someone else",
+            timezone::format(approval.self_approval_at())
+        ));
+    }
+    approval.approved_by = Some(user.clone());
+    approval.approved_at = Some(Utc::now());
+    save_schedule(&posts);
+    info!(id, approved_by = %user, "Approved scheduled post");
+    Ok(())
+}
+
+/// Whether the scheduler started its last pass too long ago, e.g. a request hanging.  `None` (not started yet) isn't.
+pub fn is_stalled(checked_at: Option<DateTime<Utc>>) -> bool {
+    checked_at.is_some_and(|checked_at| Utc::now() - checked_at > STALLED_AFTER)
+}
+
+/// Publishes scheduled posts once they are due.  While offline, due posts wait until connectivity returns.
+pub async fn run(state: Arc<Mutex<posts::AppState>>) {
+    let mut held = HashSet::new();
+    loop {
+        let offline = {
+            let mut state = state.lock().await;
+            state.scheduler_checked_at = Some(Utc::now());
+            state.offline
+        };
+        if !offline {
+            publish_due(&state, &mut held).await;
+        }
+        // Pick up the posts just sent, and changes from the command line, the API or another machine
+        {
+            let mut state = state.lock().await;
+            state.schedule = load_schedule();
+            state.queues = queues::load_queues();
+            state.schedule_pause = load_pause();
+        }
+        // Pick up posts made from other machines sharing the history
+        if !settings::get().shared_data_dir.trim().is_empty() {
+            state.lock().await.history = history::load_history();
+        }
+        tokio::time::sleep(CHECK_INTERVAL).await;
+    }
+}
+
+/// Publishes the posts that are due.  `held` remembers the due posts already reported as held, with why, so each is
+/// logged once rather than on every check.
+async fn publish_due(state: &Arc<Mutex<posts::AppState>>, held: &mut HashSet<(String, &'static str)>) {
+    // With a shared schedule, only one machine may pick up due posts at a time
+    let Some(lock) = shared::FileLock::acquire("scheduler") else {
+        return;
+    };
+    // Paused posts stay in the schedule and go out once resumed
+    if load_pause().is_some() {
+        return;
+    }
+    let paused_queues: HashSet<String> = queues::load_queues()
+        .into_iter()
+        .filter(|queue| queue.paused)
+        .map(|queue| queue.name)
+        .collect();
+    let now = Utc::now();
+    let (due, mut waiting): (Vec<ScheduledPost>, Vec<ScheduledPost>) = load_schedule().into_iter().partition(|post| {
+        post.scheduled_at <= now
+            && post.approval.as_ref().map_or(true, Approval::is_approved)
+            && !post.queue.as_ref().is_some_and(|queue| paused_queues.contains(queue))
+    });
+    let mut still_held = HashSet::new();
+    for post in waiting.iter().filter(|post| post.scheduled_at <= now) {
+        let queue = post.queue.as_ref().filter(|queue| paused_queues.contains(*queue));
+        let reason = if queue.is_some() {
+            "queue paused"
+        } else if post.approval.as_ref().is_some_and(|approval| !approval.is_approved()) {
+            "awaiting approval"
+        } else {
+            continue;
+        };
+        let key = (post.id.clone(), reason);
+        if !held.contains(&key) {
+            match queue {
+                Some(queue) => info!(id = %post.id, %queue, "Scheduled post is due but its queue is paused"),
+                None => warn!(id = %post.id, "Scheduled post is due but still awaiting approval"),
+            }
+        }
+        still_held.insert(key);
+    }
+    *held = still_held;
+    if due.is_empty() {
+        return;
+    }
+
+    // Remove them first so a slow publish can't be picked up twice; the idempotency key guards against resending.
+    // Recurring posts are replaced by their next occurrence, skipping any missed while the app wasn't running.
+    for post in &due {
+        let mut next = post.next();
+        while next.as_ref().is_some_and(|next| next.scheduled_at <= now) {
+            next = next.and_then(|next| next.next());
+        }
+        match next {
+            Some(next) => waiting.push(next),
+            None if post.recurrence.is_some() => info!(id = %post.id, "Recurring post has ended"),
+            None => {}
+        }
+    }
+    waiting.sort_by_key(|post| post.scheduled_at);
+    save_schedule(&waiting);
+    drop(lock);
+    for post in due {
+        info!(id = %post.id, scheduled_at = %post.scheduled_at, "Publishing scheduled post");
+        let author = post.author.unwrap_or_else(current_user);
+        let entry = publish::publish(
+            state,
+            &post.text,
+            &post.platforms,
+            &post.idempotency_key,
+            &post.options,
+            &author,
+        )
+        .await;
+        // Nobody may be watching when a scheduled post goes out
+        if let Some(entry) = entry {
+            alerts::post_failed(&post.text, post.scheduled_at, &alerts::failures(&entry.results)).await;
+        }
+    }
+}
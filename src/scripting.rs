@@ -0,0 +1,57 @@
+use rhai::{Engine, Scope, AST};
+use std::path::Path;
+use tracing::{error, warn};
+
+use crate::settings;
+
+const BEFORE_POST: &str = "on_before_post";
+
+/// Stops runaway scripts (an accidental infinite loop) instead of hanging the post.
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+fn engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.on_print(|text| tracing::info!(script = true, "{}", text));
+    engine
+}
+
+/// Compiles the configured hook script.  The file is read on every post, so edits apply without restarting.
+fn load(engine: &Engine) -> Option<AST> {
+    let path = settings::get().hook_script;
+    if path.trim().is_empty() || !Path::new(&path).exists() {
+        return None;
+    }
+    match engine.compile_file(path.clone().into()) {
+        Ok(ast) => Some(ast),
+        Err(err) => {
+            error!(path = %path, error = %err, "Failed to compile hook script; posting unchanged text");
+            None
+        }
+    }
+}
+
+/// Runs the script's `on_before_post(platform, text)` and returns the text it gives back.  Without a script or that
+/// function, or when the script fails, the text is posted unchanged.
+pub fn before_post(platform: &str, text: &str) -> String {
+    let engine = engine();
+    let Some(ast) = load(&engine) else {
+        return text.to_string();
+    };
+    if !ast.iter_functions().any(|function| function.name == BEFORE_POST) {
+        return text.to_string();
+    }
+
+    let args = (platform.to_string(), text.to_string());
+    match engine.call_fn::<String>(&mut Scope::new(), &ast, BEFORE_POST, args) {
+        Ok(transformed) if transformed.trim().is_empty() => {
+            warn!(platform, "{} returned empty text; posting unchanged text", BEFORE_POST);
+            text.to_string()
+        }
+        Ok(transformed) => transformed,
+        Err(err) => {
+            error!(platform, error = %err, "{} failed; posting unchanged text", BEFORE_POST);
+            text.to_string()
+        }
+    }
+}
@@ -0,0 +1,348 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{OnceLock, RwLock};
+use tracing::{info, warn};
+
+use crate::activitypub::Software;
+use crate::api::ApiToken;
+use crate::audit;
+use crate::budget::PostingCap;
+use crate::github;
+use crate::layout::PanelPosition;
+use crate::limits::{OverLimit, ThreadNumbering};
+use crate::mail;
+use crate::markdown::Rendering;
+use crate::profiles;
+use crate::theme::Theme;
+use crate::transforms::Transform;
+
+const SETTINGS_FILE: &str = "settings.json";
+/// Prefix of the environment variables overriding settings, e.g. `MULTIQUE_SMTP_HOST` for `smtp_host`.
+const ENV_PREFIX: &str = "MULTIQUE_";
+
+static SETTINGS: OnceLock<RwLock<Settings>> = OnceLock::new();
+/// Names of the settings taken from the environment at startup.  They're never written to the settings file, which
+/// keeps its own values for them.
+static OVERRIDDEN: OnceLock<Vec<String>> = OnceLock::new();
+
+/// User-editable application settings, persisted as JSON next to the token files.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Total time allowed for a single HTTP request, in seconds.
+    pub request_timeout_secs: u64,
+    /// Time allowed to establish a connection, in seconds.
+    pub connect_timeout_secs: u64,
+    /// How many times a request is retried after a connection failure.
+    pub max_retries: u32,
+    /// User-Agent header sent with every request.
+    pub user_agent: String,
+    /// Per-platform API base URL overrides (keyed by lowercase platform name), for proxies, test servers, or
+    /// API-compatible alternatives.  Empty or missing entries use the platform's default.
+    pub api_base_urls: HashMap<String, String>,
+    /// Profile whose accounts are in use; see the `profiles` module.
+    pub active_profile: String,
+    /// Where `sync` keeps the encrypted bundle: a file path (e.g. in a synced folder) or a WebDAV URL.  Empty
+    /// disables sync.
+    pub sync_target: String,
+    /// What the main Mastodon account's instance runs, for servers that only mimic Mastodon's API.
+    pub mastodon_software: Software,
+    /// Whether the Twitter account can post beyond 280 characters (a paid subscription).  `None` asks Twitter.
+    pub twitter_long_posts: Option<bool>,
+    /// What to do when text is over a platform's limit, keyed by platform.  Missing entries use the default.
+    pub over_limit: HashMap<String, OverLimit>,
+    /// How the posts of a thread are numbered.
+    pub thread_numbering: ThreadNumbering,
+    /// When a post of a thread fails, delete the posts of it already made instead of leaving it unfinished.
+    pub roll_back_threads: bool,
+    /// Most posts per platform in a rolling hour and day, keyed by platform, on top of what the servers enforce.
+    /// Missing entries are uncapped.
+    pub posting_caps: HashMap<String, PostingCap>,
+    /// The composed text is Markdown, rendered for each platform before its transforms.
+    pub markdown: bool,
+    /// How Markdown is rendered, keyed by platform.  Missing entries get plain text.
+    pub markdown_rendering: HashMap<String, Rendering>,
+    /// Rewrites applied to the text before posting, keyed by platform.
+    pub transforms: HashMap<String, Vec<Transform>>,
+    /// Rewrite hashtags to CamelCase on every platform, for screen readers.
+    pub camel_case_hashtags: bool,
+    /// Replace `:shortcode:` emoji with the emoji on every platform.
+    pub expand_emoji_shortcodes: bool,
+    /// Flag profanity in the composer, with buttons to mask it or swap in milder words, e.g. for a brand account
+    /// sharing what its users wrote.
+    pub profanity_filter: bool,
+    /// Also suggest the hashtags trending on the Mastodon instance while a hashtag is typed.
+    pub trending_hashtags: bool,
+    /// IANA zone (e.g. "America/New_York") scheduled times are shown and entered in.  Empty uses the system zone.
+    pub time_zone: String,
+    /// Scheduled posts wait for approval before publishing.
+    pub require_approval: bool,
+    /// How long the author of a scheduled post must wait before approving it themselves, in minutes.
+    pub approval_cooldown_mins: u64,
+    /// Directory (e.g. a network share) holding post history, the schedule and queues, so a team can share them.
+    /// Empty keeps them in the working directory.
+    pub shared_data_dir: String,
+    /// GitHub repositories ("owner/name", comma separated) watched for new releases.  Empty disables the watcher.
+    pub github_repos: String,
+    /// Announcement text for a new release; see `github::render` for the placeholders.
+    pub release_template: String,
+    /// Post release announcements straight away instead of drafting them for review.
+    pub release_auto_post: bool,
+    /// Platforms release announcements are auto-posted to.
+    pub release_platforms: Vec<String>,
+    /// URLs (comma separated) POSTed a JSON event whenever a post succeeds or fails on a platform.  Empty calls
+    /// none.
+    pub webhook_urls: String,
+    /// Address emailed when a scheduled post fails on a platform, so failures while nobody is watching get noticed.
+    /// Empty sends no email.
+    pub alert_email_to: String,
+    /// Sender of the alert emails; empty uses `smtp_username`.
+    pub alert_email_from: String,
+    /// SMTP server the alerts are sent through, with STARTTLS (or TLS from the start on port 465).
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    pub smtp_username: String,
+    pub smtp_password: String,
+    /// Medium stories are saved as drafts to finish on Medium, rather than published.
+    pub medium_drafts: bool,
+    /// Host of the Hashnode publication posts go to, e.g. "blog.example.com".  Empty uses the account's first one.
+    pub hashnode_host: String,
+    /// How the Email platform sends posts: through the SMTP server above, or a newsletter service's API.
+    pub newsletter_service: mail::Service,
+    /// Recipients of posts sent with SMTP or Mailgun, comma separated (e.g. a mailing list address).
+    pub newsletter_to: String,
+    /// Sender of those posts; empty uses `smtp_username` with SMTP.
+    pub newsletter_from: String,
+    /// Buttondown or Mailgun API key.
+    pub newsletter_api_key: String,
+    /// Mailgun sending domain, e.g. "mg.example.com".
+    pub mailgun_domain: String,
+    /// Address the local API listens on in daemon mode (`--daemon`); empty serves no API.
+    pub api_address: String,
+    /// Bearer tokens accepted by the local API.  Without any, anything can be read but nothing posted or changed, so
+    /// only leave it empty while the address is local.
+    pub api_tokens: Vec<ApiToken>,
+    /// MQTT broker ("host" or "host:port") whose `mqtt_topic` messages are posted (built with the `mqtt` feature).
+    /// Empty subscribes to nothing.
+    pub mqtt_broker: String,
+    pub mqtt_topic: String,
+    pub mqtt_username: String,
+    pub mqtt_password: String,
+    /// Post text for a message; see `mqtt::render` for the placeholders.
+    pub mqtt_template: String,
+    /// Platforms MQTT messages are posted to.
+    pub mqtt_platforms: Vec<String>,
+    /// Rhai script with pre-post hooks (built with the `scripting` feature).  Empty or missing runs no hooks.
+    pub hook_script: String,
+    /// Colors of the window; high contrast for low vision.
+    pub theme: Theme,
+    /// No animated spinners, and collapsing sections and other transitions happen at once.
+    pub reduced_motion: bool,
+    /// Where the instructions and results panel goes beside the composer, if anywhere.
+    pub notes_panel: PanelPosition,
+    /// Share of the window's width (or height, at the bottom) the panel takes.
+    pub notes_panel_share: f32,
+    /// The getting-started steps at the top of the panel; turned off by their "Don't show again".
+    pub show_instructions: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            request_timeout_secs: 30,
+            connect_timeout_secs: 10,
+            max_retries: 2,
+            user_agent: default_user_agent(),
+            api_base_urls: HashMap::new(),
+            active_profile: profiles::DEFAULT_PROFILE.to_string(),
+            sync_target: String::new(),
+            mastodon_software: Software::default(),
+            twitter_long_posts: None,
+            over_limit: HashMap::new(),
+            thread_numbering: ThreadNumbering::default(),
+            roll_back_threads: false,
+            posting_caps: HashMap::new(),
+            markdown: false,
+            markdown_rendering: HashMap::new(),
+            transforms: HashMap::new(),
+            camel_case_hashtags: false,
+            profanity_filter: false,
+            expand_emoji_shortcodes: true,
+            trending_hashtags: false,
+            time_zone: String::new(),
+            require_approval: false,
+            approval_cooldown_mins: 60,
+            shared_data_dir: String::new(),
+            github_repos: String::new(),
+            release_template: github::DEFAULT_TEMPLATE.to_string(),
+            release_auto_post: false,
+            release_platforms: ["Twitter", "Bluesky", "Mastodon", "LinkedIn"]
+                .iter()
+                .map(|platform| platform.to_string())
+                .collect(),
+            webhook_urls: String::new(),
+            alert_email_to: String::new(),
+            alert_email_from: String::new(),
+            smtp_host: String::new(),
+            smtp_port: 587,
+            smtp_username: String::new(),
+            smtp_password: String::new(),
+            medium_drafts: false,
+            hashnode_host: String::new(),
+            newsletter_service: mail::Service::default(),
+            newsletter_to: String::new(),
+            newsletter_from: String::new(),
+            newsletter_api_key: String::new(),
+            mailgun_domain: String::new(),
+            api_address: "127.0.0.1:8787".to_string(),
+            api_tokens: Vec::new(),
+            mqtt_broker: String::new(),
+            mqtt_topic: String::new(),
+            mqtt_username: String::new(),
+            mqtt_password: String::new(),
+            mqtt_template: "{message}".to_string(),
+            mqtt_platforms: Vec::new(),
+            hook_script: "hooks.rhai".to_string(),
+            theme: Theme::default(),
+            reduced_motion: false,
+            notes_panel: PanelPosition::default(),
+            notes_panel_share: 0.4,
+            show_instructions: true,
+        }
+    }
+}
+
+impl Settings {
+    /// These settings with the secrets (passwords, API keys and tokens) of `other` instead.  Used to keep secrets out
+    /// of a config export, and to keep this machine's own over an imported one.
+    pub fn with_secrets_from(self, other: &Settings) -> Settings {
+        Settings {
+            smtp_password: other.smtp_password.clone(),
+            newsletter_api_key: other.newsletter_api_key.clone(),
+            mqtt_password: other.mqtt_password.clone(),
+            api_tokens: other.api_tokens.clone(),
+            ..self
+        }
+    }
+}
+
+/// Names of the settings that differ between `old` and `new`.
+fn changed_fields(old: &Settings, new: &Settings) -> Vec<String> {
+    let (Ok(serde_json::Value::Object(old)), Ok(serde_json::Value::Object(new))) =
+        (serde_json::to_value(old), serde_json::to_value(new))
+    else {
+        return Vec::new();
+    };
+    new.iter()
+        .filter(|(name, value)| old.get(*name) != Some(value))
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+pub fn default_user_agent() -> String {
+    format!("multique/{}", env!("CARGO_PKG_VERSION"))
+}
+
+/// Returns the configured base URL for a platform's API, or `default` when no override is set.
+pub fn api_base_url(platform: &str, default: &str) -> String {
+    match get().api_base_urls.get(platform).map(|url| url.trim()) {
+        Some(url) if !url.is_empty() => url.trim_end_matches('/').to_string(),
+        _ => default.to_string(),
+    }
+}
+
+/// The settings as saved in the settings file, without the environment overrides.
+fn load_file() -> Settings {
+    if Path::new(SETTINGS_FILE).exists() {
+        let json = fs::read_to_string(SETTINGS_FILE).expect("Failed to read settings file");
+        serde_json::from_str(&json).unwrap_or_default()
+    } else {
+        Settings::default()
+    }
+}
+
+fn load_settings() -> Settings {
+    with_env_overrides(load_file())
+}
+
+/// Applies `MULTIQUE_<SETTING>` environment variables over `settings`, so a container can be configured without a
+/// settings file.  Values are read as JSON when they parse as the setting's type (numbers, `true`, maps such as
+/// `{"mastodon": "https://fosstodon.org/api"}`), and as plain text otherwise.  Unusable values are logged and
+/// ignored.
+fn with_env_overrides(settings: Settings) -> Settings {
+    let Ok(serde_json::Value::Object(mut fields)) = serde_json::to_value(&settings) else {
+        return settings;
+    };
+    let mut overridden = Vec::new();
+    for (name, field) in fields.iter_mut() {
+        let variable = format!("{}{}", ENV_PREFIX, name.to_ascii_uppercase());
+        let Ok(value) = std::env::var(&variable) else {
+            continue;
+        };
+        let fits = |candidate: &serde_json::Value| {
+            let mut fields = serde_json::to_value(&settings).unwrap_or_default();
+            fields[name.as_str()] = candidate.clone();
+            serde_json::from_value::<Settings>(fields).is_ok()
+        };
+        let parsed = serde_json::from_str(&value)
+            .ok()
+            .into_iter()
+            .chain([serde_json::Value::String(value)])
+            .find(fits);
+        match parsed {
+            Some(parsed) => {
+                *field = parsed;
+                overridden.push(name.clone());
+            }
+            None => warn!(%variable, "Ignoring an environment variable that doesn't fit its setting"),
+        }
+    }
+    if !overridden.is_empty() {
+        info!(settings = %overridden.join(", "), "Settings taken from the environment");
+    }
+    let _ = OVERRIDDEN.set(overridden);
+    serde_json::from_value(serde_json::Value::Object(fields)).unwrap_or(settings)
+}
+
+/// Writes `settings` to the settings file, except the ones taken from the environment: for those the file keeps what
+/// it had, so secrets passed in the environment stay off disk and an override lasts only while it's set.
+fn save_settings(settings: &Settings) {
+    let mut fields = serde_json::to_value(settings).expect("Failed to serialize settings");
+    let overridden = OVERRIDDEN.get().map(Vec::as_slice).unwrap_or_default();
+    if !overridden.is_empty() {
+        let on_disk = serde_json::to_value(load_file()).expect("Failed to serialize settings");
+        for name in overridden {
+            fields[name.as_str()] = on_disk[name.as_str()].clone();
+        }
+    }
+    let json = serde_json::to_string_pretty(&fields).expect("Failed to serialize settings");
+    fs::write(SETTINGS_FILE, json).expect("Failed to write settings file");
+}
+
+/// Returns a copy of the current settings, loading them from disk on first use.
+pub fn get() -> Settings {
+    SETTINGS
+        .get_or_init(|| RwLock::new(load_settings()))
+        .read()
+        .unwrap()
+        .clone()
+}
+
+/// Replaces the current settings and persists them.
+pub fn update(settings: Settings) {
+    let changed = changed_fields(&get(), &settings);
+    if !changed.is_empty() {
+        audit::record(
+            audit::Action::Settings,
+            None,
+            &format!("Changed {}", changed.join(", ")),
+        );
+    }
+    save_settings(&settings);
+    *SETTINGS
+        .get_or_init(|| RwLock::new(Settings::default()))
+        .write()
+        .unwrap() = settings;
+}
@@ -0,0 +1,63 @@
+use std::fs::{self, OpenOptions};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use tracing::warn;
+
+use crate::settings;
+
+/// A lock older than this is assumed to be left over from a crashed instance.
+const STALE_LOCK_AGE: Duration = Duration::from_secs(120);
+
+/// Resolves a file that may be shared with teammates (history, schedule, queues): inside the shared data directory
+/// when one is configured, otherwise in the working directory.
+pub fn path(file: &str) -> PathBuf {
+    let dir = settings::get().shared_data_dir;
+    let dir = dir.trim();
+    if dir.is_empty() {
+        PathBuf::from(file)
+    } else {
+        PathBuf::from(dir).join(file)
+    }
+}
+
+/// Name recorded as the author or approver of a post: the OS user.
+pub fn current_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// Held while one instance works on a shared file, so instances on other machines don't act on it at the same time.
+/// Released on drop.
+pub struct FileLock {
+    path: PathBuf,
+}
+
+impl FileLock {
+    /// Takes the lock named `name`, or returns `None` if another instance holds it.
+    pub fn acquire(name: &str) -> Option<FileLock> {
+        let path = self::path(&format!("{}.lock", name));
+        let is_stale = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|age| age > STALE_LOCK_AGE);
+        if is_stale {
+            warn!(lock = %path.display(), "Removing stale lock");
+            let _ = fs::remove_file(&path);
+        }
+
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .ok()
+            .map(|_| FileLock { path })
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
@@ -0,0 +1,25 @@
+/// Average silent reading speed, in words per minute.
+const READING_WORDS_PER_MINUTE: usize = 238;
+
+/// Writing statistics for the composer.
+pub struct WritingStats {
+    pub words: usize,
+    pub sentences: usize,
+    pub reading_time_secs: usize,
+}
+
+pub fn analyze(text: &str) -> WritingStats {
+    let words = text.split_whitespace().count();
+    // A sentence ends at . ! or ? (runs like "?!" or "..." count once); trailing text without one is a sentence too
+    let sentences = text
+        .split(['.', '!', '?'])
+        .filter(|sentence| sentence.chars().any(char::is_alphanumeric))
+        .count();
+    let reading_time_secs = (words * 60).div_ceil(READING_WORDS_PER_MINUTE);
+
+    WritingStats {
+        words,
+        sentences,
+        reading_time_secs,
+    }
+}
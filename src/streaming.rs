@@ -0,0 +1,212 @@
+use chrono::Utc;
+use futures::StreamExt;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::{self, Message};
+use tracing::{debug, error, info, warn};
+
+use crate::inbox::{self, Notification};
+use crate::posts::{self, Visibility};
+use crate::{html, http, mastodon};
+
+/// How often the stored tokens are checked for accounts to start or stop streaming.
+const ACCOUNTS_INTERVAL: Duration = Duration::from_secs(60);
+/// Wait before reconnecting after the stream drops or the server can't be reached.
+const RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// An account to stream notifications for.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Stream {
+    platform: String,
+    base_url: String,
+    token: String,
+}
+
+/// The main account and the additional accounts with a token.
+fn streams() -> Vec<Stream> {
+    let mut streams = Vec::new();
+    if let Some(tokens) = mastodon::load_tokens() {
+        streams.push(Stream {
+            platform: "Mastodon".to_string(),
+            base_url: mastodon::api_base_url(),
+            token: tokens.access_token,
+        });
+    }
+    for account in mastodon::load_accounts() {
+        if let Some(token) = account.access_token.clone() {
+            streams.push(Stream {
+                platform: account.platform(),
+                base_url: account.base_url(),
+                token,
+            });
+        }
+    }
+    streams
+}
+
+/// Receives the notifications of every authorized Mastodon account as they happen, through the streaming API's
+/// websocket, and puts them in the inbox.  Accounts authorized or removed later are picked up within a minute.
+pub async fn listen(state: Arc<Mutex<posts::AppState>>) {
+    let mut running: HashMap<Stream, JoinHandle<()>> = HashMap::new();
+    loop {
+        let wanted = streams();
+        running.retain(|stream, task| {
+            let keep = wanted.contains(stream);
+            if !keep {
+                task.abort();
+            }
+            keep && !task.is_finished()
+        });
+        for stream in wanted {
+            if !running.contains_key(&stream) {
+                let task = tokio::spawn(follow(Arc::clone(&state), stream.clone()));
+                running.insert(stream, task);
+            }
+        }
+        tokio::time::sleep(ACCOUNTS_INTERVAL).await;
+    }
+}
+
+/// Streams one account, reconnecting when the connection drops.  Stops when the token is refused, e.g. because it
+/// was granted before the app asked for `read:notifications`.
+async fn follow(state: Arc<Mutex<posts::AppState>>, stream: Stream) {
+    loop {
+        let url = format!(
+            "{}/api/v1/streaming?stream=user:notification&access_token={}",
+            streaming_url(&stream.base_url).await,
+            stream.token
+        );
+        match tokio_tungstenite::connect_async(url.as_str()).await {
+            Ok((mut socket, _)) => {
+                info!(platform = %stream.platform, "Streaming notifications");
+                while let Some(message) = socket.next().await {
+                    match message {
+                        Ok(Message::Text(text)) => {
+                            if let Some(notification) = parse_event(&stream.platform, &text) {
+                                debug!(platform = %stream.platform, kind = %notification.kind, "Received notification");
+                                inbox::add(&state, notification).await;
+                            }
+                        }
+                        Ok(Message::Close(_)) => break,
+                        Ok(_) => {}
+                        Err(err) => {
+                            warn!(platform = %stream.platform, error = ?err, "Notification stream failed");
+                            break;
+                        }
+                    }
+                }
+                info!(platform = %stream.platform, "Notification stream closed; reconnecting");
+            }
+            Err(tungstenite::Error::Http(response)) if matches!(response.status().as_u16(), 401 | 403) => {
+                warn!(
+                    platform = %stream.platform,
+                    status = response.status().as_u16(),
+                    "Streaming refused the token; authorize the account again to receive notifications"
+                );
+                state.lock().await.toast(
+                    format!("Authorize {} again to receive its notifications", stream.platform),
+                    false,
+                );
+                return;
+            }
+            Err(err) => {
+                error!(platform = %stream.platform, error = ?err, "Error connecting to the streaming API");
+            }
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+/// The websocket address of the instance's streaming server, which may be on another host than the API.  Falls
+/// back to the API's host.
+async fn streaming_url(base_url: &str) -> String {
+    #[derive(Deserialize)]
+    struct Instance {
+        urls: Option<Urls>,
+    }
+
+    #[derive(Deserialize)]
+    struct Urls {
+        streaming_api: Option<String>,
+    }
+
+    let reported = match http::send_idempotent(http::client().get(format!("{}/api/v1/instance", base_url))).await {
+        Ok(response) if response.status().is_success() => response
+            .json::<Instance>()
+            .await
+            .ok()
+            .and_then(|instance| instance.urls)
+            .and_then(|urls| urls.streaming_api),
+        _ => None,
+    };
+    reported.unwrap_or_else(|| {
+        base_url
+            .replacen("https://", "wss://", 1)
+            .replacen("http://", "ws://", 1)
+    })
+}
+
+/// The notification in a streaming event, or `None` for other events.  The event's payload is itself JSON,
+/// encoded as a string.
+fn parse_event(platform: &str, text: &str) -> Option<Notification> {
+    #[derive(Deserialize)]
+    struct Event {
+        event: String,
+        payload: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct Payload {
+        id: String,
+        #[serde(rename = "type")]
+        kind: String,
+        account: Account,
+        status: Option<Status>,
+    }
+
+    #[derive(Deserialize)]
+    struct Account {
+        acct: String,
+    }
+
+    #[derive(Deserialize)]
+    struct Status {
+        id: String,
+        content: String,
+        url: Option<String>,
+        visibility: Option<String>, // Kept as text, since some servers have more levels (e.g. Pleroma's "local")
+    }
+
+    let event: Event = serde_json::from_str(text).ok()?;
+    if event.event != "notification" {
+        return None;
+    }
+    let payload: Payload = serde_json::from_str(&event.payload?)
+        .inspect_err(|err| warn!(platform, error = ?err, "Failed to parse notification"))
+        .ok()?;
+    Some(Notification {
+        id: payload.id,
+        platform: platform.to_string(),
+        kind: payload.kind,
+        from: format!("@{}", payload.account.acct),
+        text: payload
+            .status
+            .as_ref()
+            .map(|status| html::to_plain_text(&status.content))
+            .unwrap_or_default(),
+        visibility: match payload.status.as_ref().and_then(|status| status.visibility.as_deref()) {
+            Some("unlisted") => Visibility::Unlisted,
+            Some("private") => Visibility::Private,
+            Some("direct") => Visibility::Direct,
+            _ => Visibility::Public,
+        },
+        status_id: payload.status.as_ref().map(|status| status.id.clone()),
+        url: payload.status.and_then(|status| status.url),
+        received_at: Utc::now(),
+        read: false,
+    })
+}
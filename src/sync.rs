@@ -0,0 +1,264 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{error, info, warn};
+
+use crate::config_bundle::ConfigBundle;
+use crate::{crypto, http, profiles, settings};
+
+const SYNC_STATE_FILE: &str = "sync_state.json";
+
+/// What gets synced: configuration plus every profile's tokens.
+#[derive(Serialize, Deserialize)]
+struct SyncBundle {
+    updated_at: DateTime<Utc>,
+    machine: String,
+    config: ConfigBundle,
+    tokens: BTreeMap<String, String>, // Token file path (relative to the working directory) -> contents
+}
+
+/// Bookkeeping from the last successful sync, used to tell which side changed since.
+#[derive(Default, Serialize, Deserialize)]
+struct SyncState {
+    remote_updated_at: Option<DateTime<Utc>>,
+    local_fingerprint: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SyncOutcome {
+    UpToDate,
+    Pushed,
+    Pulled,
+    /// Both sides changed since the last sync; the user has to pick one with `push` or `pull`.
+    Conflict,
+    Failed,
+}
+
+fn load_sync_state() -> SyncState {
+    fs::read_to_string(SYNC_STATE_FILE)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_sync_state(state: &SyncState) {
+    let json = serde_json::to_string_pretty(state).expect("Failed to serialize sync state");
+    fs::write(SYNC_STATE_FILE, json).expect("Failed to write sync state file");
+}
+
+fn machine_name() -> String {
+    env::var("HOSTNAME")
+        .or_else(|_| env::var("COMPUTERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+fn local_bundle() -> SyncBundle {
+    let tokens = profiles::token_files()
+        .into_iter()
+        .filter_map(|path| {
+            let contents = fs::read_to_string(&path).ok()?;
+            Some((path.to_string_lossy().into_owned(), contents))
+        })
+        .collect();
+
+    SyncBundle {
+        updated_at: Utc::now(),
+        machine: machine_name(),
+        config: ConfigBundle::current(),
+        tokens,
+    }
+}
+
+/// Hash of the synced content, ignoring timestamps and the active profile (which is per machine).
+fn fingerprint(bundle: &SyncBundle) -> String {
+    let mut settings = bundle.config.settings.clone();
+    settings.active_profile.clear();
+    // Going through `Value` sorts object keys, so HashMap ordering doesn't change the hash
+    let content = serde_json::json!({
+        "settings": settings,
+        "profiles": bundle.config.profiles,
+        "queues": bundle.config.queues,
+        "tokens": bundle.tokens,
+    });
+    let digest = Sha256::digest(content.to_string().as_bytes());
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn is_webdav(target: &str) -> bool {
+    target.starts_with("http://") || target.starts_with("https://")
+}
+
+/// Reads the encrypted bundle from the sync target.  `Ok(None)` means nothing has been synced there yet.
+async fn read_remote(target: &str) -> Result<Option<Vec<u8>>, String> {
+    if is_webdav(target) {
+        let mut request = http::client().get(target);
+        if let Ok(username) = env::var("SYNC_WEBDAV_USERNAME") {
+            request = request.basic_auth(username, env::var("SYNC_WEBDAV_PASSWORD").ok());
+        }
+        let response = http::send_idempotent(request)
+            .await
+            .map_err(|err| format!("{:?}", err))?;
+        match response.status() {
+            status if status.is_success() => Ok(Some(response.bytes().await.map_err(|e| e.to_string())?.to_vec())),
+            reqwest::StatusCode::NOT_FOUND => Ok(None),
+            status => Err(format!("WebDAV GET returned {}", status)),
+        }
+    } else if Path::new(target).exists() {
+        fs::read(target).map(Some).map_err(|err| err.to_string())
+    } else {
+        Ok(None)
+    }
+}
+
+async fn write_remote(target: &str, bytes: Vec<u8>) -> Result<(), String> {
+    if is_webdav(target) {
+        let mut request = http::client().put(target).body(bytes);
+        if let Ok(username) = env::var("SYNC_WEBDAV_USERNAME") {
+            request = request.basic_auth(username, env::var("SYNC_WEBDAV_PASSWORD").ok());
+        }
+        let response = http::send(request).await.map_err(|err| format!("{:?}", err))?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!("WebDAV PUT returned {}", response.status()))
+        }
+    } else {
+        fs::write(target, bytes).map_err(|err| err.to_string())
+    }
+}
+
+async fn fetch_remote_bundle(target: &str, passphrase: &str) -> Result<Option<SyncBundle>, String> {
+    let Some(bytes) = read_remote(target).await? else {
+        return Ok(None);
+    };
+    let sealed: crypto::Sealed = serde_json::from_slice(&bytes).map_err(|_| "Not a multique sync file".to_string())?;
+    let plaintext = crypto::open(passphrase, &sealed).ok_or("Wrong passphrase or corrupted sync file")?;
+    serde_json::from_slice(&plaintext)
+        .map(Some)
+        .map_err(|err| err.to_string())
+}
+
+/// Uploads the local configuration and tokens, overwriting whatever is at the sync target.
+pub async fn push(passphrase: &str) -> SyncOutcome {
+    let target = settings::get().sync_target;
+    let bundle = local_bundle();
+    let sealed = crypto::seal(
+        passphrase,
+        &serde_json::to_vec(&bundle).expect("Failed to serialize sync bundle"),
+    );
+    let bytes = serde_json::to_vec(&sealed).expect("Failed to serialize sealed bundle");
+
+    match write_remote(&target, bytes).await {
+        Ok(()) => {
+            save_sync_state(&SyncState {
+                remote_updated_at: Some(bundle.updated_at),
+                local_fingerprint: Some(fingerprint(&bundle)),
+            });
+            info!(target = %target, "Pushed configuration and tokens to sync target");
+            SyncOutcome::Pushed
+        }
+        Err(err) => {
+            error!(target = %target, error = %err, "Failed to push to sync target");
+            SyncOutcome::Failed
+        }
+    }
+}
+
+/// Replaces the local configuration and tokens with the copy at the sync target.
+pub async fn pull(passphrase: &str) -> SyncOutcome {
+    let target = settings::get().sync_target;
+    let remote = match fetch_remote_bundle(&target, passphrase).await {
+        Ok(Some(remote)) => remote,
+        Ok(None) => {
+            warn!(target = %target, "Nothing to pull; the sync target is empty");
+            return SyncOutcome::Failed;
+        }
+        Err(err) => {
+            error!(target = %target, error = %err, "Failed to pull from sync target");
+            return SyncOutcome::Failed;
+        }
+    };
+
+    let remote_updated_at = remote.updated_at;
+    let machine = remote.machine.clone();
+    apply(remote);
+    save_sync_state(&SyncState {
+        remote_updated_at: Some(remote_updated_at),
+        local_fingerprint: Some(fingerprint(&local_bundle())),
+    });
+    info!(target = %target, from = %machine, "Pulled configuration and tokens from sync target");
+    SyncOutcome::Pulled
+}
+
+fn apply(bundle: SyncBundle) {
+    // Config first, so profiles referenced by token paths exist
+    bundle.config.apply();
+    for (path, contents) in bundle.tokens {
+        let path = PathBuf::from(path);
+        if profiles::is_token_path(&path) {
+            fs::write(&path, contents).expect("Failed to write token file");
+        } else {
+            warn!(path = %path.display(), "Ignoring unexpected file in sync bundle");
+        }
+    }
+}
+
+/// Two-way sync: pushes local changes, pulls remote changes, and reports a conflict when both sides changed.  On
+/// conflict the remote copy is left untouched and an encrypted copy of it is saved locally as
+/// `sync-conflict-<time>.json` for reference.
+pub async fn sync(passphrase: &str) -> SyncOutcome {
+    let target = settings::get().sync_target;
+    if target.trim().is_empty() {
+        warn!("No sync target configured");
+        return SyncOutcome::Failed;
+    }
+
+    let remote = match fetch_remote_bundle(&target, passphrase).await {
+        Ok(Some(remote)) => remote,
+        Ok(None) => return push(passphrase).await,
+        Err(err) => {
+            error!(target = %target, error = %err, "Failed to read sync target");
+            return SyncOutcome::Failed;
+        }
+    };
+
+    let state = load_sync_state();
+    let local_fingerprint = fingerprint(&local_bundle());
+    let local_changed = state.local_fingerprint.as_deref() != Some(local_fingerprint.as_str());
+    let remote_changed = state.remote_updated_at != Some(remote.updated_at);
+
+    if local_fingerprint == fingerprint(&remote) {
+        // Same content on both sides; just remember that
+        save_sync_state(&SyncState {
+            remote_updated_at: Some(remote.updated_at),
+            local_fingerprint: Some(local_fingerprint),
+        });
+        return SyncOutcome::UpToDate;
+    }
+
+    match (local_changed, remote_changed) {
+        (false, false) => SyncOutcome::UpToDate,
+        (true, false) => push(passphrase).await,
+        (false, true) => pull(passphrase).await,
+        (true, true) => {
+            let conflict_file = format!("sync-conflict-{}.json", Utc::now().format("%Y%m%d-%H%M%S"));
+            let sealed = crypto::seal(
+                passphrase,
+                &serde_json::to_vec(&remote).expect("Failed to serialize sync bundle"),
+            );
+            let copy = serde_json::to_string_pretty(&sealed).expect("Failed to serialize sealed bundle");
+            fs::write(&conflict_file, copy).expect("Failed to write conflict file");
+            warn!(
+                target = %target,
+                remote_machine = %remote.machine,
+                conflict_file = %conflict_file,
+                "Sync conflict: both this machine and the sync target changed"
+            );
+            SyncOutcome::Conflict
+        }
+    }
+}
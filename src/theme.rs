@@ -0,0 +1,84 @@
+use eframe::egui::{self, Color32, Stroke};
+use serde::{Deserialize, Serialize};
+
+/// Colors of the window, chosen in settings.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    /// White on black with strong outlines, for low vision.
+    HighContrast,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::Dark, Theme::Light, Theme::HighContrast];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Theme::Dark => "Dark",
+            Theme::Light => "Light",
+            Theme::HighContrast => "High contrast",
+        }
+    }
+
+    fn visuals(self) -> egui::Visuals {
+        match self {
+            Theme::Dark => egui::Visuals::dark(),
+            Theme::Light => egui::Visuals::light(),
+            Theme::HighContrast => high_contrast(),
+        }
+    }
+}
+
+fn high_contrast() -> egui::Visuals {
+    let mut visuals = egui::Visuals::dark();
+    visuals.override_text_color = Some(Color32::WHITE);
+    visuals.panel_fill = Color32::BLACK;
+    visuals.window_fill = Color32::BLACK;
+    visuals.extreme_bg_color = Color32::BLACK;
+    visuals.faint_bg_color = Color32::from_gray(24);
+    visuals.window_stroke = Stroke::new(2.0, Color32::WHITE);
+    visuals.hyperlink_color = Color32::YELLOW;
+    visuals.selection.bg_fill = Color32::from_rgb(0, 70, 160);
+    visuals.selection.stroke = Stroke::new(2.0, Color32::WHITE);
+
+    let widgets = &mut visuals.widgets;
+    for (widget, fill) in [
+        (&mut widgets.noninteractive, Color32::BLACK),
+        (&mut widgets.inactive, Color32::BLACK),
+        (&mut widgets.hovered, Color32::from_gray(60)),
+        (&mut widgets.active, Color32::from_gray(90)),
+        (&mut widgets.open, Color32::from_gray(60)),
+    ] {
+        widget.bg_fill = fill;
+        widget.weak_bg_fill = fill;
+        widget.bg_stroke = Stroke::new(1.5, Color32::WHITE);
+        widget.fg_stroke = Stroke::new(1.5, Color32::WHITE);
+    }
+    // Focus and hover stand out by color as well as by fill
+    widgets.hovered.bg_stroke = Stroke::new(2.0, Color32::YELLOW);
+    widgets.active.bg_stroke = Stroke::new(2.0, Color32::YELLOW);
+    visuals
+}
+
+/// Applies the theme, and with `reduced_motion` makes collapsing sections and other transitions instant.
+pub fn apply(ctx: &egui::Context, theme: Theme, reduced_motion: bool) {
+    ctx.style_mut(|style| {
+        style.visuals = theme.visuals();
+        style.animation_time = if reduced_motion {
+            0.0
+        } else {
+            egui::Style::default().animation_time
+        };
+    });
+}
+
+/// A spinner while something is in progress, or a still hourglass when motion is reduced.
+pub fn busy(ui: &mut egui::Ui, reduced_motion: bool) {
+    if reduced_motion {
+        ui.label("⏳");
+    } else {
+        ui.spinner();
+    }
+}
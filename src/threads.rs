@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::posts::PostOptions;
+use crate::publish::{Part, Posted};
+
+const THREADS_FILE: &str = "unfinished_threads.json";
+
+/// A thread that stopped partway on one platform, kept so it can be finished from the first post that wasn't made
+/// instead of being posted again from the start.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Unfinished {
+    pub platform: String,
+    /// Key of the compose action, which finds its history entry and keeps the parts' own keys the same.
+    pub idempotency_key: String,
+    /// Every post of the thread as it was going out, numbered and split.
+    pub parts: Vec<Part>,
+    /// The posts made so far, first to last.
+    pub posted: Vec<Posted>,
+    pub options: PostOptions,
+    pub stopped_at: DateTime<Utc>,
+}
+
+impl Unfinished {
+    /// Number of the post the thread continues from, counting from 1.
+    pub fn next_post(&self) -> usize {
+        self.posted.len() + 1
+    }
+
+    fn is(&self, platform: &str, idempotency_key: &str) -> bool {
+        self.platform == platform && self.idempotency_key == idempotency_key
+    }
+}
+
+pub fn load_unfinished() -> Vec<Unfinished> {
+    if Path::new(THREADS_FILE).exists() {
+        let json = fs::read_to_string(THREADS_FILE).expect("Failed to read unfinished threads file");
+        serde_json::from_str(&json).unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+pub fn save_unfinished(threads: &[Unfinished]) {
+    let json = serde_json::to_string_pretty(threads).expect("Failed to serialize unfinished threads");
+    fs::write(THREADS_FILE, json).expect("Failed to write unfinished threads file");
+}
+
+/// Keeps `thread`, replacing what was kept for the same compose action and platform.
+pub fn record(thread: Unfinished) {
+    let mut threads = load_unfinished();
+    threads.retain(|kept| !kept.is(&thread.platform, &thread.idempotency_key));
+    threads.push(thread);
+    save_unfinished(&threads);
+}
+
+/// Forgets the thread of a compose action on `platform`, once it's finished or given up on.
+pub fn remove(platform: &str, idempotency_key: &str) {
+    let mut threads = load_unfinished();
+    threads.retain(|kept| !kept.is(platform, idempotency_key));
+    save_unfinished(&threads);
+}
+
+/// Whether a thread of the compose action is unfinished on `platform`.
+pub fn is_unfinished(platform: &str, idempotency_key: &str) -> bool {
+    load_unfinished().iter().any(|kept| kept.is(platform, idempotency_key))
+}
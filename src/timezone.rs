@@ -0,0 +1,78 @@
+use chrono::{DateTime, LocalResult, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::settings;
+
+/// Format used to show and enter scheduled times.
+pub const INPUT_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// The zone times are shown and entered in: the configured IANA zone, or `None` for the system's local zone.
+pub fn configured() -> Option<Tz> {
+    let name = settings::get().time_zone;
+    let name = name.trim();
+    if name.is_empty() {
+        None
+    } else {
+        name.parse().ok()
+    }
+}
+
+/// Whether `name` is empty (system zone) or a known IANA zone name such as "Europe/Berlin".
+pub fn is_valid(name: &str) -> bool {
+    name.trim().is_empty() || name.trim().parse::<Tz>().is_ok()
+}
+
+/// Short name of the configured zone, for labels.
+pub fn label() -> String {
+    match configured() {
+        Some(zone) => zone.name().to_string(),
+        None => "local time".to_string(),
+    }
+}
+
+/// Parses a wall-clock time entered in the configured zone.  Times repeated by a daylight saving change resolve to
+/// the earlier one; times skipped by one are rejected.
+pub fn parse(input: &str) -> Result<DateTime<Utc>, String> {
+    let naive = NaiveDateTime::parse_from_str(input.trim(), INPUT_FORMAT)
+        .map_err(|_| format!("Expected a time like {}", Utc::now().format(INPUT_FORMAT)))?;
+    from_wall_clock(naive)
+}
+
+/// Converts a wall-clock time in the configured zone to UTC, with the same rules as `parse`.
+pub fn from_wall_clock(naive: NaiveDateTime) -> Result<DateTime<Utc>, String> {
+    match configured() {
+        Some(zone) => resolve(&zone, naive),
+        None => resolve(&chrono::Local, naive),
+    }
+}
+
+/// The wall-clock time in the configured zone at `time`.
+pub fn to_wall_clock(time: DateTime<Utc>) -> NaiveDateTime {
+    match configured() {
+        Some(zone) => time.with_timezone(&zone).naive_local(),
+        None => time.with_timezone(&chrono::Local).naive_local(),
+    }
+}
+
+fn resolve<Z: TimeZone>(zone: &Z, naive: NaiveDateTime) -> Result<DateTime<Utc>, String> {
+    match zone.from_local_datetime(&naive) {
+        LocalResult::Single(time) => Ok(time.with_timezone(&Utc)),
+        LocalResult::Ambiguous(earliest, _) => Ok(earliest.with_timezone(&Utc)),
+        LocalResult::None => Err(format!(
+            "{} doesn't exist in {} (skipped by a daylight saving change)",
+            naive.format(INPUT_FORMAT),
+            label()
+        )),
+    }
+}
+
+/// Formats a UTC time in the configured zone, with its abbreviation (e.g. "2025-03-30 09:00 CEST").
+pub fn format(time: DateTime<Utc>) -> String {
+    match configured() {
+        Some(zone) => time.with_timezone(&zone).format("%Y-%m-%d %H:%M %Z").to_string(),
+        None => time
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M %Z")
+            .to_string(),
+    }
+}
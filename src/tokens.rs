@@ -0,0 +1,92 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+use tracing::{error, info};
+
+use crate::profiles;
+
+/// Upgrades the stored data of a token file by one version.
+pub type Migration = fn(Value) -> Value;
+
+/// Version of a token file written as `migrations` describe it: files from before versioning are version 1, and
+/// each migration adds one.
+fn current_version(migrations: &[Migration]) -> u64 {
+    1 + migrations.len() as u64
+}
+
+/// Writes `data` to the active profile's `file`, wrapped as `{"version", "data"}`.
+pub fn save<T: Serialize>(file: &str, migrations: &[Migration], data: &T) {
+    let envelope = json!({
+        "version": current_version(migrations),
+        "data": data,
+    });
+    let json = serde_json::to_string_pretty(&envelope).expect("Failed to serialize token data");
+    fs::write(profiles::path(file), json).expect("Failed to write token file");
+}
+
+/// Reads the active profile's `file`, upgrading data written by older versions with `migrations` (the first one
+/// upgrades version 1, and so on).  Files from before versioning hold the data without a wrapper.
+///
+/// A file that can't be read as the current format (written by a newer version, or damaged) is copied to
+/// `<file>.bak` before `None` is returned, so authorizing again doesn't destroy the old session.
+pub fn load<T: DeserializeOwned>(file: &str, migrations: &[Migration]) -> Option<T> {
+    let path = profiles::path(file);
+    if !path.exists() {
+        return None;
+    }
+    let json = fs::read_to_string(&path).expect("Failed to read token file");
+    let value: Value = match serde_json::from_str(&json) {
+        Ok(value) => value,
+        Err(err) => {
+            error!(file, error = %err, "Token file isn't valid JSON");
+            back_up(&path);
+            return None;
+        }
+    };
+
+    let (version, mut data) = match value {
+        Value::Object(mut envelope) if envelope.contains_key("version") && envelope.contains_key("data") => {
+            let version = envelope.get("version").and_then(Value::as_u64).unwrap_or(0);
+            (version, envelope.remove("data").unwrap_or_default())
+        }
+        bare => (1, bare),
+    };
+    let current = current_version(migrations);
+    if version == 0 || version > current {
+        error!(
+            file,
+            version,
+            supported = current,
+            "Token file was written by an unsupported version"
+        );
+        back_up(&path);
+        return None;
+    }
+    for migration in &migrations[(version - 1) as usize..] {
+        data = migration(data);
+    }
+
+    match serde_json::from_value(data) {
+        Ok(tokens) => {
+            if version < current {
+                info!(file, from = version, to = current, "Upgraded token file");
+            }
+            Some(tokens)
+        }
+        Err(err) => {
+            error!(file, version, error = %err, "Token file doesn't match the expected format");
+            back_up(&path);
+            None
+        }
+    }
+}
+
+fn back_up(path: &Path) {
+    let backup = path.with_extension("json.bak");
+    match fs::copy(path, &backup) {
+        Ok(_) => error!(backup = %backup.display(), "Kept a copy of the unreadable token file"),
+        Err(err) => error!(error = ?err, "Failed to back up the unreadable token file"),
+    }
+}
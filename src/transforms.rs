@@ -0,0 +1,275 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+use crate::{limits, markdown, settings};
+
+/// Longest word in `hashtag_words.txt` worth looking for.
+const MAX_WORD_LEN: usize = 16;
+
+/// A rewrite applied to the text before it is posted to a platform.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Transform {
+    /// Replaces known `:shortcode:` emoji (`:tada:`) with the emoji itself.
+    ExpandEmojiShortcodes,
+    /// Drops hashtags at the end of the text and removes the `#` from ones inside sentences.
+    StripHashtags,
+    /// Rewrites hashtags to CamelCase; see `camel_case`.
+    CamelCaseHashtags,
+    /// Drops mentions at the end of the text and removes the `@` from ones inside sentences, for platforms where the
+    /// handles don't exist.
+    StripMentions,
+}
+
+impl Transform {
+    pub const ALL: [Transform; 4] = [
+        Transform::ExpandEmojiShortcodes,
+        Transform::StripHashtags,
+        Transform::CamelCaseHashtags,
+        Transform::StripMentions,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Transform::ExpandEmojiShortcodes => "Expand :emoji:",
+            Transform::StripHashtags => "Strip hashtags",
+            Transform::CamelCaseHashtags => "CamelCase hashtags",
+            Transform::StripMentions => "Strip mentions",
+        }
+    }
+
+    pub fn apply(self, text: &str) -> String {
+        match self {
+            Transform::ExpandEmojiShortcodes => expand_shortcodes(text),
+            Transform::StripHashtags => strip(text, '#', is_hashtag_char),
+            Transform::CamelCaseHashtags => rewrite(text, '#', is_hashtag_char, |tag| format!("#{}", camel_case(tag))),
+            Transform::StripMentions => strip(text, '@', is_mention_char),
+        }
+    }
+}
+
+/// Renders Markdown for `platform` when the composer is in Markdown, then applies the transforms configured for
+/// `platform`, plus emoji shortcodes and CamelCase hashtags everywhere when those options are on, in the order they
+/// are listed in `Transform::ALL`.
+pub fn apply(platform: &str, text: &str) -> String {
+    let settings = settings::get();
+    let text = if settings.markdown {
+        let rendering = settings
+            .markdown_rendering
+            .get(platform)
+            .or_else(|| settings.markdown_rendering.get(limits::base_platform(platform)))
+            .copied()
+            .unwrap_or_default();
+        markdown::render(text, rendering)
+    } else {
+        text.to_string()
+    };
+    let mut configured = settings
+        .transforms
+        .get(platform)
+        .or_else(|| settings.transforms.get(limits::base_platform(platform)))
+        .cloned()
+        .unwrap_or_default();
+    if settings.expand_emoji_shortcodes {
+        configured.push(Transform::ExpandEmojiShortcodes);
+    }
+    if settings.camel_case_hashtags {
+        configured.push(Transform::CamelCaseHashtags);
+    }
+    Transform::ALL
+        .into_iter()
+        .filter(|transform| configured.contains(transform))
+        .fold(text, |text, transform| transform.apply(&text))
+}
+
+fn is_hashtag_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Also allows the dots and second `@` of handles like `@alice.bsky.social` and `@alice@fosstodon.org`.
+fn is_mention_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '.' | '-' | '@')
+}
+
+/// Replaces every `sigil`-prefixed token with what `replace` returns for its body.  A token starts at the beginning
+/// of the text or after whitespace or an opening bracket, so URL fragments and email addresses are left alone.
+fn rewrite(text: &str, sigil: char, is_body: fn(char) -> bool, replace: impl Fn(&str) -> String) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut result = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let starts_token = chars[i] == sigil && (i == 0 || chars[i - 1].is_whitespace() || chars[i - 1] == '(');
+        if !starts_token {
+            result.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        let mut end = i + 1;
+        while end < chars.len() && is_body(chars[end]) {
+            end += 1;
+        }
+        // Sentence punctuation right after a handle isn't part of it
+        while end > i + 1 && matches!(chars[end - 1], '.' | '-' | '@') {
+            end -= 1;
+        }
+        if end == i + 1 {
+            result.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        let body: String = chars[i + 1..end].iter().collect();
+        result.push_str(&replace(&body));
+        i = end;
+    }
+    result
+}
+
+/// Removes a trailing block of `sigil` tokens (the usual "#rust #programming" footer), then drops the sigil from the
+/// rest so sentences still read.
+fn strip(text: &str, sigil: char, is_body: fn(char) -> bool) -> String {
+    let is_token = |word: &str| {
+        let mut chars = word.chars();
+        chars.next() == Some(sigil) && chars.as_str().chars().all(is_body) && word.chars().count() > 1
+    };
+    let mut kept = text.trim_end();
+    while let Some(last) = kept.split_whitespace().last() {
+        if !is_token(last) {
+            break;
+        }
+        kept = kept[..kept.len() - last.len()].trim_end();
+    }
+    rewrite(kept, sigil, is_body, |body| body.to_string())
+}
+
+/// Rewrites a hashtag to CamelCase (#rustlang -> #RustLang, #rust_lang -> #RustLang) so screen readers read the
+/// words separately.  Runs of lowercase letters are split into words from a built-in list; runs that can't be split
+/// only get their first letter capitalized.  Tags with capitals in them are taken as already written the way the
+/// author wants, apart from dropping underscores.
+pub fn camel_case(tag: &str) -> String {
+    let respect_case = tag.chars().any(char::is_uppercase);
+    let mut result = String::with_capacity(tag.len());
+    for part in tag.split('_').filter(|part| !part.is_empty()) {
+        let chars: Vec<char> = part.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            if !chars[i].is_alphabetic() {
+                result.push(chars[i]);
+                i += 1;
+                continue;
+            }
+            let start = i;
+            while i < chars.len() && chars[i].is_alphabetic() {
+                i += 1;
+            }
+            let run: String = chars[start..i].iter().collect();
+            if respect_case {
+                result.push_str(&capitalize(&run));
+                continue;
+            }
+            match split_words(&run) {
+                Some(words) => words.iter().for_each(|word| result.push_str(&capitalize(word))),
+                None => result.push_str(&capitalize(&run)),
+            }
+        }
+    }
+    // A tag that was only underscores stays as it was
+    if result.is_empty() {
+        tag.to_string()
+    } else {
+        result
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Replaces each `:name:` found in the built-in shortcode list with its emoji.  Unknown names are left alone, so
+/// Mastodon custom emoji and times like 10:30:00 come through unchanged.
+fn expand_shortcodes(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(':') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let end = after.find(|c: char| !is_shortcode_char(c));
+        let emoji = match end {
+            Some(end) if after[end..].starts_with(':') => shortcodes().get(&after[..end]),
+            _ => None,
+        };
+        match (emoji, end) {
+            (Some(emoji), Some(end)) => {
+                result.push_str(emoji);
+                rest = &after[end + 1..];
+            }
+            _ => {
+                result.push(':');
+                rest = after;
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+fn is_shortcode_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '+' | '-')
+}
+
+fn shortcodes() -> &'static HashMap<&'static str, &'static str> {
+    static SHORTCODES: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+    SHORTCODES.get_or_init(|| {
+        include_str!("emoji_shortcodes.txt")
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| line.split_once(' '))
+            .collect()
+    })
+}
+
+fn dictionary() -> &'static HashSet<&'static str> {
+    static WORDS: OnceLock<HashSet<&'static str>> = OnceLock::new();
+    WORDS.get_or_init(|| {
+        include_str!("hashtag_words.txt")
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect()
+    })
+}
+
+/// Splits a lowercase run into the fewest dictionary words, or `None` when it isn't made of dictionary words.
+fn split_words(run: &str) -> Option<Vec<&str>> {
+    if !run.chars().all(|c| c.is_ascii_lowercase()) {
+        return None;
+    }
+    let words = dictionary();
+    // fewest[i]: fewest words making up run[..i], with where the last word starts
+    let mut fewest: Vec<Option<(usize, usize)>> = vec![None; run.len() + 1];
+    fewest[0] = Some((0, 0));
+    for end in 1..=run.len() {
+        for start in end.saturating_sub(MAX_WORD_LEN)..end {
+            let Some((count, _)) = fewest[start] else {
+                continue;
+            };
+            if words.contains(&run[start..end]) && fewest[end].map_or(true, |(best, _)| count + 1 < best) {
+                fewest[end] = Some((count + 1, start));
+            }
+        }
+    }
+
+    let mut split = Vec::new();
+    let mut end = run.len();
+    while end > 0 {
+        let (_, start) = fewest[end]?;
+        split.push(&run[start..end]);
+        end = start;
+    }
+    split.reverse();
+    Some(split)
+}
@@ -1,15 +1,33 @@
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::env;
-use std::fs;
-use std::path::Path;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
+use tracing::{error, info, warn};
 use url::Url;
 
 const TOKEN_FILE: &str = "twitter_tokens.json";
-
-use crate::posts;
+/// Upgrades of `TokenData` as stored by older versions; see `tokens::load`.
+const TOKEN_MIGRATIONS: &[tokens::Migration] = &[];
+const DEFAULT_API_BASE_URL: &str = "https://api.twitter.com";
+
+/// Pause between the tweets of a thread.  Replies posted in a quick burst look automated to Twitter, which may lock
+/// the account.
+pub const THREAD_POST_INTERVAL: Duration = Duration::from_secs(3);
+
+use crate::audit;
+use crate::engagement::Engagement;
+use crate::http;
+use crate::limits::{self, PlatformLimits};
+use crate::posts::{self, PostError, ReplySettings};
+use crate::ratelimit;
+use crate::settings;
+use crate::tokens;
+
+/// The API host to talk to, honoring the override in settings.
+fn api_base_url() -> String {
+    settings::api_base_url("twitter", DEFAULT_API_BASE_URL)
+}
 
 #[derive(Serialize, Deserialize)]
 pub struct TokenData {
@@ -22,8 +40,7 @@ pub fn save_tokens(access_token: &str, refresh_token: Option<&str>) {
         access_token: access_token.to_string(),
         refresh_token: refresh_token.map(|rt| rt.to_string()),
     };
-    let json = serde_json::to_string(&token_data).expect("Failed to serialize token data");
-    fs::write(TOKEN_FILE, json).expect("Failed to write token file");
+    tokens::save(TOKEN_FILE, TOKEN_MIGRATIONS, &token_data);
 }
 
 pub fn load_bearer_token() -> Option<String> {
@@ -35,12 +52,7 @@ pub fn load_bearer_token() -> Option<String> {
 }
 
 fn load_tokens() -> Option<TokenData> {
-    if Path::new(TOKEN_FILE).exists() {
-        let json = fs::read_to_string(TOKEN_FILE).expect("Failed to read token file");
-        serde_json::from_str(&json).ok()
-    } else {
-        None
-    }
+    tokens::load(TOKEN_FILE, TOKEN_MIGRATIONS)
 }
 
 /// Refreshes the Twitter token using the refresh token.
@@ -59,21 +71,25 @@ pub async fn refresh_twitter_token(refresh_token: &str) -> Option<String> {
     }
 
     let client_id = env::var("TWITTER_CLIENT_ID").expect("TWITTER_CLIENT_ID not set");
-    let client = Client::new();
+    let client = http::client();
     let refresh_request = TokenRefreshRequest {
         refresh_token: refresh_token.to_string(),
         grant_type: "refresh_token".to_string(),
         client_id,
     };
 
-    match client
-        .post("https://api.twitter.com/2/oauth2/token")
-        .form(&refresh_request)
-        .send()
-        .await
+    let started = Instant::now();
+    match http::send(
+        client
+            .post(format!("{}/2/oauth2/token", api_base_url()))
+            .form(&refresh_request),
+    )
+    .await
     {
         Ok(response) => {
-            if response.status().is_success() {
+            let status = response.status();
+            let duration_ms = started.elapsed().as_millis() as u64;
+            if status.is_success() {
                 if let Ok(refresh_response) = response.json::<TokenRefreshResponse>().await {
                     let new_access_token = refresh_response.access_token.clone();
                     let new_refresh_token = refresh_response.refresh_token.clone();
@@ -81,18 +97,38 @@ pub async fn refresh_twitter_token(refresh_token: &str) -> Option<String> {
                     // Save the new tokens
                     save_tokens(&new_access_token, new_refresh_token.as_deref());
 
+                    info!(
+                        platform = "twitter",
+                        request = "refresh_token",
+                        status = status.as_u16(),
+                        duration_ms,
+                        "Refreshed Twitter token"
+                    );
+                    audit::record(audit::Action::Refresh, Some("Twitter"), "");
                     Some(new_access_token)
                 } else {
-                    println!("Failed to parse token refresh response.");
+                    error!(
+                        platform = "twitter",
+                        request = "refresh_token",
+                        "Failed to parse token refresh response"
+                    );
                     None
                 }
             } else {
-                println!("Failed to refresh Twitter token: {:?}", response.text().await);
+                let body = response.text().await.unwrap_or_default();
+                warn!(
+                    platform = "twitter",
+                    request = "refresh_token",
+                    status = status.as_u16(),
+                    duration_ms,
+                    %body,
+                    "Failed to refresh Twitter token"
+                );
                 None
             }
         }
         Err(err) => {
-            println!("Error refreshing Twitter token: {:?}", err);
+            error!(platform = "twitter", request = "refresh_token", error = ?err, "Error refreshing Twitter token");
             None
         }
     }
@@ -136,7 +172,7 @@ pub async fn authorize_twitter(state: Arc<Mutex<posts::AppState>>, authorization
     let client_id = env::var("TWITTER_CLIENT_ID").expect("TWITTER_CLIENT_ID not set");
     let redirect_uri = env::var("TWITTER_REDIRECT_URI").expect("TWITTER_REDIRECT_URI not set");
 
-    let client = Client::new();
+    let client = http::client();
     let token_request = TokenRequest {
         code: authorization_code.to_string(),
         grant_type: "authorization_code".to_string(),
@@ -145,14 +181,18 @@ pub async fn authorize_twitter(state: Arc<Mutex<posts::AppState>>, authorization
         code_verifier: "challenge".to_string(), // This must match the `code_challenge` value in `generate_auth_url`.
     };
 
-    match client
-        .post("https://api.twitter.com/2/oauth2/token")
-        .form(&token_request)
-        .send()
-        .await
+    let started = Instant::now();
+    match http::send(
+        client
+            .post(format!("{}/2/oauth2/token", api_base_url()))
+            .form(&token_request),
+    )
+    .await
     {
         Ok(response) => {
-            if response.status().is_success() {
+            let status = response.status();
+            let duration_ms = started.elapsed().as_millis() as u64;
+            if status.is_success() {
                 if let Ok(token_response) = response.json::<TokenResponse>().await {
                     let access_token = token_response.access_token.clone();
                     let refresh_token = token_response.refresh_token.clone();
@@ -164,18 +204,38 @@ pub async fn authorize_twitter(state: Arc<Mutex<posts::AppState>>, authorization
                     let mut state_guard = state.lock().await;
                     state_guard.twitter_authorized = true;
 
+                    info!(
+                        platform = "twitter",
+                        request = "token",
+                        status = status.as_u16(),
+                        duration_ms,
+                        "Authorized Twitter"
+                    );
+                    audit::record(audit::Action::Authorize, Some("Twitter"), "");
                     Some(access_token)
                 } else {
-                    println!("Failed to parse token response.");
+                    error!(
+                        platform = "twitter",
+                        request = "token",
+                        "Failed to parse token response"
+                    );
                     None
                 }
             } else {
-                println!("Failed to authorize Twitter: {:?}", response.text().await);
+                let body = response.text().await.unwrap_or_default();
+                warn!(
+                    platform = "twitter",
+                    request = "token",
+                    status = status.as_u16(),
+                    duration_ms,
+                    %body,
+                    "Failed to authorize Twitter"
+                );
                 None
             }
         }
         Err(err) => {
-            println!("Error sending request: {:?}", err);
+            error!(platform = "twitter", request = "token", error = ?err, "Error sending request");
             None
         }
     }
@@ -194,65 +254,349 @@ pub async fn regenerate_twitter_token() -> Option<String> {
         // Call authorize_twitter with the new code
         let state = Arc::new(Mutex::new(posts::AppState::default()));
         if let Some(new_token) = authorize_twitter(state.clone(), code).await {
-            println!("Successfully reauthorized Twitter.");
+            info!(platform = "twitter", "Successfully reauthorized Twitter");
             return Some(new_token);
         } else {
-            println!("Failed to reauthorize Twitter.");
+            warn!(platform = "twitter", "Failed to reauthorize Twitter");
         }
     } else {
-        println!("Failed to generate authorization URL.");
+        error!(platform = "twitter", "Failed to generate authorization URL");
     }
 
     None
 }
 
-pub async fn post_to_twitter(token: &str, text: &str) -> bool {
+/// Reads the account's subscription to tell whether it can post beyond 280 characters.  Every paid tier (Basic,
+/// Premium, Premium+) can.
+pub async fn fetch_account_limits(token: &str) -> Option<PlatformLimits> {
+    #[derive(Deserialize)]
+    struct User {
+        subscription_type: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct UserResponse {
+        data: User,
+    }
+
+    let started = Instant::now();
+    let request = http::client()
+        .get(format!("{}/2/users/me", api_base_url()))
+        .query(&[("user.fields", "subscription_type")])
+        .bearer_auth(token);
+    match http::send_idempotent(request).await {
+        Ok(response) if response.status().is_success() => {
+            let user = response.json::<UserResponse>().await.ok()?.data;
+            let subscribed = user.subscription_type.is_some_and(|tier| tier != "None");
+            Some(PlatformLimits {
+                max_chars: if subscribed {
+                    limits::TWITTER_LONG_POST_MAX_CHARS
+                } else {
+                    limits::default_for("Twitter").max_chars
+                },
+                detected: true,
+                ..limits::default_for("Twitter")
+            })
+        }
+        Ok(response) => {
+            let status = response.status();
+            let duration_ms = started.elapsed().as_millis() as u64;
+            let body = response.text().await.unwrap_or_default();
+            warn!(
+                platform = "twitter",
+                request = "users/me",
+                status = status.as_u16(),
+                duration_ms,
+                %body,
+                "Failed to fetch account subscription"
+            );
+            None
+        }
+        Err(err) => {
+            error!(platform = "twitter", request = "users/me", error = ?err, "Error fetching account subscription");
+            None
+        }
+    }
+}
+
+/// Public web address of a tweet.
+pub fn post_url(tweet_id: &str) -> String {
+    format!("https://x.com/i/web/status/{}", tweet_id)
+}
+
+/// Posts a tweet, optionally as a reply to `reply_to`, returning the id of the created tweet.
+pub async fn post_to_twitter(
+    token: &str,
+    text: &str,
+    reply_to: Option<&str>,
+    reply_settings: ReplySettings,
+) -> Result<String, PostError> {
     #[derive(Serialize)]
     struct TwitterPost {
         text: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reply: Option<Reply>,
+        // Everyone can reply unless this is set
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reply_settings: Option<ReplySettings>,
+    }
+
+    #[derive(Serialize)]
+    struct Reply {
+        in_reply_to_tweet_id: String,
+    }
+
+    #[derive(Deserialize)]
+    struct TweetData {
+        id: String,
+    }
+
+    #[derive(Deserialize)]
+    struct TweetResponse {
+        data: TweetData,
     }
 
-    let client = Client::new();
-    let post_data = TwitterPost { text: text.to_string() };
+    let client = http::client();
+    let post_data = TwitterPost {
+        text: text.to_string(),
+        reply: reply_to.map(|id| Reply {
+            in_reply_to_tweet_id: id.to_string(),
+        }),
+        reply_settings: (reply_settings != ReplySettings::Everyone).then_some(reply_settings),
+    };
 
-    match client
-        .post("https://api.twitter.com/2/tweets")
-        .bearer_auth(token)
-        .json(&post_data)
-        .send()
-        .await
+    let started = Instant::now();
+    match http::send(
+        client
+            .post(format!("{}/2/tweets", api_base_url()))
+            .bearer_auth(token)
+            .json(&post_data),
+    )
+    .await
     {
         Ok(response) => {
+            ratelimit::record("Twitter", response.headers());
             let status = response.status();
+            let duration_ms = started.elapsed().as_millis() as u64;
             if status.is_success() {
-                true
+                match response.json::<TweetResponse>().await {
+                    Ok(tweet) => {
+                        info!(
+                            platform = "twitter",
+                            request = "tweets",
+                            status = status.as_u16(),
+                            duration_ms,
+                            post_id = %tweet.data.id,
+                            "Posted tweet"
+                        );
+                        Ok(tweet.data.id)
+                    }
+                    Err(_) => {
+                        error!(
+                            platform = "twitter",
+                            request = "tweets",
+                            "Posted, but failed to parse the tweet response"
+                        );
+                        Err(PostError::Rejected)
+                    }
+                }
             } else if status == 401 {
                 // Attempt token refresh
                 if let Some(refresh_token) = load_tokens().and_then(|t| t.refresh_token) {
-                    println!("Twitter token expired. Attempting refresh...");
+                    info!(
+                        platform = "twitter",
+                        request = "tweets",
+                        status = status.as_u16(),
+                        "Twitter token expired. Attempting refresh"
+                    );
                     if let Some(new_token) = refresh_twitter_token(&refresh_token).await {
-                        return Box::pin(post_to_twitter(&new_token, text)).await;
+                        return Box::pin(post_to_twitter(&new_token, text, reply_to, reply_settings)).await;
                     } else {
-                        println!("Refresh token failed. Triggering reauthorization...");
+                        warn!(platform = "twitter", "Refresh token failed. Triggering reauthorization");
                     }
                 }
 
                 // Trigger reauthorization if refresh fails
-                println!("Reauthorizing Twitter...");
+                info!(platform = "twitter", "Reauthorizing Twitter");
                 if let Some(new_token) = regenerate_twitter_token().await {
-                    return Box::pin(post_to_twitter(&new_token, text)).await;
+                    return Box::pin(post_to_twitter(&new_token, text, reply_to, reply_settings)).await;
                 }
 
-                println!("Failed to refresh or regenerate Twitter token.");
-                false
+                error!(platform = "twitter", "Failed to refresh or regenerate Twitter token");
+                Err(PostError::Unauthorized)
+            } else {
+                let body = response.text().await.unwrap_or_default();
+                warn!(
+                    platform = "twitter",
+                    request = "tweets",
+                    status = status.as_u16(),
+                    duration_ms,
+                    %body,
+                    "Failed to post tweet"
+                );
+                Err(PostError::from_status(status))
+            }
+        }
+        Err(err) => {
+            error!(platform = "twitter", request = "tweets", error = ?err, "Error posting to Twitter");
+            Err(PostError::Network)
+        }
+    }
+}
+
+/// Deletes one of the account's tweets, as when a thread that failed partway is rolled back.  Returns whether it is
+/// gone.
+pub async fn delete_tweet(token: &str, tweet_id: &str) -> bool {
+    let client = http::client();
+    let started = Instant::now();
+    match http::send_idempotent(
+        client
+            .delete(format!("{}/2/tweets/{}", api_base_url(), tweet_id))
+            .bearer_auth(token),
+    )
+    .await
+    {
+        Ok(response) => {
+            ratelimit::record("Twitter", response.headers());
+            let status = response.status();
+            let duration_ms = started.elapsed().as_millis() as u64;
+            // Already gone counts as deleted, so a retried rollback doesn't report a failure
+            if status.is_success() || status == reqwest::StatusCode::NOT_FOUND {
+                info!(
+                    platform = "twitter",
+                    request = "delete_tweet",
+                    status = status.as_u16(),
+                    duration_ms,
+                    post_id = tweet_id,
+                    "Deleted tweet"
+                );
+                true
             } else {
-                println!("Failed to post to Twitter: {:?}", response.text().await);
+                let body = response.text().await.unwrap_or_default();
+                warn!(
+                    platform = "twitter",
+                    request = "delete_tweet",
+                    status = status.as_u16(),
+                    duration_ms,
+                    %body,
+                    "Failed to delete tweet"
+                );
                 false
             }
         }
         Err(err) => {
-            println!("Error posting to Twitter: {:?}", err);
+            error!(platform = "twitter", request = "delete_tweet", error = ?err, "Error deleting tweet");
             false
         }
     }
 }
+
+/// The account named exactly `username`, as (username, display name), from `/2/users/by/username`.  Searching for
+/// users isn't available with ordinary API access, so an exact match is all there is to suggest.
+pub async fn lookup_user(token: &str, username: &str) -> Option<(String, String)> {
+    #[derive(Deserialize)]
+    struct User {
+        username: String,
+        name: String,
+    }
+
+    #[derive(Deserialize)]
+    struct UserResponse {
+        data: Option<User>,
+    }
+
+    if username.is_empty() || !username.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return None;
+    }
+    let client = http::client();
+    let started = Instant::now();
+    match http::send_idempotent(
+        client
+            .get(format!("{}/2/users/by/username/{}", api_base_url(), username))
+            .bearer_auth(token),
+    )
+    .await
+    {
+        Ok(response) if response.status().is_success() => {
+            let user = response.json::<UserResponse>().await.ok()?.data?;
+            Some((user.username, user.name))
+        }
+        Ok(response) => {
+            let status = response.status();
+            let duration_ms = started.elapsed().as_millis() as u64;
+            warn!(
+                platform = "twitter",
+                request = "get_user",
+                status = status.as_u16(),
+                duration_ms,
+                "Failed to look up user"
+            );
+            None
+        }
+        Err(err) => {
+            error!(platform = "twitter", request = "get_user", error = ?err, "Error looking up user");
+            None
+        }
+    }
+}
+
+/// Fetches the public metrics (impressions, likes, retweets, replies) of a tweet.
+pub async fn fetch_engagement(token: &str, tweet_id: &str) -> Option<Engagement> {
+    #[derive(Deserialize)]
+    struct PublicMetrics {
+        retweet_count: u64,
+        reply_count: u64,
+        like_count: u64,
+        quote_count: u64,
+        impression_count: Option<u64>,
+    }
+
+    #[derive(Deserialize)]
+    struct TweetData {
+        public_metrics: PublicMetrics,
+    }
+
+    #[derive(Deserialize)]
+    struct TweetResponse {
+        data: TweetData,
+    }
+
+    let client = http::client();
+    let started = Instant::now();
+    match http::send_idempotent(
+        client
+            .get(format!("{}/2/tweets/{}", api_base_url(), tweet_id))
+            .query(&[("tweet.fields", "public_metrics")])
+            .bearer_auth(token),
+    )
+    .await
+    {
+        Ok(response) if response.status().is_success() => {
+            let metrics = response.json::<TweetResponse>().await.ok()?.data.public_metrics;
+            Some(Engagement {
+                impressions: metrics.impression_count,
+                likes: Some(metrics.like_count),
+                reposts: Some(metrics.retweet_count + metrics.quote_count),
+                replies: Some(metrics.reply_count),
+            })
+        }
+        Ok(response) => {
+            let status = response.status();
+            let duration_ms = started.elapsed().as_millis() as u64;
+            let body = response.text().await.unwrap_or_default();
+            warn!(
+                platform = "twitter",
+                request = "get_tweet",
+                status = status.as_u16(),
+                duration_ms,
+                %body,
+                "Failed to fetch tweet metrics"
+            );
+            None
+        }
+        Err(err) => {
+            error!(platform = "twitter", request = "get_tweet", error = ?err, "Error fetching tweet metrics");
+            None
+        }
+    }
+}
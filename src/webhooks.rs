@@ -0,0 +1,84 @@
+use serde::Serialize;
+use std::time::Instant;
+use tracing::{debug, error, warn};
+
+use crate::history::{HistoryEntry, PlatformResult};
+use crate::http;
+use crate::posts::PostError;
+use crate::settings;
+
+/// What a webhook receives for each platform a post went to (or failed to).
+#[derive(Serialize)]
+struct PostEvent<'a> {
+    /// "post.succeeded" or "post.failed".
+    event: &'static str,
+    platform: &'a str,
+    entry_id: &'a str,
+    posted_at: String,
+    author: Option<&'a str>,
+    text: &'a str,
+    post_id: Option<&'a str>,
+    url: Option<&'a str>,
+    error: Option<PostError>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    thread_ids: &'a [String],
+}
+
+/// The URLs from `settings.webhook_urls`.
+pub fn urls() -> Vec<String> {
+    settings::get()
+        .webhook_urls
+        .split([',', '\n'])
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty())
+        .collect()
+}
+
+/// POSTs a JSON event for each of `results` of `entry` to every configured webhook.  A webhook that can't be reached
+/// or answers with an error is logged and otherwise ignored, so it never holds up or fails a post.
+pub async fn notify(entry: &HistoryEntry, results: &[PlatformResult]) {
+    let urls = urls();
+    if urls.is_empty() {
+        return;
+    }
+    let client = http::client();
+    let events: Vec<PostEvent> = results
+        .iter()
+        .map(|result| PostEvent {
+            event: if result.success {
+                "post.succeeded"
+            } else {
+                "post.failed"
+            },
+            platform: &result.platform,
+            entry_id: &entry.id,
+            posted_at: entry.posted_at.to_rfc3339(),
+            author: entry.author.as_deref(),
+            text: &entry.text,
+            post_id: result.post_id.as_deref(),
+            url: result.url.as_deref(),
+            error: result.error,
+            thread_ids: &result.thread_ids,
+        })
+        .collect();
+
+    let calls = urls.iter().flat_map(|url| {
+        let client = &client;
+        events.iter().map(move |event| async move {
+            let started = Instant::now();
+            match http::send(client.post(url).json(event)).await {
+                Ok(response) if response.status().is_success() => {
+                    debug!(url = %url, event = event.event, platform = event.platform, "Called webhook");
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    let duration_ms = started.elapsed().as_millis() as u64;
+                    let body = response.text().await.unwrap_or_default();
+                    warn!(url = %url, status = status.as_u16(), duration_ms, %body, "Webhook call failed");
+                }
+                Err(err) => error!(url = %url, error = ?err, "Error calling webhook"),
+            }
+        })
+    });
+    futures::future::join_all(calls).await;
+}